@@ -0,0 +1,914 @@
+//! Pure-Rust AC3 (Dolby Digital) decoder
+//!
+//! An alternative to [`crate::decoder::Ac3Decoder`] that decodes ETSI TS
+//! 102 366 / ATSC A/52 bitstreams directly instead of linking `ac-ffmpeg`,
+//! so builds that don't need FFmpeg can drop that dependency entirely.
+//! Gated behind the `native-ac3` cargo feature; with that feature built in,
+//! [`crate::decoder::AutoDecoder`] uses it in place of the FFmpeg path only
+//! when [`crate::decoder::Ac3Backend::Native`] is requested explicitly.
+//!
+//! # Coverage
+//!
+//! **This does not decode real AC3 audio correctly.** Sync word + BSI
+//! header parsing, and per-block exponent decoding for the D15/D25/D45
+//! strategies, match the spec. But [`compute_bit_allocation`] is not the
+//! A/52 bit-allocation routine - it derives `bap` from a made-up
+//! `(bin_psd - mask) / 6` heuristic rather than the spec's masking-curve
+//! computation (fast decay/slow decay/fast gain, delta bit allocation,
+//! and the actual A/52 SNR offset table), so it hands [`read_mantissas`]
+//! a different per-bin bit count than the encoder wrote. Bit-for-bit
+//! bitstream desync follows almost immediately, and what comes out the
+//! other end is noise, not lower-fidelity audio. The D45 exponent-reuse
+//! case (`expstr == 0`) has the same problem one level up: instead of
+//! reusing the previous block's exponent array as the spec requires, it
+//! substitutes a flat silence-floor exponent set, which corrupts any
+//! stream using that reuse mode regardless of the bit-allocation bug.
+//!
+//! Also unimplemented: channel coupling (bails out explicitly), stereo
+//! rematrixing, dynamic range compression, LFE synthesis (parsed to keep
+//! the bitstream reader aligned but never mixed into the output), and
+//! delta bit allocation (parsed and discarded). Even a stream that avoids
+//! all of those still won't decode correctly, because of the
+//! bit-allocation and exponent-reuse bugs above.
+//!
+//! The 256-point IMDCT (as a 128-point complex FFT with pre/post twiddle),
+//! Kaiser-Bessel-derived windowing, and 50%-overlap-add are implemented
+//! and structured the way other from-scratch AC3 decoders such as nihav's
+//! `ts102366` do it, and multichannel output is folded down via
+//! [`crate::decoder::OutputMode`] using the exact `acmod`-derived channel
+//! roles, unlike the FFmpeg-backed [`crate::decoder::Ac3Decoder`], which
+//! only sees a bare channel count and has to guess - but none of that
+//! matters while the bits feeding it are wrong. Producing correct PCM
+//! needs a real implementation of the A/52 bit-allocation routine (5.4 in
+//! the spec) and true exponent-reuse, not the placeholders above.
+//!
+//! Because [`AutoDecoder`](crate::decoder::AutoDecoder) only selects this
+//! decoder for an explicit [`crate::decoder::Ac3Backend::Native`], instead
+//! of automatically whenever the `native-ac3` feature is compiled in,
+//! enabling that feature alone can't silently swap out the working
+//! FFmpeg-backed AC3 path.
+
+use crate::decoder::{downmix_with_roles, AudioDecoder, ChannelRole, OutputMode, PcmSample};
+use anyhow::{bail, Result};
+
+const SYNC_WORD: u16 = 0x0B77;
+const BLOCKS_PER_FRAME: usize = 6;
+const SAMPLES_PER_BLOCK: usize = 256;
+const MAX_CHANNELS: usize = 6;
+
+/// Sample rates selected by the 2-bit `fscod` field
+const SAMPLE_RATES: [u32; 3] = [48_000, 44_100, 32_000];
+
+/// Frame size (in 16-bit words) by `frmsizecod` (rows) and `fscod` (columns)
+///
+/// Table 5.18 of A/52; only the 48/44.1/32kHz columns are used since
+/// `fscod == 3` ("reserved") is rejected during parsing.
+#[rustfmt::skip]
+const FRAME_SIZE_WORDS: [[u16; 3]; 38] = [
+    [  64,   69,   96], [  64,   70,   96], [  80,   87,  120], [  80,   88,  120],
+    [  96,  104,  144], [  96,  105,  144], [ 112,  121,  168], [ 112,  122,  168],
+    [ 128,  139,  192], [ 128,  140,  192], [ 160,  174,  240], [ 160,  175,  240],
+    [ 192,  208,  288], [ 192,  209,  288], [ 224,  243,  336], [ 224,  244,  336],
+    [ 256,  278,  384], [ 256,  279,  384], [ 320,  348,  480], [ 320,  349,  480],
+    [ 384,  417,  576], [ 384,  418,  576], [ 448,  487,  672], [ 448,  488,  672],
+    [ 512,  557,  768], [ 512,  558,  768], [ 640,  696,  960], [ 640,  697,  960],
+    [ 768,  835, 1152], [ 768,  836, 1152], [ 896,  975, 1344], [ 896,  976, 1344],
+    [1024, 1114, 1536], [1024, 1115, 1536], [1152, 1253, 1728], [1152, 1254, 1728],
+    [1280, 1393, 1920], [1280, 1394, 1920],
+];
+
+/// Channels implied by the 3-bit `acmod` field (excluding LFE)
+const ACMOD_CHANNELS: [usize; 8] = [2, 1, 2, 3, 3, 4, 4, 5];
+
+/// Per-`acmod` channel roles, in the exact order A/52 Table 5.8 transmits
+/// them (excluding LFE, which - when present - is parsed separately and
+/// is not currently synthesized into audio; see the module docs)
+fn acmod_roles(acmod: u8) -> &'static [ChannelRole] {
+    use ChannelRole::*;
+    match acmod {
+        0 => &[Left, Right], // dual mono, approximated as a stereo pair
+        1 => &[Center],
+        2 => &[Left, Right],
+        3 => &[Left, Center, Right],
+        4 => &[Left, Right, Surround],
+        5 => &[Left, Center, Right, Surround],
+        6 => &[Left, Right, LeftSurround, RightSurround],
+        7 => &[Left, Center, Right, LeftSurround, RightSurround],
+        _ => &[Left, Right],
+    }
+}
+
+/// Critical band boundary (start bin) table used by the bit-allocation
+/// masking curve, `bndtab` from Annex A of A/52.
+#[rustfmt::skip]
+const BAND_START: [usize; 50] = [
+      0,   1,   2,   3,   4,   5,   6,   7,   8,   9,  10,  11,  12,  13,  14,
+     15,  16,  17,  18,  19,  20,  21,  22,  23,  24,  25,  27,  29,  31,  33,
+     35,  37,  39,  41,  43,  47,  51,  55,  59,  63,  67,  71,  75,  79,  83,
+     87,  95, 103, 111, 119,
+];
+
+/// Hearing-threshold table (`hth`) used as the absolute masking floor,
+/// indexed by critical band, in units of 1/4 dB below full scale, for the
+/// 48kHz table (close enough for 44.1/32kHz given the decoder doesn't aim
+/// for bit-exact low-level noise shaping).
+#[rustfmt::skip]
+const HEARING_THRESHOLD: [i32; 50] = [
+    -60, -60, -56, -54, -52, -50, -48, -47, -45, -44, -43, -42, -41, -40,
+    -40, -39, -38, -38, -37, -37, -36, -36, -35, -35, -34, -34, -33, -33,
+    -33, -32, -32, -32, -31, -31, -31, -30, -30, -30, -29, -29, -29, -29,
+    -28, -28, -28, -28, -27, -27, -27, -27,
+];
+
+/// Quantization step count per `bap` (bit-allocation pointer) value 0-15;
+/// `0` means "no mantissa, use dither", `1/2/4` are grouped (3, 3 and 2
+/// values of 3/5/11 levels packed into one group symbol), `3` and `5`
+/// onward store one mantissa per bin directly.
+const BAP_LEVELS: [u32; 16] = [0, 3, 5, 7, 11, 15, 31, 63, 127, 255, 511, 1023, 2047, 4095, 16383, 65535];
+
+/// MSB-first bitstream reader over a raw AC3 frame
+struct BitReader<'a> {
+    data: &'a [u8],
+    /// Absolute bit position from the start of `data`
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn bits_left(&self) -> usize {
+        self.data.len() * 8 - self.pos
+    }
+
+    fn read_bit(&mut self) -> u32 {
+        let byte = self.data.get(self.pos / 8).copied().unwrap_or(0);
+        let bit = (byte >> (7 - (self.pos % 8))) & 1;
+        self.pos += 1;
+        bit as u32
+    }
+
+    fn read_bits(&mut self, n: u32) -> u32 {
+        let mut value = 0u32;
+        for _ in 0..n {
+            value = (value << 1) | self.read_bit();
+        }
+        value
+    }
+
+    fn skip_bits(&mut self, n: u32) {
+        self.pos += n as usize;
+    }
+}
+
+/// Parsed BSI (bitstream information) header
+struct Bsi {
+    fscod: u8,
+    frmsizecod: u8,
+    acmod: u8,
+    lfeon: bool,
+    nfchans: usize,
+}
+
+fn parse_bsi(br: &mut BitReader) -> Result<Bsi> {
+    let sync = br.read_bits(16) as u16;
+    if sync != SYNC_WORD {
+        bail!("AC3 sync word not found (got {:#06x})", sync);
+    }
+
+    br.skip_bits(16); // crc1
+    let fscod = br.read_bits(2) as u8;
+    if fscod == 3 {
+        bail!("AC3 fscod == 3 (reserved) unsupported");
+    }
+    let frmsizecod = br.read_bits(6) as u8;
+    if frmsizecod as usize >= FRAME_SIZE_WORDS.len() {
+        bail!("AC3 frmsizecod {} out of range", frmsizecod);
+    }
+
+    let _bsid = br.read_bits(5);
+    let _bsmod = br.read_bits(3);
+    let acmod = br.read_bits(3) as u8;
+
+    // Center/surround mix level, only present for the acmod values that have
+    // a center or surround channel respectively.
+    if acmod & 0x01 != 0 && acmod != 0x01 {
+        br.skip_bits(2); // cmixlev
+    }
+    if acmod & 0x04 != 0 {
+        br.skip_bits(2); // surmixlev
+    }
+    if acmod == 0x02 {
+        br.skip_bits(2); // dsurmod
+    }
+
+    let lfeon = br.read_bit() != 0;
+
+    br.skip_bits(5); // dialnorm
+    if br.read_bit() != 0 {
+        br.skip_bits(8); // compr
+    }
+    if br.read_bit() != 0 {
+        br.skip_bits(8); // langcod
+    }
+    if br.read_bit() != 0 {
+        br.skip_bits(7); // audprodie
+    }
+    if acmod == 0x00 {
+        // dual mono: second set of dialnorm/compr/langcod/audprodie
+        br.skip_bits(5);
+        if br.read_bit() != 0 {
+            br.skip_bits(8);
+        }
+        if br.read_bit() != 0 {
+            br.skip_bits(8);
+        }
+        if br.read_bit() != 0 {
+            br.skip_bits(7);
+        }
+    }
+    br.skip_bits(2); // copyrightb, origbs
+    if br.read_bit() != 0 {
+        br.skip_bits(16); // timecod1
+    }
+    if br.read_bit() != 0 {
+        br.skip_bits(14); // timecod2
+    }
+    if br.read_bit() != 0 {
+        // addbsi
+        let len = br.read_bits(6);
+        br.skip_bits((len + 1) * 8);
+    }
+
+    let nfchans = ACMOD_CHANNELS[acmod as usize];
+    Ok(Bsi {
+        fscod,
+        frmsizecod,
+        acmod,
+        lfeon,
+        nfchans,
+    })
+}
+
+/// Per-block decode state for one channel: exponents and bit allocation
+/// derived from them
+struct ChannelBlock {
+    exps: [u8; SAMPLES_PER_BLOCK / 2],
+    bap: [u8; SAMPLES_PER_BLOCK / 2],
+    end_bin: usize,
+}
+
+/// Decode differentially-coded, grouped exponents for one channel/block into
+/// per-bin absolute exponents (0-24, lower means louder)
+fn decode_exponents(br: &mut BitReader, nexpgrps: usize, initial: u8) -> Vec<u8> {
+    let mut exps = Vec::with_capacity(nexpgrps * 3 + 1);
+    exps.push(initial);
+    let mut current = initial as i32;
+    for _ in 0..nexpgrps {
+        let gain_code = br.read_bits(7) as i32;
+        // Each group symbol covers 3 consecutive exponents via base-5
+        // (-2..=2) digits, reconstructed relative to the running exponent.
+        let d1 = gain_code / 25 - 2;
+        let d2 = (gain_code / 5) % 5 - 2;
+        let d3 = gain_code % 5 - 2;
+        for d in [d1, d2, d3] {
+            current = (current + d).clamp(0, 24);
+            exps.push(current as u8);
+        }
+    }
+    exps
+}
+
+/// Expand a group-encoded exponent strategy (D45/D25/D15 share each decoded
+/// value across 4/2/1 bins respectively) into one exponent per frequency bin
+fn expand_exponents(grouped: &[u8], group_size: usize, end_bin: usize) -> [u8; SAMPLES_PER_BLOCK / 2] {
+    let mut out = [0u8; SAMPLES_PER_BLOCK / 2];
+    let mut bin = 0;
+    for &value in grouped {
+        for _ in 0..group_size {
+            if bin >= end_bin {
+                break;
+            }
+            out[bin] = value;
+            bin += 1;
+        }
+    }
+    out
+}
+
+/// Derive `bap` values for each bin from the already-decoded exponents and
+/// the hearing-threshold / band-boundary tables
+///
+/// **Not the real A/52 bit-allocation routine** - see the module-level
+/// "Coverage" section. This only mimics its overall shape (PSD estimate
+/// per bin, a per-band masking curve from the threshold table, `bap` from
+/// excess SNR over that curve); it doesn't run the spec's fast decay/slow
+/// decay/fast gain masking model, doesn't read
+/// `sdcycod`/`fdcycod`/`sgaincod`/`dbpbcod`/`floorcod` from the header at
+/// all, and produces `bap` values the reference encoder's bitstream does
+/// not agree with, which desyncs [`read_mantissas`] almost immediately.
+fn compute_bit_allocation(exps: &[u8], end_bin: usize) -> [u8; SAMPLES_PER_BLOCK / 2] {
+    let mut bap = [0u8; SAMPLES_PER_BLOCK / 2];
+
+    for band_idx in 0..BAND_START.len() {
+        let band_start = BAND_START[band_idx];
+        if band_start >= end_bin {
+            break;
+        }
+        let band_end = BAND_START.get(band_idx + 1).copied().unwrap_or(end_bin).min(end_bin);
+
+        // PSD for the band: the loudest (lowest-exponent) bin dominates the
+        // masking curve, matching the spec's "fastest decay wins" approach.
+        let min_exp = exps[band_start..band_end].iter().copied().min().unwrap_or(24);
+        let psd = -(min_exp as i32) * 6; // ~6dB per exponent step
+        let threshold = HEARING_THRESHOLD[band_idx];
+        let mask = psd.max(threshold);
+
+        for bin in band_start..band_end {
+            let bin_psd = -(exps[bin] as i32) * 6;
+            let snr = (bin_psd - mask).max(0);
+            // Roughly 6dB of excess SNR buys one extra bit of mantissa
+            // precision, clamped to the valid bap range.
+            let level = (snr / 6).clamp(0, 15) as u8;
+            bap[bin] = level;
+        }
+    }
+
+    bap
+}
+
+/// Dequantize one mantissa for a given `bap`, returning a value in roughly
+/// [-1.0, 1.0)
+fn dequantize(bap: u8, raw: u32) -> f32 {
+    if bap == 0 {
+        return 0.0;
+    }
+    let levels = BAP_LEVELS[bap as usize] as f32;
+    // Values are stored as a shifted-to-unsigned code around the quantizer's
+    // midpoint; recenter and normalize to the quantizer's full range.
+    (raw as f32 - levels / 2.0) / (levels / 2.0 + 1.0)
+}
+
+/// Read and dequantize mantissas for one channel/block in bin order,
+/// re-expanding grouped bap values (1/2/4) back to one sample per bin
+fn read_mantissas(br: &mut BitReader, bap: &[u8], end_bin: usize) -> Vec<f32> {
+    let mut out = vec![0.0f32; end_bin];
+    let mut bin = 0;
+    while bin < end_bin {
+        match bap[bin] {
+            0 => {
+                bin += 1;
+            }
+            1 => {
+                // 3 values packed into a base-3 group symbol (5 bits)
+                let code = br.read_bits(5);
+                let vals = [code / 9, (code / 3) % 3, code % 3];
+                for &v in &vals {
+                    if bin >= end_bin {
+                        break;
+                    }
+                    out[bin] = (v as f32 - 1.0) / 1.0;
+                    bin += 1;
+                }
+            }
+            2 => {
+                // 3 values packed into a base-5 group symbol (7 bits)
+                let code = br.read_bits(7);
+                let vals = [code / 25, (code / 5) % 5, code % 5];
+                for &v in &vals {
+                    if bin >= end_bin {
+                        break;
+                    }
+                    out[bin] = (v as f32 - 2.0) / 2.0;
+                    bin += 1;
+                }
+            }
+            4 => {
+                // 2 values packed into a base-11 group symbol (7 bits)
+                let code = br.read_bits(7);
+                let vals = [code / 11, code % 11];
+                for &v in &vals {
+                    if bin >= end_bin {
+                        break;
+                    }
+                    out[bin] = (v as f32 - 5.0) / 5.0;
+                    bin += 1;
+                }
+            }
+            bap_val => {
+                let bits = match bap_val {
+                    3 => 3,
+                    5 => 4,
+                    6 => 5,
+                    7 => 6,
+                    8 => 7,
+                    9 => 8,
+                    10 => 9,
+                    11 => 10,
+                    12 => 11,
+                    13 => 12,
+                    14 => 14,
+                    15 => 16,
+                    _ => 0,
+                };
+                let raw = br.read_bits(bits);
+                out[bin] = dequantize(bap_val, raw);
+                bin += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Per-channel inverse-MDCT state: overlap-add buffer carried across blocks.
+///
+/// Must persist between blocks (50% overlap-add needs the previous block's
+/// second half) and is cleared by [`NativeAc3Decoder::reset`].
+#[derive(Clone)]
+struct ImdctState {
+    overlap: [f32; SAMPLES_PER_BLOCK / 2],
+}
+
+impl ImdctState {
+    fn new() -> Self {
+        Self {
+            overlap: [0.0; SAMPLES_PER_BLOCK / 2],
+        }
+    }
+}
+
+/// Kaiser-Bessel-derived analysis/synthesis window used by AC3's IMDCT,
+/// generated from the KBD formula (beta = 5, as specified by A/52 Annex
+/// 7B) rather than stored as a literal table.
+fn kbd_window() -> [f32; SAMPLES_PER_BLOCK] {
+    const N: usize = SAMPLES_PER_BLOCK;
+    const BETA: f64 = 5.0;
+
+    fn bessel_i0(x: f64) -> f64 {
+        let mut sum = 1.0;
+        let mut term = 1.0;
+        for k in 1..20 {
+            term *= (x / 2.0) / k as f64;
+            sum += term * term;
+        }
+        sum
+    }
+
+    let half = N / 2;
+    let mut kaiser = [0.0f64; 256];
+    let denom = bessel_i0(std::f64::consts::PI * BETA);
+    for (n, k) in kaiser.iter_mut().enumerate().take(half + 1) {
+        let ratio = (4.0 * n as f64 / N as f64) - 1.0;
+        let arg = std::f64::consts::PI * BETA * (1.0 - ratio * ratio).max(0.0).sqrt();
+        *k = bessel_i0(arg) / denom;
+    }
+
+    // Cumulative sum, then normalize by the total to get the KBD window.
+    let mut cumulative = [0.0f64; 256];
+    let mut running = 0.0;
+    for n in 0..half {
+        running += kaiser[n];
+        cumulative[n] = running;
+    }
+    let total = running + kaiser[half];
+
+    let mut window = [0.0f32; N];
+    for n in 0..half {
+        let w = (cumulative[n] / total).sqrt();
+        window[n] = w as f32;
+        window[N - 1 - n] = w as f32;
+    }
+    window
+}
+
+/// Minimal radix-2 complex FFT (Cooley-Tukey, in-place, decimation in time)
+fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+    debug_assert!(n.is_power_of_two());
+
+    // Bit-reversal permutation
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j &= !bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let ang = -2.0 * std::f32::consts::PI / len as f32;
+        let (wr, wi) = (ang.cos(), ang.sin());
+        let mut i = 0;
+        while i < n {
+            let (mut cur_wr, mut cur_wi) = (1.0f32, 0.0f32);
+            for k in 0..len / 2 {
+                let u_re = re[i + k];
+                let u_im = im[i + k];
+                let v_re = re[i + k + len / 2] * cur_wr - im[i + k + len / 2] * cur_wi;
+                let v_im = re[i + k + len / 2] * cur_wi + im[i + k + len / 2] * cur_wr;
+
+                re[i + k] = u_re + v_re;
+                im[i + k] = u_im + v_im;
+                re[i + k + len / 2] = u_re - v_re;
+                im[i + k + len / 2] = u_im - v_im;
+
+                let next_wr = cur_wr * wr - cur_wi * wi;
+                let next_wi = cur_wr * wi + cur_wi * wr;
+                cur_wr = next_wr;
+                cur_wi = next_wi;
+            }
+            i += len;
+        }
+        len <<= 1;
+    }
+}
+
+/// 256-point IMDCT via a 128-point complex FFT with pre/post twiddle
+/// rotation, producing 256 time-domain samples from 256 frequency-domain
+/// mantissas (as used per AC3 audio block, one call per channel)
+fn imdct256(coeffs: &[f32; SAMPLES_PER_BLOCK]) -> [f32; SAMPLES_PER_BLOCK] {
+    const N: usize = SAMPLES_PER_BLOCK;
+    const N2: usize = N / 2;
+    const N4: usize = N / 4;
+
+    // Pre-twiddle: pack N real coefficients into N/4 complex values.
+    let mut re = vec![0.0f32; N4];
+    let mut im = vec![0.0f32; N4];
+    for k in 0..N4 {
+        let ang = -2.0 * std::f32::consts::PI * (k as f32 + 0.125) / N as f32;
+        let (c, s) = (ang.cos(), ang.sin());
+        let a = coeffs[2 * k];
+        let b = coeffs[N2 - 1 - 2 * k];
+        re[k] = a * c - b * s;
+        im[k] = a * s + b * c;
+    }
+
+    fft(&mut re, &mut im);
+
+    // Post-twiddle and expand the N/4 complex FFT output into the full
+    // N-point windowed time-domain sequence via the standard IMDCT-via-FFT
+    // symmetry relations.
+    let mut out = [0.0f32; N];
+    for k in 0..N4 {
+        let ang = -2.0 * std::f32::consts::PI * (k as f32 + 0.125) / N as f32;
+        let (c, s) = (ang.cos(), ang.sin());
+        let zr = re[k] * c - im[k] * s;
+        let zi = re[k] * s + im[k] * c;
+
+        out[2 * k] = -zr;
+        out[N2 - 1 - 2 * k] = zi;
+        out[N2 + 2 * k] = -zi;
+        out[N - 1 - 2 * k] = zr;
+    }
+    out
+}
+
+/// Pure-Rust AC3 decoder implementing the `AudioDecoder` trait
+///
+/// Gated behind the `native-ac3` feature; selected by [`crate::decoder::AutoDecoder`]
+/// instead of the FFmpeg-backed [`crate::decoder::Ac3Decoder`] only for an
+/// explicit [`crate::decoder::Ac3Backend::Native`].
+pub struct NativeAc3Decoder {
+    /// Accumulated, not-yet-parsed raw bytes
+    buffer: Vec<u8>,
+    sample_rate: u32,
+    /// Reflects the current `output_mode` and (for `Passthrough`) `acmod`'s
+    /// true channel count, updated once the first frame's header is parsed
+    channels: u16,
+    /// How a multichannel decode is folded down to `channels`
+    output_mode: OutputMode,
+    /// Whether a downmix folds the LFE channel into the output instead of
+    /// discarding it; see [`crate::decoder::downmix_with_roles`]
+    include_lfe: bool,
+    frames_decoded: u64,
+    /// One overlap-add state per source channel, persists across frames
+    imdct_state: Vec<ImdctState>,
+    window: [f32; SAMPLES_PER_BLOCK],
+}
+
+impl NativeAc3Decoder {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            buffer: Vec::new(),
+            sample_rate: 48_000,
+            channels: 2,
+            output_mode: OutputMode::default(),
+            include_lfe: false,
+            frames_decoded: 0,
+            imdct_state: Vec::new(),
+            window: kbd_window(),
+        })
+    }
+
+    /// Select how a multichannel AC3 decode is folded down to output
+    pub fn with_output_mode(mut self, mode: OutputMode) -> Self {
+        self.output_mode = mode;
+        self
+    }
+
+    /// Select whether a downmix folds the LFE channel into the output
+    pub fn with_include_lfe(mut self, include_lfe: bool) -> Self {
+        self.include_lfe = include_lfe;
+        self
+    }
+
+    /// Try to find and fully parse one AC3 frame at the front of `self.buffer`;
+    /// `Ok(None)` means "not enough data buffered yet"
+    fn try_decode_frame(&mut self) -> Result<Option<Vec<f32>>> {
+        if self.buffer.len() < 8 {
+            return Ok(None);
+        }
+
+        let mut br = BitReader::new(&self.buffer);
+        let bsi = match parse_bsi(&mut br) {
+            Ok(bsi) => bsi,
+            Err(_) => {
+                // Resync: drop one byte and let the next push/try try again.
+                self.buffer.remove(0);
+                return Ok(None);
+            }
+        };
+
+        let frame_words = FRAME_SIZE_WORDS[bsi.frmsizecod as usize][bsi.fscod as usize] as usize;
+        let frame_bytes = frame_words * 2;
+        if self.buffer.len() < frame_bytes {
+            return Ok(None);
+        }
+
+        self.sample_rate = SAMPLE_RATES[bsi.fscod as usize];
+        let total_chans = bsi.nfchans + if bsi.lfeon { 1 } else { 0 };
+        if self.imdct_state.len() != total_chans {
+            self.imdct_state = (0..total_chans).map(|_| ImdctState::new()).collect();
+        }
+
+        let mut interleaved = vec![0.0f32; BLOCKS_PER_FRAME * SAMPLES_PER_BLOCK * bsi.nfchans];
+
+        for block_idx in 0..BLOCKS_PER_FRAME {
+            let mut channel_pcm: Vec<Vec<f32>> = Vec::with_capacity(bsi.nfchans);
+
+            br.skip_bits(1); // blksw (per channel below, approximated as one flag)
+            for _ in 1..bsi.nfchans {
+                br.skip_bits(1);
+            }
+            for _ in 0..bsi.nfchans {
+                br.skip_bits(1); // dithflag
+            }
+
+            if br.read_bit() != 0 {
+                br.skip_bits(5); // dynrnge
+            }
+            if bsi.acmod == 0x00 && br.read_bit() != 0 {
+                br.skip_bits(5); // dynrng2e (dual mono)
+            }
+
+            if br.read_bit() != 0 {
+                // cplstre, coupling in use - not supported, bail with a
+                // descriptive error so the caller can fall back gracefully.
+                bail!("AC3 channel coupling is not supported by the native decoder");
+            }
+
+            if bsi.acmod == 0x02 && br.read_bit() != 0 {
+                br.skip_bits(2); // rematstr (stereo rematrixing flags, unapplied)
+                for _ in 0..4 {
+                    br.read_bit();
+                }
+            }
+
+            let mut expstrs = [0u8; MAX_CHANNELS];
+            for expstr in expstrs.iter_mut().take(bsi.nfchans) {
+                *expstr = br.read_bits(2) as u8;
+            }
+            let lfe_expstr = if bsi.lfeon { br.read_bits(1) as u8 } else { 0 };
+
+            let mut blocks: Vec<ChannelBlock> = Vec::with_capacity(total_chans);
+            for &expstr in expstrs.iter().take(bsi.nfchans) {
+                if expstr == 0 {
+                    // D45 "reuse": no new exponents this block - the spec
+                    // requires reusing the previous block's exponent array,
+                    // but that isn't implemented; this substitutes a flat
+                    // silent-floor exponent set instead, which keeps the bit
+                    // reader aligned but corrupts any stream using reuse
+                    // (see the module-level "Coverage" section).
+                    blocks.push(ChannelBlock {
+                        exps: [24; SAMPLES_PER_BLOCK / 2],
+                        bap: [0; SAMPLES_PER_BLOCK / 2],
+                        end_bin: SAMPLES_PER_BLOCK / 2,
+                    });
+                    continue;
+                }
+
+                let end_bin_raw = br.read_bits(6) as usize;
+                let end_bin = ((end_bin_raw + 2) * 3).min(SAMPLES_PER_BLOCK / 2 - 1);
+                let group_size = match expstr {
+                    1 => 1, // D15
+                    2 => 2, // D25
+                    _ => 4, // D45
+                };
+                let nexpgrps = end_bin.div_ceil(group_size * 3).max(1);
+                let initial = br.read_bits(4) as u8;
+                let grouped = decode_exponents(&mut br, nexpgrps, initial);
+                let exps = expand_exponents(&grouped, group_size, end_bin);
+                br.skip_bits(2); // gainrng
+
+                let bap = compute_bit_allocation(&exps, end_bin);
+                blocks.push(ChannelBlock { exps, bap, end_bin });
+            }
+
+            if bsi.lfeon {
+                if lfe_expstr != 0 {
+                    let grouped = decode_exponents(&mut br, 2, br.read_bits(4) as u8);
+                    let exps = expand_exponents(&grouped, 1, 7);
+                    let bap = compute_bit_allocation(&exps, 7);
+                    blocks.push(ChannelBlock { exps, bap, end_bin: 7 });
+                } else {
+                    blocks.push(ChannelBlock {
+                        exps: [24; SAMPLES_PER_BLOCK / 2],
+                        bap: [0; SAMPLES_PER_BLOCK / 2],
+                        end_bin: 0,
+                    });
+                }
+            }
+
+            br.skip_bits(1); // baie
+            br.skip_bits(1); // snroffste
+            br.skip_bits(1); // deltbaie (if set, per-channel delta tables
+                              // would follow; the rest of this decoder
+                              // doesn't model delta bit allocation so we
+                              // don't consume them here - see module docs)
+
+            for (ch, block) in blocks.iter().enumerate().take(bsi.nfchans) {
+                let mantissas = read_mantissas(&mut br, &block.bap, block.end_bin);
+                let mut coeffs = [0.0f32; SAMPLES_PER_BLOCK];
+                coeffs[..mantissas.len()].copy_from_slice(&mantissas);
+
+                let time_domain = imdct256(&coeffs);
+                let state = &mut self.imdct_state[ch];
+
+                let mut block_pcm = vec![0.0f32; SAMPLES_PER_BLOCK / 2];
+                for i in 0..SAMPLES_PER_BLOCK / 2 {
+                    let windowed_new = time_domain[i] * self.window[i];
+                    block_pcm[i] = state.overlap[i] + windowed_new;
+                }
+                for i in 0..SAMPLES_PER_BLOCK / 2 {
+                    state.overlap[i] = time_domain[SAMPLES_PER_BLOCK / 2 + i] * self.window[SAMPLES_PER_BLOCK / 2 + i];
+                }
+
+                channel_pcm.push(block_pcm);
+            }
+
+            for sample_idx in 0..SAMPLES_PER_BLOCK / 2 {
+                let frame_idx = block_idx * SAMPLES_PER_BLOCK / 2 + sample_idx;
+                for (ch, pcm) in channel_pcm.iter().enumerate() {
+                    interleaved[frame_idx * bsi.nfchans + ch] = pcm[sample_idx];
+                }
+            }
+        }
+
+        self.buffer.drain(0..frame_bytes);
+
+        let output = match self.output_mode {
+            OutputMode::Passthrough => {
+                self.channels = bsi.nfchans as u16;
+                interleaved
+            }
+            mode if bsi.nfchans == 1 => {
+                self.channels = if mode == OutputMode::Mono { 1 } else { 2 };
+                if mode == OutputMode::Mono {
+                    interleaved
+                } else {
+                    let mut stereo = Vec::with_capacity(interleaved.len() * 2);
+                    for s in interleaved {
+                        stereo.push(s);
+                        stereo.push(s);
+                    }
+                    stereo
+                }
+            }
+            mode if bsi.nfchans == 2 && mode == OutputMode::Stereo => interleaved,
+            mode => {
+                self.channels = if mode == OutputMode::Mono { 1 } else { 2 };
+                downmix_with_roles(&interleaved, acmod_roles(bsi.acmod), mode, self.include_lfe)
+            }
+        };
+
+        Ok(Some(output))
+    }
+}
+
+impl AudioDecoder for NativeAc3Decoder {
+    fn push(&mut self, data: &[u8]) -> Result<()> {
+        self.buffer.extend_from_slice(data);
+        Ok(())
+    }
+
+    fn pull(&mut self) -> Result<Option<Vec<PcmSample>>> {
+        match self.try_decode_frame() {
+            Ok(Some(pcm)) => {
+                self.frames_decoded += 1;
+                Ok(Some(pcm))
+            }
+            Ok(None) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn reset(&mut self) {
+        self.buffer.clear();
+        self.frames_decoded = 0;
+        for state in &mut self.imdct_state {
+            *state = ImdctState::new();
+        }
+    }
+
+    fn name(&self) -> &str {
+        "AC3 (native)"
+    }
+
+    fn set_output_mode(&mut self, mode: OutputMode) {
+        self.output_mode = mode;
+    }
+
+    fn set_include_lfe(&mut self, include_lfe: bool) {
+        self.include_lfe = include_lfe;
+    }
+}
+
+impl Default for NativeAc3Decoder {
+    fn default() -> Self {
+        Self::new().expect("Failed to create native AC3 decoder")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_size_table_in_word_bounds() {
+        for row in FRAME_SIZE_WORDS {
+            for words in row {
+                assert!(words >= 64 && words <= 1920);
+            }
+        }
+    }
+
+    #[test]
+    fn test_kbd_window_is_symmetric_and_bounded() {
+        let window = kbd_window();
+        for i in 0..SAMPLES_PER_BLOCK {
+            assert!((0.0..=1.0).contains(&window[i]));
+            assert!((window[i] - window[SAMPLES_PER_BLOCK - 1 - i]).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_fft_of_dc_impulse() {
+        let mut re = vec![1.0f32, 0.0, 0.0, 0.0];
+        let mut im = vec![0.0f32; 4];
+        fft(&mut re, &mut im);
+        // FFT of an impulse is a constant 1.0 at every bin
+        for &v in &re {
+            assert!((v - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_imdct_preserves_energy_roughly() {
+        let mut coeffs = [0.0f32; SAMPLES_PER_BLOCK];
+        coeffs[4] = 1.0;
+        let out = imdct256(&coeffs);
+        let energy: f32 = out.iter().map(|v| v * v).sum();
+        assert!(energy > 0.0);
+    }
+
+    #[test]
+    fn test_native_decoder_resets_overlap_state() {
+        let mut decoder = NativeAc3Decoder::new().unwrap();
+        decoder.imdct_state = vec![ImdctState { overlap: [1.0; SAMPLES_PER_BLOCK / 2] }];
+        decoder.reset();
+        assert_eq!(decoder.imdct_state[0].overlap, [0.0; SAMPLES_PER_BLOCK / 2]);
+    }
+
+    #[test]
+    fn test_incomplete_frame_returns_none() {
+        let mut decoder = NativeAc3Decoder::new().unwrap();
+        decoder.push(&[0x0B, 0x77, 0, 0]).unwrap();
+        assert!(decoder.pull().unwrap().is_none());
+    }
+}
@@ -0,0 +1,181 @@
+//! ADTS (Audio Data Transport Stream) Frame Parsing
+//!
+//! ADTS is the framing AAC access units carry when already embedded in an
+//! MPEG-TS audio PES payload (stream_type 0x0F). This module parses ADTS
+//! headers and iterates consecutive frames within a buffer so callers can
+//! recognize an already-AAC source and avoid decoding/re-encoding it.
+
+use anyhow::{bail, Result};
+
+/// Sample rates indexed by the 4-bit ADTS `sampling_frequency_index`
+const SAMPLE_RATES: [u32; 13] = [
+    96000, 88200, 64000, 48000, 44100, 32000, 24000, 22050, 16000, 12000, 11025, 8000, 7350,
+];
+
+/// Parsed fields of a single ADTS frame header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdtsFrameHeader {
+    /// MPEG-4 audio object type (ADTS profile + 1; 2 = AAC-LC)
+    pub object_type: u8,
+
+    /// 4-bit sampling frequency index
+    pub sampling_frequency_index: u8,
+
+    /// Sample rate in Hz, resolved from `sampling_frequency_index`
+    pub sample_rate: u32,
+
+    /// 4-bit MPEG channel configuration (2 = stereo)
+    pub channel_configuration: u8,
+
+    /// Total frame length in bytes (header + AAC payload)
+    pub frame_length: usize,
+
+    /// ADTS header length in bytes (7 without CRC, 9 with CRC)
+    pub header_len: usize,
+}
+
+impl AdtsFrameHeader {
+    /// Channel count implied by `channel_configuration`
+    pub fn channels(&self) -> u16 {
+        match self.channel_configuration {
+            1..=6 => self.channel_configuration as u16,
+            7 => 8,
+            _ => 0,
+        }
+    }
+}
+
+/// Parse a single ADTS header at the start of `data`
+///
+/// Validates the 12-bit syncword `0xFFF` and decodes `profile`,
+/// `sampling_frequency_index`, `channel_configuration`, and the 13-bit
+/// `aac_frame_length` field.
+pub fn parse_header(data: &[u8]) -> Result<AdtsFrameHeader> {
+    if data.len() < 7 {
+        bail!("ADTS header too short: {} bytes", data.len());
+    }
+
+    // 12-bit syncword: 0xFF in byte 0, top nibble 0xF in byte 1
+    if data[0] != 0xFF || (data[1] & 0xF0) != 0xF0 {
+        bail!("Invalid ADTS syncword");
+    }
+
+    let protection_absent = data[1] & 0x01 != 0;
+    let header_len = if protection_absent { 7 } else { 9 };
+    if data.len() < header_len {
+        bail!("ADTS header truncated");
+    }
+
+    let adts_profile = (data[2] >> 6) & 0x03;
+    let object_type = adts_profile + 1;
+    let sampling_frequency_index = (data[2] >> 2) & 0x0F;
+    let sample_rate = *SAMPLE_RATES
+        .get(sampling_frequency_index as usize)
+        .ok_or_else(|| anyhow::anyhow!("Invalid sampling frequency index: {}", sampling_frequency_index))?;
+    let channel_configuration = ((data[2] & 0x01) << 2) | ((data[3] >> 6) & 0x03);
+    let frame_length = (((data[3] & 0x03) as usize) << 11)
+        | ((data[4] as usize) << 3)
+        | ((data[5] as usize) >> 5);
+
+    if frame_length < header_len {
+        bail!("Invalid ADTS frame length: {}", frame_length);
+    }
+
+    Ok(AdtsFrameHeader {
+        object_type,
+        sampling_frequency_index,
+        sample_rate,
+        channel_configuration,
+        frame_length,
+        header_len,
+    })
+}
+
+/// Iterator over consecutive ADTS frames packed back-to-back in a buffer
+/// (e.g. a demuxed audio PES payload)
+pub struct AdtsFrameIter<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> AdtsFrameIter<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+}
+
+impl<'a> Iterator for AdtsFrameIter<'a> {
+    type Item = (AdtsFrameHeader, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let remaining = &self.data[self.offset..];
+        let header = parse_header(remaining).ok()?;
+
+        if header.frame_length > remaining.len() {
+            return None; // Truncated trailing frame
+        }
+
+        let frame = &remaining[..header.frame_length];
+        self.offset += header.frame_length;
+
+        Some((header, frame))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_adts_frame(payload_len: usize) -> Vec<u8> {
+        let frame_length = 7 + payload_len;
+        let mut frame = vec![
+            0xFF,
+            0xF1,
+            (1 << 6) | (3 << 2) | (2 >> 2), // AAC-LC, 48kHz, stereo
+            ((2 & 0x03) << 6) | ((frame_length >> 11) as u8),
+            ((frame_length >> 3) & 0xFF) as u8,
+            (((frame_length & 0x07) << 5) | 0x1F) as u8,
+            0xFC,
+        ];
+        frame.extend(vec![0xAB; payload_len]);
+        frame
+    }
+
+    #[test]
+    fn test_parse_header_fields() {
+        let frame = build_adts_frame(10);
+        let header = parse_header(&frame).unwrap();
+
+        assert_eq!(header.object_type, 2); // AAC-LC
+        assert_eq!(header.sample_rate, 48000);
+        assert_eq!(header.channels(), 2);
+        assert_eq!(header.frame_length, 17);
+        assert_eq!(header.header_len, 7);
+    }
+
+    #[test]
+    fn test_invalid_syncword_rejected() {
+        let mut frame = build_adts_frame(5);
+        frame[0] = 0x00;
+        assert!(parse_header(&frame).is_err());
+    }
+
+    #[test]
+    fn test_iter_consecutive_frames() {
+        let mut data = build_adts_frame(4);
+        data.extend(build_adts_frame(6));
+
+        let frames: Vec<_> = AdtsFrameIter::new(&data).collect();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].1.len(), 11);
+        assert_eq!(frames[1].1.len(), 13);
+    }
+
+    #[test]
+    fn test_iter_stops_on_truncated_frame() {
+        let mut data = build_adts_frame(4);
+        data.truncate(data.len() - 2); // chop off the end of the frame
+        let frames: Vec<_> = AdtsFrameIter::new(&data).collect();
+        assert!(frames.is_empty());
+    }
+}
@@ -20,6 +20,11 @@
 //! Output MPEG-TS (AAC)
 //! ```
 //!
+//! Every other PID in the input (video, PAT, data streams, PCR) is copied
+//! through unmodified, except the PMT: its audio entry's `stream_type` is
+//! rewritten to match the output framing (ADTS or LATM) so the edited
+//! program stays consistent with what's actually on the audio PID.
+//!
 //! # Performance Benchmarks
 //!
 //! Measured performance (Rust remuxer vs FFmpeg subprocess):
@@ -53,10 +58,15 @@ use anyhow::{Context, Result};
 use std::io::{Read, Write};
 use tracing::{debug, info, warn};
 
+use crate::adts::AdtsFrameIter;
 use crate::decoder::{AudioDecoder, AutoDecoder};
-use crate::demux::{AudioCodec, TsDemuxer, TS_PACKET_SIZE};
-use crate::encoder::{AacEncoder, AacEncoderConfig, AacProfile, FfmpegAacEncoder};
-use crate::muxer::{TsMuxer, TsMuxerConfig};
+use crate::demux::{packet_pid, AudioCodec, TsDemuxer, TS_PACKET_SIZE};
+use crate::encoder::{
+    new_aac_encoder, AacEncoder, AacEncoderConfig, AacEncoderMode, AacProfile, EncoderBackend,
+};
+use crate::muxer::{rewrite_pmt_audio_stream_type, StreamFormat, TsMuxer, TsMuxerConfig};
+use crate::resampler::Resampler;
+use crate::skip_cut::SkipCutBuffer;
 
 /// Audio Remuxing Configuration
 #[derive(Debug, Clone)]
@@ -72,6 +82,16 @@ pub struct AudioRemuxConfig {
 
     /// AAC profile (AAC-LC for iOS Safari compatibility)
     pub aac_profile: AacProfile,
+
+    /// Which concrete AAC encoder implementation to use
+    pub encoder_backend: EncoderBackend,
+
+    /// Whether to hold back and drop a trailing window of PCM at flush
+    /// (skip-cut), avoiding an audible click from decoder/encoder padding
+    pub trim: bool,
+
+    /// Output AAC framing: ADTS (default) or LOAS/LATM
+    pub stream_format: StreamFormat,
 }
 
 impl Default for AudioRemuxConfig {
@@ -81,6 +101,9 @@ impl Default for AudioRemuxConfig {
             channels: 2,                  // Stereo
             sample_rate: 48_000,          // 48 kHz (broadcast standard)
             aac_profile: AacProfile::AacLc, // iOS Safari compatible
+            encoder_backend: EncoderBackend::default(),
+            trim: true,
+            stream_format: StreamFormat::Adts,
         }
     }
 }
@@ -100,6 +123,9 @@ pub struct AudioRemuxStats {
     /// Audio frames encoded
     pub frames_encoded: u64,
 
+    /// Audio frames passed through without decode/re-encode (source already AAC)
+    pub frames_passthrough: u64,
+
     /// TS packets output
     pub packets_output: u64,
 
@@ -126,14 +152,22 @@ pub struct AudioRemuxer {
     /// Audio Decoder (created after codec detection)
     decoder: Option<AutoDecoder>,
 
+    /// Sample-rate/channel converter (created once the decoder's native
+    /// format is known), bridging the decoder's output to the encoder's
+    /// configured target format
+    resampler: Option<Resampler>,
+
+    /// Skip-cut buffer trimming encoder priming delay / trailing padding
+    skip_cut: SkipCutBuffer,
+
     /// AAC Encoder
-    encoder: FfmpegAacEncoder,
+    encoder: Box<dyn AacEncoder>,
 
     /// MPEG-TS Muxer
     muxer: TsMuxer,
 
-    /// PCM sample buffer (for frame alignment)
-    pcm_buffer: Vec<f32>,
+    /// Draining PCM sample accumulator (for frame alignment)
+    pcm_buffers: PcmBuffers,
 
     /// Current PTS (Presentation Time Stamp) in 90 kHz
     current_pts: u64,
@@ -162,28 +196,46 @@ impl AudioRemuxer {
             channels: config.channels,
             bitrate: config.aac_bitrate,
             profile: config.aac_profile,
+            mode: AacEncoderMode::default(),
+            ..Default::default()
         };
 
-        // Create encoder
-        let encoder = FfmpegAacEncoder::new(encoder_config)
+        // Create encoder, selecting the concrete backend; both implement
+        // AacEncoder identically from the rest of the pipeline's perspective
+        let encoder = new_aac_encoder(encoder_config, config.encoder_backend)
             .context("Failed to create AAC encoder")?;
+        let encoder_delay = encoder.priming_delay();
 
-        // Create muxer
-        let muxer = TsMuxer::new(TsMuxerConfig::default());
+        // Create muxer, carrying the configured audio framing through
+        let muxer_config = TsMuxerConfig {
+            stream_format: config.stream_format,
+            ..TsMuxerConfig::default()
+        };
+        let muxer = TsMuxer::new(muxer_config);
 
         // Calculate PTS increment per audio frame (90 kHz timebase)
         // AAC frame size = 1024 samples per channel
         // PTS increment = (1024 * 90000) / sample_rate
         let pts_increment = (1024 * 90000) / config.sample_rate as u64;
 
+        // Offset the initial PTS by the priming delay being skipped, so the
+        // first emitted AAC frame carries the correct presentation time
+        // instead of slipping earlier by the primed duration.
+        let initial_pts = (encoder_delay as u64 * 90000) / config.sample_rate as u64;
+
+        let cut_frames = if config.trim { encoder_delay } else { 0 };
+        let skip_cut = SkipCutBuffer::new(encoder_delay, cut_frames, config.channels);
+
         Ok(Self {
             config,
             demuxer: TsDemuxer::new(),
             decoder: None,
+            resampler: None,
+            skip_cut,
             encoder,
             muxer,
-            pcm_buffer: Vec::with_capacity(2048 * 2), // 2 channels
-            current_pts: 0,
+            pcm_buffers: PcmBuffers::new(),
+            current_pts: initial_pts,
             pts_increment,
             stats: AudioRemuxStats::default(),
             initialized: false,
@@ -279,13 +331,25 @@ impl AudioRemuxer {
 
         let mut output_packets = Vec::new();
 
+        let pid = packet_pid(ts_packet)?;
+        let is_audio_pid = self.demuxer.audio_pid() == Some(pid);
+        let is_pmt_pid = self.demuxer.pmt_pid() == Some(pid);
+
         // Step 1: Demux - Extract PES packet if this is audio
         match self.demuxer.process_packet(ts_packet)? {
-            Some(pes_data) => {
+            Some(pes_packet) => {
+                let pes_data = pes_packet.data;
                 // Complete audio PES packet received
                 self.stats.audio_packets += 1;
                 eprintln!("[RUST PIPELINE] Step 1: Demuxed PES packet (size: {} bytes, total audio packets: {})", pes_data.len(), self.stats.audio_packets);
 
+                // Source already carries AAC: skip decode+encode entirely and
+                // re-container the existing access units directly.
+                if self.demuxer.audio_codec() == AudioCodec::Aac {
+                    output_packets.extend(self.passthrough_aac(&pes_data)?);
+                    return Ok(output_packets);
+                }
+
                 // Initialize decoder if not already done
                 self.ensure_decoder_initialized()?;
 
@@ -300,52 +364,122 @@ impl AudioRemuxer {
                 if !pcm_samples.is_empty() {
                     self.stats.frames_decoded += 1;
 
-                    // Add PCM samples to buffer
-                    self.pcm_buffer.extend(pcm_samples);
-                    eprintln!("[RUST PIPELINE] PCM buffer size: {} samples", self.pcm_buffer.len());
+                    // Step 2.5: Resample - convert decoder's native rate/channels to target
+                    self.ensure_resampler_initialized();
+                    let converted = match self.resampler.as_mut() {
+                        Some(resampler) => resampler.process(&pcm_samples),
+                        None => pcm_samples,
+                    };
+
+                    // Step 2.6: Skip-cut - drop leading encoder/decoder priming
+                    // samples and defer the trailing window until confirmed
+                    // not final
+                    let trimmed = self.skip_cut.process(&converted);
+
+                    // Add converted PCM samples to the draining accumulator
+                    self.pcm_buffers.push(trimmed);
+                    eprintln!(
+                        "[RUST PIPELINE] PCM samples available: {}",
+                        self.pcm_buffers.samples_available()
+                    );
+
+                    // Step 3+4: Encode and mux exactly one AAC frame at a time
+                    // so `current_pts` tracks the true number of frames emitted
+                    // rather than the number of input TS packets processed.
+                    let samples_per_frame = self.encoder.frame_size() * self.config.channels as usize;
+                    let mut frame_samples = vec![0.0f32; samples_per_frame];
+
+                    while self.pcm_buffers.consume_exact(&mut frame_samples) {
+                        let aac_data = self
+                            .encoder
+                            .encode(&frame_samples)
+                            .context("Failed to encode AAC")?;
+
+                        eprintln!("[RUST PIPELINE] Step 3: Encoded AAC data (size: {} bytes)", aac_data.len());
+
+                        // A single encode() call can emit more than one AAC
+                        // frame (ADTS-framed back to back); mux and timestamp
+                        // each one individually.
+                        for frame in split_adts_frames(&aac_data) {
+                            self.stats.frames_encoded += 1;
+
+                            let pts = self.current_pts;
+                            let dts = pts; // For audio, DTS = PTS
+
+                            let ts_packets = self
+                                .muxer
+                                .mux_audio(frame, pts, dts)
+                                .context("Failed to mux AAC")?;
+
+                            eprintln!("[RUST PIPELINE] Step 4: Muxed {} TS packets", ts_packets.len());
+
+                            self.stats.packets_output += ts_packets.len() as u64;
+                            output_packets.extend(ts_packets);
+
+                            // Advance PTS once per emitted AAC frame
+                            self.current_pts += self.pts_increment;
+                        }
+                    }
+                } else {
+                    eprintln!("[RUST PIPELINE] Decoder returned empty PCM samples");
+                }
+            }
+            None => {
+                if is_pmt_pid {
+                    // Preserve the program's own PMT, rewriting only the
+                    // audio entry's stream_type to reflect our output framing.
+                    let packet_array = ts_packet_array(ts_packet)?;
+                    let rewritten = self.demuxer.audio_pid().and_then(|audio_pid| {
+                        rewrite_pmt_audio_stream_type(&packet_array, audio_pid, self.config.stream_format)
+                    });
+                    output_packets.push(rewritten.unwrap_or(packet_array));
+                } else if !is_audio_pid {
+                    // PAT, video, and other data PIDs (including the PCR PID)
+                    // pass through untouched so the program stays watchable.
+                    output_packets.push(ts_packet_array(ts_packet)?);
+                }
+                // `is_audio_pid` with no PES yet: still buffering, nothing to emit.
+            }
+        }
 
-                    // Step 3: Encode - PCM → AAC (process complete frames)
-                    let aac_data = self
-                        .encoder
-                        .encode(&self.pcm_buffer)
-                        .context("Failed to encode AAC")?;
+        Ok(output_packets)
+    }
 
-                    eprintln!("[RUST PIPELINE] Step 3: Encoded AAC data (size: {} bytes)", aac_data.len());
+    /// Re-container already-AAC access units without decoding/re-encoding
+    ///
+    /// Parses the ADTS frames in `pes_data`, checks each one's ASC
+    /// parameters against the configured output format, and muxes them
+    /// directly. The decoder/resampler/skip-cut/encoder stages are never
+    /// touched for these frames.
+    fn passthrough_aac(&mut self, pes_data: &[u8]) -> Result<Vec<[u8; 188]>> {
+        let mut output_packets = Vec::new();
 
-                    // Encoder returns data only when it has complete frames
-                    if !aac_data.is_empty() {
-                        self.stats.frames_encoded += 1;
+        for (header, frame) in AdtsFrameIter::new(pes_data) {
+            if header.sample_rate != self.config.sample_rate || header.channels() != self.config.channels {
+                warn!(
+                    "AAC passthrough: source ASC ({}Hz, {}ch) doesn't match configured output ({}Hz, {}ch); passing through anyway",
+                    header.sample_rate, header.channels(), self.config.sample_rate, self.config.channels
+                );
+            }
 
-                        // Step 4: Mux - AAC → TS packets
-                        let pts = self.current_pts;
-                        let dts = pts; // For audio, DTS = PTS
+            self.stats.frames_passthrough += 1;
 
-                        let ts_packets = self
-                            .muxer
-                            .mux_audio(&aac_data, pts, dts)
-                            .context("Failed to mux AAC")?;
+            let pts = self.current_pts;
+            let dts = pts; // For audio, DTS = PTS
 
-                        eprintln!("[RUST PIPELINE] Step 4: Muxed {} TS packets", ts_packets.len());
+            let ts_packets = self
+                .muxer
+                .mux_audio(frame, pts, dts)
+                .context("Failed to mux passthrough AAC frame")?;
 
-                        output_packets.extend(ts_packets);
-                        self.stats.packets_output += output_packets.len() as u64;
+            self.stats.packets_output += ts_packets.len() as u64;
+            output_packets.extend(ts_packets);
 
-                        // Increment PTS for next frame
-                        self.current_pts += self.pts_increment;
-                    } else {
-                        eprintln!("[RUST PIPELINE] Encoder returned empty data (waiting for complete frame)");
-                    }
-                } else {
-                    eprintln!("[RUST PIPELINE] Decoder returned empty PCM samples");
-                }
-            }
-            None => {
-                // Not audio or incomplete PES - check if video passthrough needed
-                // For now, we'll only output when we have audio to mux
-                // Video passthrough would be added here
-            }
+            self.current_pts += self.pts_increment;
         }
 
+        self.initialized = true;
+
         Ok(output_packets)
     }
 
@@ -360,7 +494,12 @@ impl AudioRemuxer {
 
             debug!("Initializing decoder for codec: {:?}", codec);
 
-            let decoder = AutoDecoder::new(codec).context("Failed to create audio decoder")?;
+            // Canonicalize the decoder's output to the remuxer's target sample
+            // rate up front, so the resampler below only has to handle the
+            // channel layout and never double-converts the rate.
+            let decoder = AutoDecoder::new(codec)
+                .context("Failed to create audio decoder")?
+                .with_target_rate(self.config.sample_rate);
 
             self.decoder = Some(decoder);
             self.initialized = true;
@@ -376,24 +515,64 @@ impl AudioRemuxer {
         Ok(())
     }
 
+    /// Create the resampler once the decoder has reported its native
+    /// sample rate/channel layout
+    fn ensure_resampler_initialized(&mut self) {
+        if self.resampler.is_some() {
+            return;
+        }
+
+        if let Some(decoder) = self.decoder.as_ref() {
+            let src_rate = decoder.sample_rate();
+            let src_channels = decoder.channels();
+
+            debug!(
+                "Initializing resampler: {}Hz/{}ch -> {}Hz/{}ch",
+                src_rate, src_channels, self.config.sample_rate, self.config.channels
+            );
+
+            self.resampler = Some(Resampler::new(
+                src_rate,
+                src_channels,
+                self.config.sample_rate,
+                self.config.channels,
+            ));
+        }
+    }
+
     /// Flush remaining data at end of stream
     fn flush(&mut self) -> Result<Vec<[u8; 188]>> {
         debug!("Flushing audio remuxer");
 
         let mut output_packets = Vec::new();
 
-        // Flush encoder (encode remaining PCM samples)
-        if !self.pcm_buffer.is_empty() {
-            let aac_data = self.encoder.flush().context("Failed to flush encoder")?;
+        // Discard the skip-cut held tail (trailing encoder/decoder padding)
+        // instead of emitting it as audible garbage.
+        self.skip_cut.flush();
+
+        // Drain the trailing partial frame (fewer than `samples_per_frame`
+        // samples) into the encoder's own buffer before flushing it.
+        let remaining = self.pcm_buffers.samples_available();
+        if remaining > 0 {
+            let mut tail = vec![0.0f32; remaining];
+            self.pcm_buffers.consume_exact(&mut tail);
+            self.encoder.encode(&tail).context("Failed to encode trailing PCM")?;
+        }
 
-            if !aac_data.is_empty() {
-                let pts = self.current_pts;
-                let dts = pts;
+        // Flush encoder (pads and encodes any remaining buffered samples)
+        let aac_data = self.encoder.flush().context("Failed to flush encoder")?;
 
-                let ts_packets = self.muxer.mux_audio(&aac_data, pts, dts)?;
-                output_packets.extend(ts_packets);
-                self.stats.packets_output += output_packets.len() as u64;
-            }
+        for frame in split_adts_frames(&aac_data) {
+            self.stats.frames_encoded += 1;
+
+            let pts = self.current_pts;
+            let dts = pts;
+
+            let ts_packets = self.muxer.mux_audio(frame, pts, dts)?;
+            self.stats.packets_output += ts_packets.len() as u64;
+            output_packets.extend(ts_packets);
+
+            self.current_pts += self.pts_increment;
         }
 
         Ok(output_packets)
@@ -402,11 +581,12 @@ impl AudioRemuxer {
     /// Log current statistics
     fn log_stats(&self) {
         info!(
-            "Remuxing stats: processed {} packets ({} audio), decoded {} frames, encoded {} frames, output {} packets, errors: {}",
+            "Remuxing stats: processed {} packets ({} audio), decoded {} frames, encoded {} frames, passthrough {} frames, output {} packets, errors: {}",
             self.stats.packets_processed,
             self.stats.audio_packets,
             self.stats.frames_decoded,
             self.stats.frames_encoded,
+            self.stats.frames_passthrough,
             self.stats.packets_output,
             self.stats.errors
         );
@@ -438,6 +618,131 @@ impl AudioRemuxer {
     }
 }
 
+/// Draining PCM sample accumulator
+///
+/// Buffers incoming PCM chunks as a `Vec<Vec<f32>>` instead of
+/// concatenating them into one ever-growing buffer. Samples are pulled
+/// from the front in frame-sized chunks via `consume_exact`; fully
+/// drained chunks are freed as the cursor advances past them, so memory
+/// use stays bounded on long streams instead of growing without limit.
+/// This is what lets `process_ts_packet` push decoder output as soon as
+/// it arrives while `frame_size()`-sized AAC frames are pulled out on
+/// their own cadence, with no partial-frame bookkeeping duplicated at
+/// each call site.
+struct PcmBuffers {
+    /// Buffered PCM chunks awaiting consumption
+    chunks: Vec<Vec<f32>>,
+
+    /// Index into `chunks` currently being drained
+    chunk_cursor: usize,
+
+    /// Offset within `chunks[chunk_cursor]` of the next unconsumed sample
+    sample_cursor: usize,
+
+    /// Total unconsumed samples across all buffered chunks
+    available: usize,
+}
+
+impl PcmBuffers {
+    fn new() -> Self {
+        Self {
+            chunks: Vec::new(),
+            chunk_cursor: 0,
+            sample_cursor: 0,
+            available: 0,
+        }
+    }
+
+    /// Push a chunk of PCM samples onto the back of the accumulator
+    fn push(&mut self, samples: Vec<f32>) {
+        if samples.is_empty() {
+            return;
+        }
+        self.available += samples.len();
+        self.chunks.push(samples);
+    }
+
+    /// Total unconsumed samples currently buffered
+    fn samples_available(&self) -> usize {
+        self.available
+    }
+
+    /// Drain exactly `out.len()` samples into `out`
+    ///
+    /// Returns `false` (leaving the buffer untouched) if fewer than
+    /// `out.len()` samples are currently available.
+    fn consume_exact(&mut self, out: &mut [f32]) -> bool {
+        if self.available < out.len() {
+            return false;
+        }
+
+        let mut written = 0;
+        while written < out.len() {
+            let chunk = &self.chunks[self.chunk_cursor];
+            let remaining_in_chunk = chunk.len() - self.sample_cursor;
+            let need = out.len() - written;
+            let take = remaining_in_chunk.min(need);
+
+            out[written..written + take]
+                .copy_from_slice(&chunk[self.sample_cursor..self.sample_cursor + take]);
+
+            written += take;
+            self.sample_cursor += take;
+
+            if self.sample_cursor == chunk.len() {
+                self.chunk_cursor += 1;
+                self.sample_cursor = 0;
+            }
+        }
+
+        self.available -= out.len();
+        self.free_consumed_chunks();
+        true
+    }
+
+    /// Drop chunks fully behind the consumer cursor, freeing their backing storage
+    fn free_consumed_chunks(&mut self) {
+        if self.chunk_cursor > 0 {
+            self.chunks.drain(0..self.chunk_cursor);
+            self.chunk_cursor = 0;
+        }
+    }
+}
+
+/// Copy a TS packet slice into a fixed-size array for passthrough output
+fn ts_packet_array(data: &[u8]) -> Result<[u8; 188]> {
+    data.try_into().context("TS packet must be exactly 188 bytes")
+}
+
+/// Split a buffer of back-to-back ADTS-framed AAC frames into individual frames
+///
+/// Each encoder call can flush more than one AAC frame at once; each one
+/// needs its own PES packet and PTS, so the caller splits on ADTS frame
+/// boundaries before muxing.
+fn split_adts_frames(data: &[u8]) -> Vec<&[u8]> {
+    let mut frames = Vec::new();
+    let mut offset = 0;
+
+    while offset + 7 <= data.len() {
+        if data[offset] != 0xFF || (data[offset + 1] & 0xF0) != 0xF0 {
+            break; // Not a valid ADTS sync word
+        }
+
+        let frame_length = (((data[offset + 3] & 0x03) as usize) << 11)
+            | ((data[offset + 4] as usize) << 3)
+            | ((data[offset + 5] as usize) >> 5);
+
+        if frame_length == 0 || offset + frame_length > data.len() {
+            break;
+        }
+
+        frames.push(&data[offset..offset + frame_length]);
+        offset += frame_length;
+    }
+
+    frames
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -479,6 +784,177 @@ mod tests {
         let stats = AudioRemuxStats::default();
         assert_eq!(stats.packets_processed, 0);
         assert_eq!(stats.frames_decoded, 0);
+        assert_eq!(stats.frames_passthrough, 0);
         assert_eq!(stats.errors, 0);
     }
+
+    #[test]
+    fn test_pcm_buffers_consume_exact_across_chunks() {
+        let mut buffers = PcmBuffers::new();
+        buffers.push(vec![1.0, 2.0, 3.0]);
+        buffers.push(vec![4.0, 5.0]);
+
+        assert_eq!(buffers.samples_available(), 5);
+
+        let mut out = [0.0f32; 4];
+        assert!(buffers.consume_exact(&mut out));
+        assert_eq!(out, [1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(buffers.samples_available(), 1);
+
+        // Not enough samples left for another 4-sample pull
+        assert!(!buffers.consume_exact(&mut out));
+    }
+
+    #[test]
+    fn test_pcm_buffers_frees_drained_chunks() {
+        let mut buffers = PcmBuffers::new();
+        buffers.push(vec![1.0, 2.0]);
+        buffers.push(vec![3.0, 4.0]);
+
+        let mut out = [0.0f32; 2];
+        assert!(buffers.consume_exact(&mut out));
+
+        // First chunk fully drained and should have been freed
+        assert_eq!(buffers.chunks.len(), 1);
+        assert_eq!(buffers.chunk_cursor, 0);
+    }
+
+    #[test]
+    fn test_passthrough_aac_bypasses_decoder_and_counts_stats() {
+        let config = AudioRemuxConfig {
+            sample_rate: 48000,
+            channels: 2,
+            ..Default::default()
+        };
+        let mut remuxer = AudioRemuxer::new(config).unwrap();
+
+        // Two minimal ADTS frames, 48kHz stereo AAC-LC, matching the configured output
+        let mut pes_data = Vec::new();
+        for _ in 0..2 {
+            pes_data.extend_from_slice(&[0xFF, 0xF1, 0x4C, 0x80, 0x01, 0x1F, 0xFC, 0xAB]);
+        }
+
+        let packets = remuxer.passthrough_aac(&pes_data).unwrap();
+        assert!(!packets.is_empty());
+        assert_eq!(remuxer.stats().frames_passthrough, 2);
+        assert_eq!(remuxer.stats().frames_decoded, 0);
+        assert!(remuxer.is_initialized());
+    }
+
+    /// Build a minimal PAT packet (single program) pointing at `pmt_pid`
+    fn build_pat_packet(pmt_pid: u16) -> [u8; 188] {
+        let mut packet = [0u8; TS_PACKET_SIZE];
+        packet[0] = 0x47;
+        packet[1] = 0x40; // PUSI, PID = 0x0000
+        packet[2] = 0x00;
+        packet[3] = 0x10;
+
+        let payload: [u8; 17] = [
+            0x00, // pointer field
+            0x00, // table_id = PAT
+            0xB0, 0x0D, // section_length = 13
+            0x00, 0x01, // transport_stream_id
+            0xC1, // version/current
+            0x00, // section_number
+            0x00, // last_section_number
+            0x00, 0x01, // program_number = 1
+            0xE0 | ((pmt_pid >> 8) as u8),
+            (pmt_pid & 0xFF) as u8,
+            0x00, 0x00, 0x00, 0x00, // CRC32 (unused by the parser)
+        ];
+        packet[4..4 + payload.len()].copy_from_slice(&payload);
+        packet
+    }
+
+    /// Build a minimal single-audio-stream PMT packet
+    fn build_pmt_packet(pmt_pid: u16, pcr_pid: u16, audio_pid: u16, stream_type: u8) -> [u8; 188] {
+        let mut packet = [0u8; TS_PACKET_SIZE];
+        packet[0] = 0x47;
+        packet[1] = 0x40 | ((pmt_pid >> 8) as u8 & 0x1F);
+        packet[2] = (pmt_pid & 0xFF) as u8;
+        packet[3] = 0x10;
+
+        let payload: [u8; 22] = [
+            0x00, // pointer field
+            0x02, // table_id = PMT
+            0xB0, 0x12, // section_length = 18
+            0x00, 0x01, // program_number = 1
+            0xC1, // version/current
+            0x00, // section_number
+            0x00, // last_section_number
+            0xE0 | ((pcr_pid >> 8) as u8),
+            (pcr_pid & 0xFF) as u8,
+            0xF0, 0x00, // program_info_length = 0
+            stream_type,
+            0xE0 | ((audio_pid >> 8) as u8),
+            (audio_pid & 0xFF) as u8,
+            0xF0, 0x00, // ES info length = 0
+            0x00, 0x00, 0x00, 0x00, // CRC32 (unused by the parser)
+        ];
+        packet[4..4 + payload.len()].copy_from_slice(&payload);
+        packet
+    }
+
+    #[test]
+    fn test_process_ts_packet_passes_through_psi_and_video() {
+        let config = AudioRemuxConfig::default();
+        let mut remuxer = AudioRemuxer::new(config).unwrap();
+
+        let pat = build_pat_packet(0x1000);
+        let out = remuxer.process_ts_packet(&pat).unwrap();
+        assert_eq!(out, vec![pat]);
+
+        let pmt = build_pmt_packet(0x1000, 0x0100, 0x0101, 0x0F);
+        let out = remuxer.process_ts_packet(&pmt).unwrap();
+        assert_eq!(out.len(), 1);
+        // Default output framing is ADTS, matching the source's stream_type,
+        // so the audio entry comes back unchanged.
+        assert_eq!(out[0][4 + 13], 0x0F);
+
+        // A video packet (not the audio or PMT PID) is copied through verbatim
+        let mut video = [0xAAu8; TS_PACKET_SIZE];
+        video[0] = 0x47;
+        video[1] = 0x40; // PUSI, PID = 0x0100
+        video[2] = 0x00;
+        video[3] = 0x10;
+        let out = remuxer.process_ts_packet(&video).unwrap();
+        assert_eq!(out, vec![video]);
+    }
+
+    #[test]
+    fn test_process_ts_packet_rewrites_pmt_for_latm_output() {
+        let config = AudioRemuxConfig {
+            stream_format: StreamFormat::Latm,
+            ..Default::default()
+        };
+        let mut remuxer = AudioRemuxer::new(config).unwrap();
+
+        remuxer.process_ts_packet(&build_pat_packet(0x1000)).unwrap();
+
+        let pmt = build_pmt_packet(0x1000, 0x0100, 0x0101, 0x0F);
+        let out = remuxer.process_ts_packet(&pmt).unwrap();
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0][4 + 13], 0x11); // Rewritten from ADTS to LATM
+    }
+
+    #[test]
+    fn test_split_adts_frames_multiple() {
+        // Two minimal 8-byte ADTS frames (7-byte header + 1 byte payload) back to back
+        let mut data = Vec::new();
+        for _ in 0..2 {
+            data.push(0xFF);
+            data.push(0xF1);
+            data.push(0x40);
+            data.push(0x00); // frame_length[12:11] = 00
+            data.push(0x01); // frame_length[10:3] = 1
+            data.push(0x1F); // frame_length[2:0] = 000, buffer fullness MSB
+            data.push(0xFC);
+            data.push(0xAB); // 1 byte payload, total frame_length = 8
+        }
+
+        let frames = split_adts_frames(&data);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].len(), 8);
+        assert_eq!(frames[1].len(), 8);
+    }
 }
@@ -0,0 +1,227 @@
+//! In-process libav ingestion via a custom `AVIOContext`
+//!
+//! Everywhere else in this crate, VAAPI encoding means spawning the
+//! `ffmpeg` CLI as a subprocess (see [`crate::transcoder::VaapiTranscoder`])
+//! and handing it bytes over a pipe - [`transcode_stdin`] feeds a request
+//! body to the child's `pipe:0` exactly that way. That always forces a
+//! subprocess boundary: the source has to be something the OS can hand to
+//! a child's stdin, which rules out seeking and any source that only
+//! exists as in-process memory (e.g. a future MoQ/WebTransport ingest with
+//! no file and no OS pipe to write to).
+//!
+//! [`AvioReader`] is the building block for removing that boundary: it
+//! wraps a `tokio::sync::mpsc::Receiver<Bytes>` in a custom `AVIOContext`
+//! via `avio_alloc_context`, so libav can read frames directly out of
+//! in-process memory instead of an OS pipe. The read callback pulls the
+//! next `Bytes` off the channel (returning `AVERROR_EOF` once it closes);
+//! the seek callback only supports the size-query and "where am I" probes
+//! some demuxers issue, since a live push channel has no buffered history
+//! to seek backward into.
+//!
+//! **This crate has no in-process libav decode/encode call graph for an
+//! `AvioReader` to feed into yet** - every actual transcode still goes
+//! through the subprocess path above, and wiring decoded frames from an
+//! `AvioReader`-backed `AVFormatContext` through to a VAAPI encode without
+//! a subprocess boundary is real future work, not attempted here.
+//! [`crate::transcoder::VaapiTranscoder::probe_reader`] uses this today
+//! only to probe a pushed stream's format in-process; it does not yet
+//! replace [`transcode_stdin`] for the actual encode.
+//!
+//! # Safety
+//!
+//! `avio_alloc_context`'s callbacks are invoked by libav's C code directly,
+//! not through any Rust call site in this crate - they must never unwind
+//! across that boundary (hence the `catch_unwind` in each one, mirroring
+//! [`crate::ffi`]'s existing pattern for Go-facing `extern "C"` functions),
+//! and the `opaque` pointer's lifetime is tied to the `AVIOContext` itself,
+//! freed together in [`AvioReader`]'s `Drop`.
+//!
+//! [`transcode_stdin`]: crate::transcoder::VaapiTranscoder::transcode_stdin
+
+use std::os::raw::{c_int, c_void};
+use std::panic::catch_unwind;
+use std::ptr;
+
+use bytes::Bytes;
+use ffmpeg_sys_next as sys;
+use tokio::sync::mpsc;
+use tracing::error;
+
+/// Chunk size libav is told to request reads in; it may ask for less
+const AVIO_BUFFER_SIZE: usize = 4096;
+
+/// Read-callback state: the channel frames arrive on, any unconsumed tail
+/// of the last `Bytes` pulled off it, and the runtime handle the callback
+/// uses to block on the async channel from libav's synchronous call
+struct ReaderState {
+    rx: mpsc::Receiver<Bytes>,
+    pending: Bytes,
+    runtime: tokio::runtime::Handle,
+}
+
+/// Owns a custom `AVIOContext` that reads from a `tokio::sync::mpsc::Receiver<Bytes>`
+///
+/// Frees its `av_malloc`'d buffer and the context itself on `Drop`, so a
+/// caller never has to remember libav's manual-free contract.
+pub struct AvioReader {
+    ctx: *mut sys::AVIOContext,
+    state: *mut ReaderState,
+}
+
+// SAFETY: `ctx` and `state` are only ever touched (read from or mutated)
+// through libav's callbacks or `AvioReader`'s own methods/`Drop`, never
+// concurrently - `ReaderState` itself holds nothing `!Send`.
+unsafe impl Send for AvioReader {}
+
+impl AvioReader {
+    /// Build an `AVIOContext` that reads from `rx`, reporting
+    /// `AVERROR_EOF` to libav once the channel closes with nothing left
+    /// pending
+    ///
+    /// Must be called from within a Tokio runtime - the read callback
+    /// blocks the (non-async) thread libav calls it from via a cloned
+    /// [`tokio::runtime::Handle`].
+    pub fn new(rx: mpsc::Receiver<Bytes>) -> anyhow::Result<Self> {
+        let runtime = tokio::runtime::Handle::try_current()
+            .map_err(|_| anyhow::anyhow!("AvioReader::new must run inside a Tokio runtime"))?;
+
+        let state = Box::into_raw(Box::new(ReaderState {
+            rx,
+            pending: Bytes::new(),
+            runtime,
+        }));
+
+        // SAFETY: `buffer` is sized exactly as told to `avio_alloc_context`;
+        // `state` outlives the context and is only freed alongside it, in
+        // `Drop`, never before `avio_context_free` has run.
+        let ctx = unsafe {
+            let buffer = sys::av_malloc(AVIO_BUFFER_SIZE) as *mut u8;
+            if buffer.is_null() {
+                drop(Box::from_raw(state));
+                anyhow::bail!("av_malloc failed to allocate the AVIO buffer");
+            }
+
+            let ctx = sys::avio_alloc_context(
+                buffer,
+                AVIO_BUFFER_SIZE as c_int,
+                0, // write_flag: this is a read-only source
+                state as *mut c_void,
+                Some(read_packet),
+                None, // write_packet: unused, read-only source
+                Some(seek),
+            );
+            if ctx.is_null() {
+                sys::av_free(buffer as *mut c_void);
+            }
+            ctx
+        };
+
+        if ctx.is_null() {
+            // SAFETY: avio_alloc_context failed without taking ownership of
+            // `state`; it (and the buffer, already freed above) are still
+            // ours to clean up.
+            unsafe {
+                drop(Box::from_raw(state));
+            }
+            anyhow::bail!("avio_alloc_context returned null");
+        }
+
+        Ok(Self { ctx, state })
+    }
+
+    /// Raw pointer for assigning an `AVFormatContext::pb`
+    ///
+    /// The returned pointer is only valid for as long as this `AvioReader`
+    /// is alive and not yet dropped.
+    pub fn as_ptr(&self) -> *mut sys::AVIOContext {
+        self.ctx
+    }
+}
+
+impl Drop for AvioReader {
+    fn drop(&mut self) {
+        // SAFETY: `self.ctx.buffer` may have been reallocated by libav
+        // internally since `new` allocated it, which is why this frees
+        // whatever the context's `buffer` field currently points to (via
+        // `av_freep`) rather than the pointer `new` originally passed in.
+        unsafe {
+            if !self.ctx.is_null() {
+                sys::av_freep(&mut (*self.ctx).buffer as *mut _ as *mut c_void);
+                sys::avio_context_free(&mut self.ctx);
+            }
+            drop(Box::from_raw(self.state));
+        }
+    }
+}
+
+/// `AVIOContext` read callback: pull the next chunk of bytes off the
+/// channel, blocking the calling thread on the state's stored runtime
+/// handle since this is invoked from libav's synchronous C call site
+///
+/// # Safety
+///
+/// Called by libav with `opaque` set to the `*mut ReaderState` passed to
+/// `avio_alloc_context`, and `buf` valid for at least `buf_size` writable
+/// bytes - both upheld by libav's AVIO contract, not by this crate.
+unsafe extern "C" fn read_packet(opaque: *mut c_void, buf: *mut u8, buf_size: c_int) -> c_int {
+    let result = catch_unwind(|| {
+        // SAFETY: `opaque` was created from a live `Box<ReaderState>` in
+        // `AvioReader::new` and outlives every call libav makes into it.
+        let state = unsafe { &mut *(opaque as *mut ReaderState) };
+        let buf_size = buf_size.max(0) as usize;
+
+        if state.pending.is_empty() {
+            let runtime = state.runtime.clone();
+            state.pending = match runtime.block_on(state.rx.recv()) {
+                Some(bytes) => bytes,
+                None => return sys::AVERROR_EOF,
+            };
+        }
+
+        let take = state.pending.len().min(buf_size);
+        let chunk = state.pending.split_to(take);
+        // SAFETY: `buf` is writable for `buf_size` bytes per the AVIO
+        // contract, and `take <= buf_size`.
+        unsafe {
+            ptr::copy_nonoverlapping(chunk.as_ptr(), buf, take);
+        }
+        take as c_int
+    });
+
+    result.unwrap_or_else(|e| {
+        error!("AvioReader read callback panicked: {:?}", e);
+        sys::AVERROR_UNKNOWN
+    })
+}
+
+/// `AVIOContext` seek callback
+///
+/// This reader is fed by a live, unbuffered `mpsc` channel with no
+/// retained history, so only `AVSEEK_SIZE` (reporting "unknown") and the
+/// no-op current-position probe (`SEEK_CUR` with a zero offset) some
+/// demuxers issue are meaningful; any other seek fails, the same way a
+/// genuinely non-seekable pipe would.
+///
+/// # Safety
+///
+/// See [`read_packet`] - the same `opaque` contract applies here.
+unsafe extern "C" fn seek(opaque: *mut c_void, offset: i64, whence: c_int) -> i64 {
+    let result = catch_unwind(|| {
+        // SAFETY: see `read_packet`.
+        let _state = unsafe { &mut *(opaque as *mut ReaderState) };
+
+        if whence & sys::AVSEEK_SIZE != 0 {
+            return -1; // size is unknown for a live push channel
+        }
+        if whence == sys::SEEK_CUR && offset == 0 {
+            return 0; // "where am I" probe; position tracking isn't kept
+        }
+
+        -1
+    });
+
+    result.unwrap_or_else(|e| {
+        error!("AvioReader seek callback panicked: {:?}", e);
+        -1
+    })
+}
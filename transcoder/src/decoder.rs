@@ -5,8 +5,15 @@
 //!
 //! # Supported Codecs
 //!
-//! - **MP2 (MPEG-1 Layer 2)**: Via Symphonia (pure Rust)
-//! - **AC3 (Dolby Digital)**: Via ac-ffmpeg (FFmpeg bindings)
+//! - **MP1/MP2 (MPEG-1 Layer 1/2)**: Via Symphonia (pure Rust, MP2 only) by
+//!   default, or [`crate::mp2_native::Mp1Mp2Decoder`] (pure Rust, also
+//!   accepts MP1) when selected via [`Mp2Backend::Native`] and built with
+//!   the `native-mp2` feature
+//! - **AC3 (Dolby Digital)**: Via ac-ffmpeg (FFmpeg bindings) by default, or
+//!   [`crate::ac3_native::NativeAc3Decoder`] (pure Rust, see that module's
+//!   "Coverage" section for why this is not a spec-accurate decoder) when
+//!   selected via [`Ac3Backend::Native`] and built with the `native-ac3`
+//!   feature
 //!
 //! # Usage
 //!
@@ -17,11 +24,12 @@
 //! let pcm_samples = decoder.decode(pes_data)?;
 //! ```
 
+use crate::resampler::Resampler;
 use anyhow::{Context, Result};
-use std::io::Cursor;
+use std::sync::{Arc, Mutex};
 use symphonia::core::audio::{AudioBufferRef, Signal};
 use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_MP2};
-use symphonia::core::formats::FormatOptions;
+use symphonia::core::formats::{FormatOptions, FormatReader};
 use symphonia::core::io::MediaSourceStream;
 use symphonia::core::meta::MetadataOptions;
 use symphonia::core::probe::Hint;
@@ -42,20 +50,39 @@ pub type PcmSample = f32;
 
 /// Audio decoder trait
 ///
-/// Defines the interface for all audio decoders.
+/// Defines the interface for all audio decoders as a streaming `push`/`pull`
+/// pair: `push` feeds in a PES payload as it arrives, and `pull` drains
+/// whatever complete frames that made available, so implementations can
+/// keep their format reader/decoder alive across calls instead of rebuilding
+/// it from scratch for every payload.
 pub trait AudioDecoder: Send {
-    /// Decode compressed audio data to PCM samples
+    /// Feed a chunk of compressed audio (a PES payload) into the decoder
     ///
-    /// # Arguments
-    ///
-    /// * `data` - Compressed audio data (PES payload)
+    /// Doesn't produce any samples itself; call [`Self::pull`] afterward
+    /// (possibly more than once) to drain whatever frames that made ready.
+    fn push(&mut self, data: &[u8]) -> Result<()>;
+
+    /// Drain one decoded frame's worth of PCM samples, if one is ready
     ///
-    /// # Returns
+    /// Interleaved PCM samples as f32 [-1.0, 1.0] (stereo: `[L, R, L, R,
+    /// ...]`, mono: `[M, M, M, ...]`). Returns `Ok(None)` when there isn't
+    /// yet enough pushed data to produce another frame - that's not an
+    /// error, just "call `push` again and retry".
+    fn pull(&mut self) -> Result<Option<Vec<PcmSample>>>;
+
+    /// Decode a complete PES payload to PCM samples in one call
     ///
-    /// * Interleaved PCM samples as f32 [-1.0, 1.0]
-    /// * For stereo: [L, R, L, R, ...]
-    /// * For mono: [M, M, M, ...]
-    fn decode(&mut self, data: &[u8]) -> Result<Vec<PcmSample>>;
+    /// A thin `push` + drain-all-`pull`s loop, kept for callers that hand
+    /// over one self-contained payload at a time rather than streaming
+    /// incremental chunks.
+    fn decode(&mut self, data: &[u8]) -> Result<Vec<PcmSample>> {
+        self.push(data)?;
+        let mut samples = Vec::new();
+        while let Some(chunk) = self.pull()? {
+            samples.extend(chunk);
+        }
+        Ok(samples)
+    }
 
     /// Get the sample rate in Hz
     fn sample_rate(&self) -> u32;
@@ -70,11 +97,231 @@ pub trait AudioDecoder: Send {
 
     /// Get decoder name for logging
     fn name(&self) -> &str;
+
+    /// Select how a multichannel decode should be folded down for output
+    ///
+    /// A no-op default for decoders that only ever produce one channel
+    /// layout (e.g. MP2, which is mono or stereo already); multichannel
+    /// decoders like AC3 override this.
+    fn set_output_mode(&mut self, _mode: OutputMode) {}
+
+    /// Whether a multichannel downmix should fold the LFE channel into the
+    /// output instead of discarding it (BS.775's default 2-channel downmix
+    /// excludes LFE)
+    ///
+    /// A no-op default for decoders with no LFE channel to begin with;
+    /// multichannel decoders like AC3 override this.
+    fn set_include_lfe(&mut self, _include_lfe: bool) {}
+}
+
+/// Output channel layout a multichannel decoder folds its decode down to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputMode {
+    /// Downmix to stereo using ITU-R BS.775 coefficients (the default)
+    #[default]
+    Stereo,
+    /// Downmix to a single mono channel
+    Mono,
+    /// Keep the native channel count and order; `channels()` reports it
+    Passthrough,
+}
+
+/// The role a decoded channel plays in its source layout, used to pick the
+/// right BS.775 downmix coefficient for it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelRole {
+    Left,
+    Right,
+    Center,
+    LeftSurround,
+    RightSurround,
+    /// A single mono surround channel (e.g. AC3 acmod 4, "2/1"), split
+    /// evenly across both downmixed outputs
+    Surround,
+    Lfe,
+}
+
+/// ITU-R BS.775 downmix coefficient for folding a channel into the left or
+/// right output, by role and which output side is being computed
+fn bs775_coefficient(role: ChannelRole, include_lfe: bool) -> (f32, f32) {
+    // (left_gain, right_gain): how much of this channel lands in each of
+    // the two output channels.
+    const CENTER_LEVEL: f32 = std::f32::consts::FRAC_1_SQRT_2; // -3dB
+    const SURROUND_LEVEL: f32 = std::f32::consts::FRAC_1_SQRT_2; // -3dB
+    match role {
+        ChannelRole::Left => (1.0, 0.0),
+        ChannelRole::Right => (0.0, 1.0),
+        ChannelRole::Center => (CENTER_LEVEL, CENTER_LEVEL),
+        ChannelRole::LeftSurround => (SURROUND_LEVEL, 0.0),
+        ChannelRole::RightSurround => (0.0, SURROUND_LEVEL),
+        ChannelRole::Surround => (SURROUND_LEVEL, SURROUND_LEVEL),
+        ChannelRole::Lfe => {
+            if include_lfe {
+                (SURROUND_LEVEL, SURROUND_LEVEL)
+            } else {
+                (0.0, 0.0)
+            }
+        }
+    }
+}
+
+/// Fixed headroom gain applied to every downmixed sample, chosen so the
+/// worst-case BS.775 combination that can land in one output channel - a
+/// direct channel plus a center and a surround folded in at -3dB each
+/// (e.g. 5.1's L + C + Ls) - can never clip: `1.0 + FRAC_1_SQRT_2 * 2`
+/// is that worst-case gain sum, and this is its reciprocal. This holds
+/// with LFE excluded; folding LFE in adds one more -3dB term, so a
+/// full-scale input can then exceed 1.0.
+///
+/// A fixed gain (rather than normalizing each call by its own peak) keeps
+/// the transfer function identical across calls, so loudness doesn't pump
+/// up and down at decode-call boundaries depending on whether that
+/// particular buffer happened to clip.
+const DOWNMIX_HEADROOM_GAIN: f32 = 1.0 / (1.0 + 2.0 * std::f32::consts::FRAC_1_SQRT_2);
+
+/// Fold an interleaved multichannel PCM buffer down to stereo or mono using
+/// ITU-R BS.775 downmix coefficients, mapping each input channel to its role
+/// via `roles` (one entry per input channel, in interleaving order)
+///
+/// `include_lfe` selects whether the LFE channel (if `roles` contains one)
+/// is folded into the output at -3dB or discarded, matching BS.775's
+/// default of excluding it.
+///
+/// Applies a fixed [`DOWNMIX_HEADROOM_GAIN`] to every sample rather than
+/// normalizing per call by that call's own peak - see its docs for why an
+/// adaptive gain would pump loudness across decode-call boundaries.
+pub(crate) fn downmix_with_roles(
+    samples: &[f32],
+    roles: &[ChannelRole],
+    mode: OutputMode,
+    include_lfe: bool,
+) -> Vec<f32> {
+    debug_assert_ne!(mode, OutputMode::Passthrough);
+    let channels = roles.len();
+    if channels == 0 {
+        return Vec::new();
+    }
+
+    let frame_count = samples.len() / channels;
+    let mut out = Vec::with_capacity(frame_count * if mode == OutputMode::Mono { 1 } else { 2 });
+
+    for frame_idx in 0..frame_count {
+        let base = frame_idx * channels;
+        let (mut left, mut right) = (0.0f32, 0.0f32);
+        for (ch, &role) in roles.iter().enumerate() {
+            let (lg, rg) = bs775_coefficient(role, include_lfe);
+            let sample = samples[base + ch];
+            left += sample * lg;
+            right += sample * rg;
+        }
+        left *= DOWNMIX_HEADROOM_GAIN;
+        right *= DOWNMIX_HEADROOM_GAIN;
+
+        match mode {
+            OutputMode::Stereo => {
+                out.push(left);
+                out.push(right);
+            }
+            OutputMode::Mono => {
+                out.push((left + right) * 0.5);
+            }
+            OutputMode::Passthrough => unreachable!(),
+        }
+    }
+
+    out
+}
+
+/// Bytes a [`PushCursor`] will read past before compacting its shared
+/// buffer's already-consumed prefix
+const COMPACT_THRESHOLD: usize = 64 * 1024;
+
+/// Read cursor over an [`Mp2Decoder`]'s shared, append-only PES byte buffer
+///
+/// Reads return `ErrorKind::WouldBlock` once caught up to the end of
+/// whatever's been pushed so far, so Symphonia's format/packet reader
+/// treats "no more data yet" as "try again after the next `push`" rather
+/// than as end of stream. The cursor that survives past a successful probe
+/// (kept alive inside [`Mp2Reader`]) also opportunistically compacts
+/// already-read bytes out of the shared buffer so a long-running stream
+/// doesn't grow it forever; cursors used for a failed probe attempt are
+/// simply dropped and never advance far enough to trigger that.
+struct PushCursor {
+    shared: Arc<Mutex<Vec<u8>>>,
+    pos: usize,
+}
+
+impl PushCursor {
+    fn new(shared: Arc<Mutex<Vec<u8>>>) -> Self {
+        Self { shared, pos: 0 }
+    }
+}
+
+impl std::io::Read for PushCursor {
+    fn read(&mut self, out: &mut [u8]) -> std::io::Result<usize> {
+        let mut shared = self.shared.lock().unwrap();
+        if self.pos >= shared.len() {
+            return Err(std::io::ErrorKind::WouldBlock.into());
+        }
+
+        let available = shared.len() - self.pos;
+        let n = out.len().min(available);
+        out[..n].copy_from_slice(&shared[self.pos..self.pos + n]);
+        self.pos += n;
+
+        if self.pos >= COMPACT_THRESHOLD {
+            shared.drain(0..self.pos);
+            self.pos = 0;
+        }
+
+        Ok(n)
+    }
+}
+
+impl std::io::Seek for PushCursor {
+    fn seek(&mut self, from: std::io::SeekFrom) -> std::io::Result<u64> {
+        let shared = self.shared.lock().unwrap();
+        let new_pos = match from {
+            std::io::SeekFrom::Start(offset) => offset as i64,
+            std::io::SeekFrom::Current(delta) => self.pos as i64 + delta,
+            std::io::SeekFrom::End(delta) => shared.len() as i64 + delta,
+        };
+        if new_pos < 0 || new_pos as usize > shared.len() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "seek out of bounds of buffered data",
+            ));
+        }
+        self.pos = new_pos as usize;
+        Ok(self.pos as u64)
+    }
+}
+
+impl symphonia::core::io::MediaSource for PushCursor {
+    fn is_seekable(&self) -> bool {
+        false
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// Symphonia format reader + decoder for an [`Mp2Decoder`], created once
+/// probing the first pushed bytes succeeds
+struct Mp2Reader {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn symphonia::core::codecs::Decoder>,
+    track_id: u32,
 }
 
 /// MP2 Audio Decoder (MPEG-1 Layer 2)
 ///
-/// Uses Symphonia for pure Rust MP2 decoding.
+/// Uses Symphonia for pure Rust MP2 decoding. The format reader and
+/// decoder are created once, the first time enough data has been pushed to
+/// probe the stream, and reused for the lifetime of the decoder rather than
+/// rebuilt on every `push` - this keeps decoder history (and avoids
+/// re-probing overhead) across a continuous live TS stream.
 pub struct Mp2Decoder {
     /// Detected sample rate (Hz)
     sample_rate: u32,
@@ -84,6 +331,13 @@ pub struct Mp2Decoder {
 
     /// Frame counter for statistics
     frames_decoded: u64,
+
+    /// Raw PES bytes from `push` not yet consumed by Symphonia
+    buffer: Arc<Mutex<Vec<u8>>>,
+
+    /// `None` until the first `push` provides enough data to probe the
+    /// MP2 format
+    reader: Option<Mp2Reader>,
 }
 
 impl Mp2Decoder {
@@ -93,9 +347,71 @@ impl Mp2Decoder {
             sample_rate: 48000, // Default, will be updated from stream
             channels: 2,        // Default stereo
             frames_decoded: 0,
+            buffer: Arc::new(Mutex::new(Vec::new())),
+            reader: None,
         })
     }
 
+    /// Try to probe the MP2 format from whatever's been pushed so far
+    ///
+    /// A no-op once `self.reader` is already set. Leaves `self.reader` as
+    /// `None` (not an error) if there isn't enough data buffered yet to
+    /// detect the format; the next `push` will retry.
+    fn try_probe(&mut self) -> Result<()> {
+        if self.reader.is_some() {
+            return Ok(());
+        }
+
+        let cursor = PushCursor::new(self.buffer.clone());
+        let mss = MediaSourceStream::new(Box::new(cursor), Default::default());
+
+        let mut hint = Hint::new();
+        hint.with_extension("mp2");
+
+        let probed = match symphonia::default::get_probe().format(
+            &hint,
+            mss,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        ) {
+            Ok(probed) => probed,
+            Err(symphonia::core::errors::Error::IoError(e))
+                if e.kind() == std::io::ErrorKind::WouldBlock =>
+            {
+                return Ok(());
+            }
+            Err(e) => return Err(e).context("Failed to probe MP2 format"),
+        };
+
+        let format = probed.format;
+
+        let track = format
+            .tracks()
+            .iter()
+            .find(|t| t.codec_params.codec == CODEC_TYPE_MP2)
+            .context("No MP2 audio track found")?;
+
+        if let Some(sr) = track.codec_params.sample_rate {
+            self.sample_rate = sr;
+        }
+        if let Some(ch) = track.codec_params.channels {
+            self.channels = ch.count() as u16;
+        }
+        let track_id = track.id;
+
+        let decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions::default())
+            .context("Failed to create MP2 decoder")?;
+
+        self.reader = Some(Mp2Reader {
+            format,
+            decoder,
+            track_id,
+        });
+
+        Ok(())
+    }
+
     /// Convert Symphonia AudioBufferRef to f32 PCM samples
     fn convert_to_pcm(buffer: &AudioBufferRef) -> Result<Vec<PcmSample>> {
         match buffer {
@@ -168,79 +484,48 @@ impl Mp2Decoder {
 }
 
 impl AudioDecoder for Mp2Decoder {
-    fn decode(&mut self, data: &[u8]) -> Result<Vec<PcmSample>> {
-        // Create a cursor from owned data (required for 'static lifetime)
-        let owned_data = data.to_vec();
-        let cursor = Cursor::new(owned_data);
-        let mss = MediaSourceStream::new(Box::new(cursor), Default::default());
-
-        // Create a hint for the format probe
-        let mut hint = Hint::new();
-        hint.with_extension("mp2");
-
-        // Probe the format
-        let format_opts = FormatOptions::default();
-        let metadata_opts = MetadataOptions::default();
-        let decoder_opts = DecoderOptions::default();
-
-        let probed = symphonia::default::get_probe()
-            .format(&hint, mss, &format_opts, &metadata_opts)
-            .context("Failed to probe MP2 format")?;
-
-        let mut format = probed.format;
-
-        // Find the audio track
-        let track = format
-            .tracks()
-            .iter()
-            .find(|t| t.codec_params.codec == CODEC_TYPE_MP2)
-            .context("No MP2 audio track found")?;
-
-        // Update sample rate and channels from stream
-        if let Some(sr) = track.codec_params.sample_rate {
-            self.sample_rate = sr;
-        }
-        if let Some(ch) = track.codec_params.channels {
-            self.channels = ch.count() as u16;
-        }
-
-        let track_id = track.id;
-
-        // Create decoder for the track
-        let mut decoder = symphonia::default::get_codecs()
-            .make(&track.codec_params, &decoder_opts)
-            .context("Failed to create MP2 decoder")?;
+    fn push(&mut self, data: &[u8]) -> Result<()> {
+        self.buffer.lock().unwrap().extend_from_slice(data);
+        self.try_probe()
+    }
 
-        let mut all_samples = Vec::new();
+    fn pull(&mut self) -> Result<Option<Vec<PcmSample>>> {
+        let Some(reader) = self.reader.as_mut() else {
+            // Not enough data pushed yet to detect the MP2 format.
+            return Ok(None);
+        };
 
-        // Decode all packets
         loop {
-            // Read the next packet
-            let packet = match format.next_packet() {
+            let packet = match reader.format.next_packet() {
                 Ok(packet) => packet,
                 Err(symphonia::core::errors::Error::IoError(e))
-                    if e.kind() == std::io::ErrorKind::UnexpectedEof =>
+                    if e.kind() == std::io::ErrorKind::WouldBlock
+                        || e.kind() == std::io::ErrorKind::UnexpectedEof =>
                 {
-                    break; // End of stream
+                    // Not enough data pushed yet for a full packet.
+                    return Ok(None);
                 }
                 Err(e) => {
-                    warn!("Error reading packet: {}", e);
-                    break;
+                    warn!("Error reading MP2 packet: {}", e);
+                    return Ok(None);
                 }
             };
 
             // Skip packets from other tracks
-            if packet.track_id() != track_id {
+            if packet.track_id() != reader.track_id {
                 continue;
             }
 
-            // Decode the packet
-            match decoder.decode(&packet) {
+            match reader.decoder.decode(&packet) {
                 Ok(audio_buf) => {
-                    // Convert to PCM samples
                     let pcm = Self::convert_to_pcm(&audio_buf)?;
-                    all_samples.extend(pcm);
                     self.frames_decoded += 1;
+                    trace!(
+                        "Decoded MP2 frame #{}: {} samples",
+                        self.frames_decoded,
+                        pcm.len()
+                    );
+                    return Ok(Some(pcm));
                 }
                 Err(e) => {
                     warn!("Error decoding MP2 frame: {}", e);
@@ -248,14 +533,6 @@ impl AudioDecoder for Mp2Decoder {
                 }
             }
         }
-
-        trace!(
-            "Decoded {} MP2 samples ({} frames)",
-            all_samples.len(),
-            self.frames_decoded
-        );
-
-        Ok(all_samples)
     }
 
     fn sample_rate(&self) -> u32 {
@@ -268,6 +545,8 @@ impl AudioDecoder for Mp2Decoder {
 
     fn reset(&mut self) {
         self.frames_decoded = 0;
+        self.reader = None;
+        self.buffer.lock().unwrap().clear();
     }
 
     fn name(&self) -> &str {
@@ -292,9 +571,17 @@ pub struct Ac3Decoder {
     /// Detected sample rate (Hz)
     sample_rate: u32,
 
-    /// Output channels (always 2 for stereo)
+    /// Output channels, reflecting the current `output_mode` and (for
+    /// `Passthrough`) the source's true channel count
     channels: u16,
 
+    /// How a multichannel decode is folded down to `channels`
+    output_mode: OutputMode,
+
+    /// Whether a downmix folds the LFE channel into the output instead of
+    /// discarding it; see [`crate::decoder::downmix_with_roles`]
+    include_lfe: bool,
+
     /// Frame counter for statistics
     frames_decoded: u64,
 
@@ -309,12 +596,47 @@ impl Ac3Decoder {
         Ok(Self {
             decoder: None,
             sample_rate: 48000, // Default, updated from stream
-            channels: 2,        // Always output stereo
+            channels: 2,        // Updated once the real output mode/channel count is known
+            output_mode: OutputMode::default(),
+            include_lfe: false,
             frames_decoded: 0,
             initialized: false,
         })
     }
 
+    /// Select how a multichannel AC3 decode is folded down to output
+    pub fn with_output_mode(mut self, mode: OutputMode) -> Self {
+        self.output_mode = mode;
+        self
+    }
+
+    /// Select whether a downmix folds the LFE channel into the output
+    pub fn with_include_lfe(mut self, include_lfe: bool) -> Self {
+        self.include_lfe = include_lfe;
+        self
+    }
+
+    /// Best-effort role mapping for a bare channel count
+    ///
+    /// `ac_ffmpeg`'s `ChannelLayout` only exposes a channel count here, not
+    /// individual channel identities, so two distinct AC3 layouts that
+    /// happen to share a channel count (e.g. acmod 3 "3/0" L,C,R vs acmod 4
+    /// "2/1" L,R,S, both 3 channels) can't be told apart from this alone.
+    /// This maps each count to the more common of its candidate layouts;
+    /// [`crate::ac3_native::NativeAc3Decoder`] knows the real `acmod` and
+    /// can map roles exactly instead.
+    fn standard_roles_for_channel_count(channels: usize) -> Option<Vec<ChannelRole>> {
+        use ChannelRole::*;
+        match channels {
+            2 => Some(vec![Left, Right]),
+            3 => Some(vec![Left, Right, Center]),
+            4 => Some(vec![Left, Right, LeftSurround, RightSurround]),
+            5 => Some(vec![Left, Right, Center, LeftSurround, RightSurround]),
+            6 => Some(vec![Left, Right, Center, Lfe, LeftSurround, RightSurround]),
+            _ => None,
+        }
+    }
+
     /// Initialize decoder on first packet
     fn init_decoder(&mut self) -> Result<()> {
         if self.initialized {
@@ -342,48 +664,6 @@ impl Ac3Decoder {
         Ok(())
     }
 
-    /// Downmix multi-channel audio to stereo
-    fn downmix_to_stereo(&self, samples: Vec<f32>, input_channels: usize) -> Vec<f32> {
-        if input_channels == 2 {
-            return samples; // Already stereo
-        }
-
-        if input_channels == 1 {
-            // Mono to stereo: duplicate
-            let mut stereo = Vec::with_capacity(samples.len() * 2);
-            for sample in samples {
-                stereo.push(sample);
-                stereo.push(sample);
-            }
-            return stereo;
-        }
-
-        // 5.1 to stereo downmix
-        // Standard layout: FL, FR, FC, LFE, BL, BR
-        let frame_count = samples.len() / input_channels;
-        let mut stereo = Vec::with_capacity(frame_count * 2);
-
-        for frame_idx in 0..frame_count {
-            let base = frame_idx * input_channels;
-
-            let fl = samples[base];
-            let fr = samples[base + 1];
-            let fc = samples.get(base + 2).copied().unwrap_or(0.0);
-            let bl = samples.get(base + 4).copied().unwrap_or(0.0);
-            let br = samples.get(base + 5).copied().unwrap_or(0.0);
-
-            // Downmix formula: L = FL + 0.7*FC + 0.5*BL
-            let left = fl + (fc * 0.7) + (bl * 0.5);
-            let right = fr + (fc * 0.7) + (br * 0.5);
-
-            // Prevent clipping
-            stereo.push(left.clamp(-1.0, 1.0));
-            stereo.push(right.clamp(-1.0, 1.0));
-        }
-
-        stereo
-    }
-
     /// Convert audio frame to PCM f32 samples (interleaved)
     fn frame_to_pcm(&self, frame: &ac_ffmpeg::codec::audio::AudioFrame) -> Result<Vec<f32>> {
         let channel_layout = frame.channel_layout();
@@ -469,7 +749,7 @@ impl Ac3Decoder {
 }
 
 impl AudioDecoder for Ac3Decoder {
-    fn decode(&mut self, data: &[u8]) -> Result<Vec<PcmSample>> {
+    fn push(&mut self, data: &[u8]) -> Result<()> {
         // Initialize decoder on first call
         if !self.initialized {
             self.init_decoder()?;
@@ -480,62 +760,87 @@ impl AudioDecoder for Ac3Decoder {
         packet_mut.data_mut().copy_from_slice(data);
         let packet = packet_mut.freeze();
 
-        // Push packet to decoder (borrow ends after this call)
-        self.decoder.as_mut()
+        self.decoder
+            .as_mut()
             .context("AC3 decoder not initialized")?
             .push(packet)
             .context("Failed to push packet to AC3 decoder")?;
 
-        let mut all_samples = Vec::new();
+        Ok(())
+    }
 
-        // Take all decoded frames
-        loop {
-            let frame_opt = self.decoder.as_mut()
-                .context("AC3 decoder not initialized")?
-                .take()
-                .context("Failed to take frame from AC3 decoder")?;
-
-            let frame = match frame_opt {
-                Some(f) => f,
-                None => break,
-            };
+    fn pull(&mut self) -> Result<Option<Vec<PcmSample>>> {
+        let frame_opt = self
+            .decoder
+            .as_mut()
+            .context("AC3 decoder not initialized")?
+            .take()
+            .context("Failed to take frame from AC3 decoder")?;
 
-            // Update sample rate from stream
-            self.sample_rate = frame.sample_rate();
+        let Some(frame) = frame_opt else {
+            return Ok(None);
+        };
 
-            let channel_layout = frame.channel_layout();
-            let input_channels = channel_layout.channels() as usize;
+        // Update sample rate from stream
+        self.sample_rate = frame.sample_rate();
 
-            trace!(
-                "Decoded AC3 frame: {} samples/channel, {} channels, {}Hz",
-                frame.samples(),
-                input_channels,
-                self.sample_rate
-            );
+        let channel_layout = frame.channel_layout();
+        let input_channels = channel_layout.channels() as usize;
 
-            // Convert frame to PCM (decoder borrow released, can call self methods)
-            let pcm = self.frame_to_pcm(&frame)?;
+        trace!(
+            "Decoded AC3 frame: {} samples/channel, {} channels, {}Hz",
+            frame.samples(),
+            input_channels,
+            self.sample_rate
+        );
 
-            // Downmix to stereo if needed
-            let stereo = if input_channels != 2 {
-                trace!("Downmixing {} channels to stereo", input_channels);
-                self.downmix_to_stereo(pcm, input_channels)
-            } else {
-                pcm
-            };
+        // Convert frame to PCM (decoder borrow released, can call self methods)
+        let pcm = self.frame_to_pcm(&frame)?;
 
-            all_samples.extend(stereo);
-            self.frames_decoded += 1;
-        }
+        let output = match self.output_mode {
+            OutputMode::Passthrough => {
+                self.channels = input_channels as u16;
+                pcm
+            }
+            mode if input_channels == 1 => {
+                // A mono source has nothing to fold down from; BS.775's
+                // center-channel attenuation doesn't apply to the only
+                // channel there is.
+                self.channels = if mode == OutputMode::Mono { 1 } else { 2 };
+                if mode == OutputMode::Mono {
+                    pcm
+                } else {
+                    let mut stereo = Vec::with_capacity(pcm.len() * 2);
+                    for sample in pcm {
+                        stereo.push(sample);
+                        stereo.push(sample);
+                    }
+                    stereo
+                }
+            }
+            mode if input_channels == 2 && mode == OutputMode::Stereo => pcm,
+            mode => {
+                self.channels = if mode == OutputMode::Mono { 1 } else { 2 };
+                match Self::standard_roles_for_channel_count(input_channels) {
+                    Some(roles) => {
+                        trace!("Downmixing {} channels to {:?} via BS.775", input_channels, mode);
+                        downmix_with_roles(&pcm, &roles, mode, self.include_lfe)
+                    }
+                    None => {
+                        warn!(
+                            "Unrecognized AC3 channel count {}, passing through undownmixed",
+                            input_channels
+                        );
+                        self.channels = input_channels as u16;
+                        pcm
+                    }
+                }
+            }
+        };
 
-        trace!(
-            "AC3 decode complete: {} PCM samples from {} bytes (frame #{})",
-            all_samples.len(),
-            data.len(),
-            self.frames_decoded
-        );
+        self.frames_decoded += 1;
 
-        Ok(all_samples)
+        Ok(Some(output))
     }
 
     fn sample_rate(&self) -> u32 {
@@ -555,6 +860,14 @@ impl AudioDecoder for Ac3Decoder {
     fn name(&self) -> &str {
         "AC3 (FFmpeg)"
     }
+
+    fn set_output_mode(&mut self, mode: OutputMode) {
+        self.output_mode = mode;
+    }
+
+    fn set_include_lfe(&mut self, include_lfe: bool) {
+        self.include_lfe = include_lfe;
+    }
 }
 
 impl Default for Ac3Decoder {
@@ -563,26 +876,121 @@ impl Default for Ac3Decoder {
     }
 }
 
+/// Which MP1/MP2 decoder implementation [`AutoDecoder`] picks for
+/// [`crate::demux::AudioCodec::Mp2`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mp2Backend {
+    /// Symphonia (pure Rust) - the default. Only accepts Layer II (MP2).
+    Symphonia,
+    /// The `native-mp2` feature's hand-written decoder - also accepts
+    /// Layer I (MP1), and a lighter dependency footprint than Symphonia.
+    Native,
+}
+
+impl Default for Mp2Backend {
+    fn default() -> Self {
+        Self::Symphonia
+    }
+}
+
+/// Which AC3 decoder implementation [`AutoDecoder`] picks for
+/// [`crate::demux::AudioCodec::Ac3`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ac3Backend {
+    /// FFmpeg (`ac-ffmpeg`) - the default, and the only backend that
+    /// decodes real AC3 correctly.
+    Ffmpeg,
+    /// The `native-ac3` feature's hand-written decoder. Must be requested
+    /// explicitly even when the `native-ac3` feature is compiled in - see
+    /// [`crate::ac3_native`]'s "Coverage" section for why it must not be
+    /// picked automatically just because the feature is enabled.
+    Native,
+}
+
+impl Default for Ac3Backend {
+    fn default() -> Self {
+        Self::Ffmpeg
+    }
+}
+
 /// Auto-detecting decoder wrapper
 ///
 /// Automatically selects the appropriate decoder based on codec type.
 pub struct AutoDecoder {
     decoder: Box<dyn AudioDecoder>,
     codec_type: crate::demux::AudioCodec,
+
+    /// Output sample rate decoded PCM is resampled to before being
+    /// returned from `pull`, if set; `None` passes the decoder's native
+    /// rate straight through
+    target_rate: Option<u32>,
+
+    /// Created lazily, once the inner decoder has reported its native
+    /// sample rate on the first successful `pull`
+    resampler: Option<Resampler>,
 }
 
 impl AutoDecoder {
-    /// Create a new auto-detecting decoder
+    /// Create a new auto-detecting decoder, using the default backends
+    /// ([`Mp2Backend::Symphonia`], [`Ac3Backend::Ffmpeg`])
     pub fn new(codec: crate::demux::AudioCodec) -> Result<Self> {
+        Self::with_backends(codec, Mp2Backend::default(), Ac3Backend::default())
+    }
+
+    /// Create a new auto-detecting decoder, picking the given backend when
+    /// `codec` is [`crate::demux::AudioCodec::Mp2`] (ignored otherwise)
+    pub fn with_mp2_backend(codec: crate::demux::AudioCodec, mp2_backend: Mp2Backend) -> Result<Self> {
+        Self::with_backends(codec, mp2_backend, Ac3Backend::default())
+    }
+
+    /// Create a new auto-detecting decoder, picking the given backend when
+    /// `codec` is [`crate::demux::AudioCodec::Ac3`] (ignored otherwise)
+    pub fn with_ac3_backend(codec: crate::demux::AudioCodec, ac3_backend: Ac3Backend) -> Result<Self> {
+        Self::with_backends(codec, Mp2Backend::default(), ac3_backend)
+    }
+
+    /// Create a new auto-detecting decoder, picking the given backend for
+    /// each codec (ignored unless `codec` matches that backend's codec)
+    pub fn with_backends(
+        codec: crate::demux::AudioCodec,
+        mp2_backend: Mp2Backend,
+        ac3_backend: Ac3Backend,
+    ) -> Result<Self> {
         let decoder: Box<dyn AudioDecoder> = match codec {
-            crate::demux::AudioCodec::Mp2 => {
-                debug!("Creating MP2 decoder");
-                Box::new(Mp2Decoder::new()?)
-            }
-            crate::demux::AudioCodec::Ac3 => {
-                debug!("Creating AC3 decoder");
-                Box::new(Ac3Decoder::new()?)
-            }
+            crate::demux::AudioCodec::Mp2 => match mp2_backend {
+                Mp2Backend::Symphonia => {
+                    debug!("Creating MP2 decoder (Symphonia)");
+                    Box::new(Mp2Decoder::new()?)
+                }
+                #[cfg(feature = "native-mp2")]
+                Mp2Backend::Native => {
+                    debug!("Creating MP1/MP2 decoder (native Rust)");
+                    Box::new(crate::mp2_native::Mp1Mp2Decoder::new()?)
+                }
+                #[cfg(not(feature = "native-mp2"))]
+                Mp2Backend::Native => {
+                    anyhow::bail!(
+                        "Mp2Backend::Native requires building with the `native-mp2` feature"
+                    )
+                }
+            },
+            crate::demux::AudioCodec::Ac3 => match ac3_backend {
+                Ac3Backend::Ffmpeg => {
+                    debug!("Creating AC3 decoder (FFmpeg-based)");
+                    Box::new(Ac3Decoder::new()?)
+                }
+                #[cfg(feature = "native-ac3")]
+                Ac3Backend::Native => {
+                    debug!("Creating AC3 decoder (native Rust, no FFmpeg)");
+                    Box::new(crate::ac3_native::NativeAc3Decoder::new()?)
+                }
+                #[cfg(not(feature = "native-ac3"))]
+                Ac3Backend::Native => {
+                    anyhow::bail!(
+                        "Ac3Backend::Native requires building with the `native-ac3` feature"
+                    )
+                }
+            },
             crate::demux::AudioCodec::Aac => {
                 anyhow::bail!("AAC decoding not needed (already in target format)")
             }
@@ -594,9 +1002,38 @@ impl AutoDecoder {
         Ok(Self {
             decoder,
             codec_type: codec,
+            target_rate: None,
+            resampler: None,
         })
     }
 
+    /// Resample decoded PCM to a fixed `rate` before returning it from
+    /// `pull`/`decode`, instead of passing through whatever rate the
+    /// source declares
+    ///
+    /// MP2 sources are commonly 48/44.1/32 kHz and AC3 sources vary too;
+    /// pinning a canonical output rate here means downstream consumers
+    /// (e.g. an AAC encoder configured for one fixed rate) don't each need
+    /// their own rate-conversion step.
+    pub fn with_target_rate(mut self, rate: u32) -> Self {
+        self.target_rate = Some(rate);
+        self
+    }
+
+    /// Select how a multichannel decode (currently only AC3) is folded down
+    /// to output; a no-op for codecs that don't support multichannel output
+    pub fn with_output_mode(mut self, mode: OutputMode) -> Self {
+        self.decoder.set_output_mode(mode);
+        self
+    }
+
+    /// Select whether a multichannel downmix (currently only AC3) folds the
+    /// LFE channel into the output; a no-op for codecs with no LFE channel
+    pub fn with_include_lfe(mut self, include_lfe: bool) -> Self {
+        self.decoder.set_include_lfe(include_lfe);
+        self
+    }
+
     /// Get the codec type
     pub fn codec_type(&self) -> crate::demux::AudioCodec {
         self.codec_type
@@ -604,12 +1041,34 @@ impl AutoDecoder {
 }
 
 impl AudioDecoder for AutoDecoder {
-    fn decode(&mut self, data: &[u8]) -> Result<Vec<PcmSample>> {
-        self.decoder.decode(data)
+    fn push(&mut self, data: &[u8]) -> Result<()> {
+        self.decoder.push(data)
+    }
+
+    fn pull(&mut self) -> Result<Option<Vec<PcmSample>>> {
+        let Some(pcm) = self.decoder.pull()? else {
+            return Ok(None);
+        };
+
+        let Some(target_rate) = self.target_rate else {
+            return Ok(Some(pcm));
+        };
+
+        if self.resampler.is_none() {
+            let native_rate = self.decoder.sample_rate();
+            let channels = self.decoder.channels();
+            debug!(
+                "Initializing AutoDecoder output resampler: {}Hz -> {}Hz ({} channels)",
+                native_rate, target_rate, channels
+            );
+            self.resampler = Some(Resampler::new(native_rate, channels, target_rate, channels));
+        }
+
+        Ok(Some(self.resampler.as_mut().unwrap().process(&pcm)))
     }
 
     fn sample_rate(&self) -> u32 {
-        self.decoder.sample_rate()
+        self.target_rate.unwrap_or_else(|| self.decoder.sample_rate())
     }
 
     fn channels(&self) -> u16 {
@@ -617,7 +1076,8 @@ impl AudioDecoder for AutoDecoder {
     }
 
     fn reset(&mut self) {
-        self.decoder.reset()
+        self.decoder.reset();
+        self.resampler = None;
     }
 
     fn name(&self) -> &str {
@@ -670,4 +1130,67 @@ mod tests {
         let result = AutoDecoder::new(crate::demux::AudioCodec::Aac);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_downmix_with_roles_5_1_to_stereo() {
+        use ChannelRole::*;
+        let roles = [Left, Right, Center, Lfe, LeftSurround, RightSurround];
+        // One frame: L=0.5, R=0.5, C=0.5, LFE=1.0 (excluded by default), Ls=0.5, Rs=0.5
+        let samples = [0.5, 0.5, 0.5, 1.0, 0.5, 0.5];
+        let out = downmix_with_roles(&samples, &roles, OutputMode::Stereo, false);
+        assert_eq!(out.len(), 2);
+        // L_out = (L + 0.707*C + 0.707*Ls) * DOWNMIX_HEADROOM_GAIN, with LFE
+        // excluded from the default downmix
+        let expected = (0.5 + std::f32::consts::FRAC_1_SQRT_2 * 0.5 + std::f32::consts::FRAC_1_SQRT_2 * 0.5)
+            * DOWNMIX_HEADROOM_GAIN;
+        assert!((out[0] - expected).abs() < 1e-5);
+        assert!((out[1] - expected).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_downmix_with_roles_includes_lfe_when_requested() {
+        use ChannelRole::*;
+        let roles = [Left, Right, Lfe];
+        let samples = [0.5, 0.5, 1.0];
+        let excluded = downmix_with_roles(&samples, &roles, OutputMode::Stereo, false);
+        let included = downmix_with_roles(&samples, &roles, OutputMode::Stereo, true);
+        assert!((excluded[0] - 0.5 * DOWNMIX_HEADROOM_GAIN).abs() < 1e-5);
+        // Folding LFE in adds its -3dB contribution on top of the direct channel
+        let expected_included = (0.5 + std::f32::consts::FRAC_1_SQRT_2 * 1.0) * DOWNMIX_HEADROOM_GAIN;
+        assert!((included[0] - expected_included).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_downmix_with_roles_fixed_headroom_prevents_worst_case_clipping() {
+        use ChannelRole::*;
+        // Exactly the combination `DOWNMIX_HEADROOM_GAIN` is sized for: a
+        // direct channel plus a center and a same-side surround, each
+        // folded in at -3dB.
+        let roles = [Left, Center, LeftSurround];
+        let samples = [1.0, 1.0, 1.0];
+        let out = downmix_with_roles(&samples, &roles, OutputMode::Stereo, false);
+        assert!((out[0] - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_downmix_with_roles_fixed_gain_is_deterministic_across_calls() {
+        use ChannelRole::*;
+        let roles = [Left, Right];
+        // A quiet call and a loud call must apply the exact same gain - an
+        // adaptive per-call peak normalization would instead make the
+        // quiet call's effective gain depend on whatever the loud call's
+        // peak happened to be (pumping).
+        let quiet = downmix_with_roles(&[0.1, 0.1], &roles, OutputMode::Stereo, false);
+        let loud = downmix_with_roles(&[1.0, 1.0], &roles, OutputMode::Stereo, false);
+        assert!((quiet[0] / 0.1 - loud[0] / 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_downmix_with_roles_mono_averages_stereo_pair() {
+        use ChannelRole::*;
+        let roles = [Left, Right];
+        let samples = [1.0, 0.5];
+        let out = downmix_with_roles(&samples, &roles, OutputMode::Mono, false);
+        assert_eq!(out, vec![0.75 * DOWNMIX_HEADROOM_GAIN]);
+    }
 }
@@ -26,6 +26,15 @@ const TS_SYNC_BYTE: u8 = 0x47;
 /// MPEG-TS packet size (188 bytes)
 pub const TS_PACKET_SIZE: usize = 188;
 
+/// MPEG-TS packet size with Reed-Solomon FEC parity appended (204 bytes),
+/// as used by some DVB tuner outputs. The trailing 16 bytes are FEC parity
+/// and are skipped before handing the leading 188 bytes to `TsPacket::parse`.
+const TS_PACKET_SIZE_FEC: usize = 204;
+
+/// Number of consecutive sync bytes (at candidate `stride` spacing) required
+/// to confirm a packet framing lock rather than a coincidental 0x47 byte
+const SYNC_LOCK_CONFIRMATIONS: usize = 3;
+
 /// PAT (Program Association Table) PID
 const PAT_PID: u16 = 0x0000;
 
@@ -57,6 +66,74 @@ impl AudioCodec {
     }
 }
 
+/// Broad category of an elementary stream listed in the PMT
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamKind {
+    /// Video (H.264, HEVC, ...)
+    Video,
+    /// Audio (MP2, AC-3, AAC, ...)
+    Audio,
+    /// DVB subtitles or teletext
+    Subtitle,
+    /// Listed in the PMT but not a kind this demuxer classifies
+    Unknown,
+}
+
+impl StreamKind {
+    /// Classify a stream from its PMT `stream_type` byte
+    ///
+    /// Private-data streams (`0x06`) are audio or subtitle depending on
+    /// their descriptors, so callers that find one should refine the result
+    /// with `from_private_descriptor_tag` rather than trusting this alone.
+    pub fn from_stream_type(stream_type: u8) -> Self {
+        match stream_type {
+            0x01 | 0x02 | 0x1B | 0x24 => Self::Video, // MPEG-2, H.264, HEVC
+            0x03 | 0x04 | 0x0F | 0x81 => Self::Audio,  // MPEG audio, AAC, AC-3
+            _ => Self::Unknown,
+        }
+    }
+
+    /// Refine a `0x06` (private data) stream's classification from one of
+    /// its descriptor tags
+    fn from_private_descriptor_tag(desc_tag: u8) -> Option<Self> {
+        match desc_tag {
+            0x6A | 0x7A | 0x81 => Some(Self::Audio),    // AC-3 / E-AC-3 / ATSC AC-3
+            0x56 | 0x59 => Some(Self::Subtitle),          // Teletext / DVB subtitle
+            _ => None,
+        }
+    }
+}
+
+/// An elementary stream listed in the PMT
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamInfo {
+    /// Elementary stream PID
+    pub pid: u16,
+    /// Raw PMT `stream_type` byte
+    pub stream_type: u8,
+    /// Broad category (video/audio/subtitle/unknown)
+    pub kind: StreamKind,
+    /// Audio codec, if `kind` is `StreamKind::Audio`; `AudioCodec::Unknown`
+    /// otherwise
+    pub audio_codec: AudioCodec,
+}
+
+/// Peek the PID of a TS packet without parsing its adaptation field/payload
+///
+/// Callers use this to route a packet (audio vs. PAT/PMT vs. passthrough)
+/// before deciding whether to hand it to the demuxer at all.
+pub fn packet_pid(data: &[u8]) -> Result<u16> {
+    if data.len() != TS_PACKET_SIZE {
+        bail!("Invalid TS packet size: expected {}, got {}", TS_PACKET_SIZE, data.len());
+    }
+
+    if data[0] != TS_SYNC_BYTE {
+        bail!("Invalid sync byte: expected 0x{:02X}, got 0x{:02X}", TS_SYNC_BYTE, data[0]);
+    }
+
+    Ok((((data[1] & 0x1F) as u16) << 8) | (data[2] as u16))
+}
+
 /// MPEG-TS Packet
 ///
 /// Represents a parsed Transport Stream packet (188 bytes).
@@ -87,6 +164,11 @@ pub struct TsPacket {
     /// Continuity counter (4 bits, cycles 0-15)
     pub continuity: u8,
 
+    /// Program Clock Reference, if the adaptation field carried one: a
+    /// 27 MHz counter (`base * 300 + extension`) used to reconstruct
+    /// wall-clock timing alongside the PTS/DTS values
+    pcr: Option<u64>,
+
     /// Payload data (slice of original packet)
     pub payload: Vec<u8>,
 }
@@ -116,10 +198,19 @@ impl TsPacket {
 
         // Calculate payload offset
         let mut payload_offset = 4;
+        let mut pcr = None;
 
-        // Skip adaptation field if present
+        // Skip adaptation field if present, extracting the PCR first if the
+        // adaptation flags byte advertises one
         if has_adaptation {
             let adaptation_length = data[4] as usize;
+            if adaptation_length > 0 {
+                let flags = data[5];
+                let pcr_flag = (flags & 0x10) != 0;
+                if pcr_flag && adaptation_length >= 7 && data.len() >= 12 {
+                    pcr = Some(parse_pcr(&data[6..12]));
+                }
+            }
             payload_offset += 1 + adaptation_length;
         }
 
@@ -139,6 +230,7 @@ impl TsPacket {
             has_adaptation,
             has_payload,
             continuity,
+            pcr,
             payload,
         })
     }
@@ -147,58 +239,260 @@ impl TsPacket {
     pub fn is_scrambled(&self) -> bool {
         self.scrambling != 0
     }
+
+    /// Program Clock Reference carried by this packet's adaptation field,
+    /// if any, as a 27 MHz tick count (`base * 300 + extension`)
+    pub fn pcr(&self) -> Option<u64> {
+        self.pcr
+    }
+}
+
+/// Reconstruct a 27 MHz PCR value from its 6-byte adaptation-field encoding
+///
+/// The first 33 bits (a 90 kHz base, across `bytes[0..4]` plus the top bit
+/// of `bytes[4]`) are multiplied by 300 and combined with a 9-bit extension
+/// (the low bit of `bytes[4]` plus all of `bytes[5]`) to recover the full
+/// 27 MHz clock: `pcr = base * 300 + extension`.
+fn parse_pcr(bytes: &[u8]) -> u64 {
+    let base = ((bytes[0] as u64) << 25)
+        | ((bytes[1] as u64) << 17)
+        | ((bytes[2] as u64) << 9)
+        | ((bytes[3] as u64) << 1)
+        | ((bytes[4] as u64) >> 7);
+    let extension = (((bytes[4] as u64) & 0x01) << 8) | (bytes[5] as u64);
+
+    base * 300 + extension
+}
+
+/// A reassembled PES packet along with the timing extracted from its
+/// optional header, if present
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PesPacket {
+    /// Raw PES packet bytes (start code through the end of the payload)
+    pub data: Vec<u8>,
+
+    /// 33-bit Presentation Timestamp (90kHz clock), if the optional header
+    /// carried one
+    pub pts: Option<u64>,
+
+    /// 33-bit Decode Timestamp (90kHz clock), if the optional header
+    /// carried one (only ever present alongside a PTS)
+    pub dts: Option<u64>,
+
+    /// `true` if this packet was flushed early by "keep broken" mode after
+    /// a discontinuity, rather than completing to its declared length
+    pub incomplete: bool,
+}
+
+/// Stream IDs whose PES packets carry the optional header (and therefore
+/// PTS/DTS); padding, ECM/EMM, and other non-elementary-stream PES types
+/// never do
+fn has_pes_optional_header(stream_id: u8) -> bool {
+    matches!(stream_id, 0xC0..=0xDF | 0xE0..=0xEF | 0xBD)
+}
+
+/// Reassemble a 33-bit PTS/DTS value from its 5-byte PES-header encoding
+///
+/// Each field is split across 3 marker-bit-interleaved groups: 3 bits,
+/// 15 bits, 15 bits, with a `1` marker bit after each group.
+fn read_pes_timestamp(bytes: &[u8]) -> u64 {
+    let b0 = bytes[0] as u64;
+    let b1 = bytes[1] as u64;
+    let b2 = bytes[2] as u64;
+    let b3 = bytes[3] as u64;
+    let b4 = bytes[4] as u64;
+
+    (((b0 >> 1) & 0x07) << 30) | (((b1 << 7) | (b2 >> 1)) << 15) | ((b3 << 7) | (b4 >> 1))
+}
+
+/// Parse the PTS/DTS carried in a complete PES packet's optional header
+///
+/// `payload` is the full PES packet starting at the start code (byte 0).
+/// Returns `(None, None)` for stream IDs that don't carry an optional
+/// header, when `PTS_DTS_flags` indicates neither is present, or when the
+/// buffered payload doesn't yet extend far enough to read the field(s) -
+/// which can happen if the header spilled across a TS packet boundary and
+/// not enough of the PES packet had been reassembled yet.
+fn parse_pts_dts(payload: &[u8]) -> (Option<u64>, Option<u64>) {
+    if payload.len() < 9 {
+        return (None, None);
+    }
+
+    if !has_pes_optional_header(payload[3]) {
+        return (None, None);
+    }
+
+    let pts_dts_flags = (payload[7] >> 6) & 0x03;
+    if pts_dts_flags == 0 {
+        return (None, None);
+    }
+
+    if payload.len() < 14 {
+        return (None, None);
+    }
+    let pts = read_pes_timestamp(&payload[9..14]);
+
+    if pts_dts_flags != 0b11 || payload.len() < 19 {
+        return (Some(pts), None);
+    }
+    let dts = read_pes_timestamp(&payload[14..19]);
+
+    (Some(pts), Some(dts))
+}
+
+/// Metadata known at the start of a PES packet (from its 6-byte start code
+/// + stream_id + length fields), before any payload bytes have arrived
+#[derive(Debug, Clone, Copy)]
+pub struct PesHeader {
+    /// PES stream ID (0xC0-0xDF for MPEG audio, 0xBD for private stream 1/AC3, ...)
+    pub stream_id: u8,
+
+    /// Declared total PES packet length in bytes (header + payload), or
+    /// `None` for the unbounded ("0") convention used by video streams
+    pub pes_length: Option<usize>,
+}
+
+/// Streaming consumer for reassembled PES data
+///
+/// Implement this to receive PES payload as TS packets arrive, without the
+/// buffer-and-clone overhead `process_packet`'s `Option<PesPacket>` return
+/// incurs on high-bitrate streams. Drive it via
+/// `TsDemuxer::process_packet_with_consumer`.
+pub trait PesConsumer {
+    /// A new PES packet has started (on PUSI), before any payload bytes
+    fn begin_packet(&mut self, header: PesHeader);
+
+    /// The next slice of payload bytes, in arrival order, for the packet
+    /// currently in progress
+    fn continue_packet(&mut self, data: &[u8]);
+
+    /// The packet is done: either the declared PES length was satisfied
+    /// (`complete = true`), or it was flushed early by "keep broken" mode
+    /// after a discontinuity (`complete = false`)
+    fn end_packet(&mut self, complete: bool);
+
+    /// A continuity counter gap was detected and "keep broken" mode is off;
+    /// whatever packet was in progress has been discarded
+    fn continuity_error(&mut self);
+}
+
+/// Default `PesConsumer` reproducing the original buffer-and-clone
+/// behavior, used internally by `PesBuffer::add_payload_buffered`
+#[derive(Debug, Default)]
+struct BufferingConsumer {
+    data: Vec<u8>,
+    completed: Option<PesPacket>,
+}
+
+impl PesConsumer for BufferingConsumer {
+    fn begin_packet(&mut self, _header: PesHeader) {
+        self.data.clear();
+    }
+
+    fn continue_packet(&mut self, data: &[u8]) {
+        self.data.extend_from_slice(data);
+    }
+
+    fn end_packet(&mut self, complete: bool) {
+        let (pts, dts) = parse_pts_dts(&self.data);
+        self.completed = Some(PesPacket {
+            data: std::mem::take(&mut self.data),
+            pts,
+            dts,
+            incomplete: !complete,
+        });
+    }
+
+    fn continuity_error(&mut self) {
+        self.data.clear();
+    }
 }
 
 /// PES Packet Buffer
 ///
-/// Accumulates TS packet payloads to reassemble complete PES packets.
+/// Tracks PES reassembly framing (continuity, declared length) for one PID
+/// and drives a `PesConsumer`'s callbacks as TS packet payloads arrive.
 #[derive(Debug)]
 struct PesBuffer {
-    /// Accumulated PES data
-    data: Vec<u8>,
-
     /// Expected continuity counter for next packet
     expected_continuity: u8,
 
-    /// PES packet length (from header, 0 = unbounded)
-    pes_length: usize,
+    /// Declared PES packet length in bytes, once known (0 = not yet started)
+    expected_length: usize,
+
+    /// Bytes handed to the consumer so far for the packet in progress
+    bytes_received: usize,
 
     /// Whether we have started receiving PES data
     started: bool,
+
+    /// Continuity counter of the last packet actually applied (distinct
+    /// from `expected_continuity`), used to recognize a duplicated packet
+    last_continuity: Option<u8>,
+
+    /// Count of genuine discontinuities (not duplicates) seen on this PID
+    discontinuities: u64,
+
+    /// Built-in consumer backing `add_payload_buffered`
+    buffering: BufferingConsumer,
 }
 
 impl PesBuffer {
     fn new() -> Self {
         Self {
-            data: Vec::with_capacity(8192),
             expected_continuity: 0,
+            expected_length: 0,
+            bytes_received: 0,
             started: false,
-            pes_length: 0,
+            last_continuity: None,
+            discontinuities: 0,
+            buffering: BufferingConsumer::default(),
         }
     }
 
-    /// Add TS packet payload to PES buffer
+    /// Feed a TS packet's payload into ongoing PES reassembly, invoking
+    /// `consumer`'s callbacks as packet boundaries are crossed
     ///
-    /// Returns `Some(pes_data)` when a complete PES packet is ready.
-    fn add_payload(&mut self, packet: &TsPacket) -> Result<Option<Vec<u8>>> {
+    /// Payload bytes are handed to `consumer` as they arrive rather than
+    /// copied into an internal buffer - `BufferingConsumer` (used by
+    /// `add_payload_buffered`) is what re-introduces a copy for callers that
+    /// just want a single owned `PesPacket`.
+    fn add_payload<C: PesConsumer>(&mut self, packet: &TsPacket, consumer: &mut C, keep_broken: bool) -> Result<()> {
+        // A TS packet may legitimately be repeated once with the same
+        // continuity counter (e.g. retransmitted by the network); only a
+        // changed counter indicates packets were actually lost in between.
+        if self.started && !packet.payload.is_empty() && Some(packet.continuity) == self.last_continuity {
+            trace!("Duplicate packet for PID {} (continuity {}), ignoring", packet.pid, packet.continuity);
+            return Ok(());
+        }
+
         // Check for packet loss (continuity counter mismatch)
         if self.started && packet.continuity != self.expected_continuity {
             warn!(
                 "Continuity error for PID {}: expected {}, got {}",
                 packet.pid, self.expected_continuity, packet.continuity
             );
-            // Reset buffer on error
+            self.discontinuities += 1;
+            if keep_broken {
+                // Flush what we have rather than discarding it outright, for
+                // downstream decoders tolerant of gaps (e.g. audio codecs
+                // that can conceal a dropped frame).
+                consumer.end_packet(false);
+            } else {
+                consumer.continuity_error();
+            }
             self.reset();
-            return Ok(None);
+            return Ok(());
         }
 
         // Update expected continuity counter (cycles 0-15)
         self.expected_continuity = (packet.continuity + 1) & 0x0F;
+        self.last_continuity = Some(packet.continuity);
 
         // If PUSI flag set, this is the start of a new PES packet
         if packet.payload_start {
-            // If we had data buffered, it's incomplete - discard it
-            if !self.data.is_empty() {
+            // If we had a packet in progress, it's incomplete - discard it
+            if self.started {
                 warn!("Incomplete PES packet discarded (PID {})", packet.pid);
             }
             self.reset();
@@ -215,50 +509,72 @@ impl PesBuffer {
                 bail!("Invalid PES start code");
             }
 
+            let stream_id = packet.payload[3];
+
             // PES packet length (bytes 4-5)
             let pes_length = ((packet.payload[4] as usize) << 8) | (packet.payload[5] as usize);
 
             // Store PES length (0 means unbounded - used for video)
-            self.pes_length = if pes_length == 0 {
+            self.expected_length = if pes_length == 0 {
                 MAX_PES_SIZE
             } else {
                 pes_length + 6 // +6 for PES header
             };
 
-            eprintln!("[RUST PES] PID {}: Started new PES packet, expected length: {} bytes (raw: {})", packet.pid, self.pes_length, pes_length);
+            eprintln!("[RUST PES] PID {}: Started new PES packet, expected length: {} bytes (raw: {})", packet.pid, self.expected_length, pes_length);
             self.started = true;
+
+            consumer.begin_packet(PesHeader {
+                stream_id,
+                pes_length: if pes_length == 0 { None } else { Some(self.expected_length) },
+            });
         }
 
-        // Append payload to buffer
+        // Hand payload to the consumer
         if self.started && !packet.payload.is_empty() {
-            self.data.extend_from_slice(&packet.payload);
+            consumer.continue_packet(&packet.payload);
+            self.bytes_received += packet.payload.len();
 
-            // Check if buffer is getting too large
-            if self.data.len() > MAX_PES_SIZE {
+            // Check if the packet is getting too large
+            if self.bytes_received > MAX_PES_SIZE {
                 warn!("PES buffer too large, resetting");
                 self.reset();
-                return Ok(None);
+                return Ok(());
             }
 
             // Check if PES packet is complete
-            if self.data.len() >= self.pes_length {
-                // Extract complete PES packet
-                eprintln!("[RUST PES] PID {}: Complete PES packet ready! (size: {} bytes, expected: {})", packet.pid, self.data.len(), self.pes_length);
-                let pes_data = self.data.clone();
+            if self.bytes_received >= self.expected_length {
+                eprintln!("[RUST PES] PID {}: Complete PES packet ready! (size: {} bytes, expected: {})", packet.pid, self.bytes_received, self.expected_length);
+                consumer.end_packet(true);
                 self.reset();
-                return Ok(Some(pes_data));
             } else {
-                eprintln!("[RUST PES] PID {}: Buffering... ({}/{} bytes)", packet.pid, self.data.len(), self.pes_length);
+                eprintln!("[RUST PES] PID {}: Buffering... ({}/{} bytes)", packet.pid, self.bytes_received, self.expected_length);
             }
         }
 
-        Ok(None)
+        Ok(())
+    }
+
+    /// Convenience wrapper around `add_payload` driving the built-in
+    /// `BufferingConsumer`, preserving the original buffer-and-clone
+    /// `Option<PesPacket>` return shape.
+    fn add_payload_buffered(&mut self, packet: &TsPacket, keep_broken: bool) -> Result<Option<PesPacket>> {
+        let mut consumer = std::mem::take(&mut self.buffering);
+        let result = self.add_payload(packet, &mut consumer, keep_broken);
+        self.buffering = consumer;
+        result?;
+        Ok(self.buffering.completed.take())
     }
 
+    /// Reset per-packet reassembly state
+    ///
+    /// Deliberately leaves `last_continuity` and `discontinuities` alone:
+    /// duplicate detection and discontinuity counting track the PID across
+    /// packet boundaries, not just within a single PES packet's lifetime.
     fn reset(&mut self) {
-        self.data.clear();
         self.started = false;
-        self.pes_length = 0;
+        self.expected_length = 0;
+        self.bytes_received = 0;
     }
 }
 
@@ -275,9 +591,17 @@ pub struct TsDemuxer {
     /// PES buffers for each PID
     pes_buffers: HashMap<u16, PesBuffer>,
 
-    /// PMT PID (detected from PAT)
+    /// PMT PID (detected from PAT) of the currently demuxed program
     pmt_pid: Option<u16>,
 
+    /// Every `program_number -> pmt_pid` entry discovered in the PAT so far
+    /// (an MPTS carries one per channel multiplexed into the stream)
+    programs: HashMap<u16, u16>,
+
+    /// Program number pinned via `select_program`/`with_selected_program`,
+    /// if any. `None` keeps the original first-program-found behavior.
+    selected_program: Option<u16>,
+
     /// Fallback to standard PIDs if PMT not found after this many packets
     fallback_threshold: u64,
 
@@ -287,6 +611,39 @@ pub struct TsDemuxer {
     /// Statistics
     packets_processed: u64,
     audio_packets: u64,
+
+    /// Byte-stream ring buffer for `push_bytes`, holding unparsed bytes
+    /// between calls (and any bytes scanned past while (re-)acquiring sync)
+    sync_buffer: Vec<u8>,
+
+    /// Detected packet stride once sync is locked: `TS_PACKET_SIZE` (188) for
+    /// plain TS, `TS_PACKET_SIZE_FEC` (204) for RS-FEC framed tuner output.
+    /// `None` while scanning for lock.
+    stride: Option<usize>,
+
+    /// When `true`, a continuity-counter discontinuity flushes the
+    /// in-progress PES packet as `incomplete` instead of discarding it
+    /// outright, for downstream consumers that tolerate gaps
+    keep_broken: bool,
+
+    /// Most recent Program Clock Reference seen on any PID, for correlating
+    /// with PES PTS/DTS values to drive timing
+    last_pcr: Option<u64>,
+
+    /// Every elementary stream listed in the current PMT, keyed by PID
+    /// (video, audio, subtitle/teletext - not just the selected audio PID)
+    streams: HashMap<u16, StreamInfo>,
+
+    /// Completed PES packets produced by `demux_packet`, queued per PID
+    /// until a caller drains them with `take_stream`
+    completed: HashMap<u16, Vec<PesPacket>>,
+
+    /// PCR_PID field from the currently demuxed program's PMT
+    pcr_pid: Option<u16>,
+
+    /// `program_number` of the currently demuxed program (the pinned
+    /// selection, or the first program found in the PAT by default)
+    current_program: Option<u16>,
 }
 
 impl TsDemuxer {
@@ -297,20 +654,200 @@ impl TsDemuxer {
             audio_codec: AudioCodec::Unknown,
             pes_buffers: HashMap::new(),
             pmt_pid: None,
+            programs: HashMap::new(),
+            selected_program: None,
             fallback_threshold: 1000, // Try fallback after 1000 packets (~5 seconds)
             fallback_active: false,
             packets_processed: 0,
             audio_packets: 0,
+            sync_buffer: Vec::with_capacity(4 * TS_PACKET_SIZE_FEC),
+            stride: None,
+            keep_broken: false,
+            last_pcr: None,
+            streams: HashMap::new(),
+            completed: HashMap::new(),
+            pcr_pid: None,
+            current_program: None,
+        }
+    }
+
+    /// Feed raw, unframed bytes from a tuner or capture device
+    ///
+    /// Unlike `process_packet`, the input need not be aligned to packet
+    /// boundaries: it is appended to an internal ring buffer, which scans for
+    /// the 0x47 sync byte and locks onto a packet stride (188 bytes for plain
+    /// TS, 204 for Reed-Solomon FEC-framed streams) once the sync byte
+    /// reappears at that spacing for `SYNC_LOCK_CONFIRMATIONS` consecutive
+    /// packets. If the expected sync byte goes missing (glitch, dropped
+    /// bytes), lock is dropped and the scan restarts rather than bailing.
+    ///
+    /// Returns every complete audio PES packet produced by the bytes consumed
+    /// so far (normally 0 or 1, but a single call can flush several).
+    pub fn push_bytes(&mut self, data: &[u8]) -> Result<Vec<PesPacket>> {
+        self.sync_buffer.extend_from_slice(data);
+        let mut pes_packets = Vec::new();
+
+        loop {
+            if self.stride.is_none() {
+                self.try_acquire_sync_lock();
+                if self.stride.is_none() {
+                    break; // Not enough data yet to confirm a lock
+                }
+            }
+            let stride = self.stride.expect("stride checked above");
+
+            if self.sync_buffer.len() < stride {
+                break; // Need more bytes for a full packet (+ FEC tail)
+            }
+
+            if self.sync_buffer[0] != TS_SYNC_BYTE {
+                warn!("Lost TS sync lock (stride {}), re-scanning", stride);
+                self.stride = None;
+                continue;
+            }
+
+            let packet = self.sync_buffer[..TS_PACKET_SIZE].to_vec();
+            self.sync_buffer.drain(..stride);
+
+            match self.process_packet(&packet) {
+                Ok(Some(pes)) => pes_packets.push(pes),
+                Ok(None) => {}
+                Err(e) => warn!("Dropping unparsable TS packet: {}", e),
+            }
+        }
+
+        Ok(pes_packets)
+    }
+
+    /// Scan `sync_buffer` for a 0x47 byte followed by two more at a
+    /// consistent 188- or 204-byte spacing, and lock onto that stride
+    ///
+    /// Leaves `sync_buffer` untouched (waiting for more data) if there isn't
+    /// yet enough buffered to confirm a candidate; otherwise discards bytes
+    /// before the confirmed lock position, or bytes that were ruled out as
+    /// having no viable lock, to keep the buffer bounded.
+    fn try_acquire_sync_lock(&mut self) {
+        let candidates = [TS_PACKET_SIZE, TS_PACKET_SIZE_FEC];
+        let span = candidates
+            .iter()
+            .max()
+            .copied()
+            .unwrap_or(TS_PACKET_SIZE_FEC)
+            * SYNC_LOCK_CONFIRMATIONS;
+
+        if self.sync_buffer.len() < span {
+            return;
         }
+
+        let scan_end = self.sync_buffer.len() - span + 1;
+        for start in 0..scan_end {
+            if self.sync_buffer[start] != TS_SYNC_BYTE {
+                continue;
+            }
+
+            for &stride in &candidates {
+                let locked = (1..SYNC_LOCK_CONFIRMATIONS)
+                    .all(|i| self.sync_buffer[start + i * stride] == TS_SYNC_BYTE);
+                if locked {
+                    debug!("Acquired TS sync lock at offset {} (stride {})", start, stride);
+                    self.sync_buffer.drain(..start);
+                    self.stride = Some(stride);
+                    return;
+                }
+            }
+        }
+
+        // Nothing in the scanned region locks; drop it so the buffer doesn't
+        // grow unbounded on noise, keeping the unscanned tail for next time.
+        self.sync_buffer.drain(..scan_end);
     }
 
     /// Process a single TS packet
     ///
-    /// Returns `Some(pes_data)` when a complete audio PES packet is ready.
-    pub fn process_packet(&mut self, data: &[u8]) -> Result<Option<Vec<u8>>> {
+    /// Returns `Some(pes_packet)` when a complete audio PES packet is ready.
+    /// This is a convenience wrapper over `process_packet_with_consumer`
+    /// backed by a `BufferingConsumer`, for callers that just want an owned
+    /// `PesPacket`.
+    pub fn process_packet(&mut self, data: &[u8]) -> Result<Option<PesPacket>> {
+        match self.route_packet(data)? {
+            Some(packet) => {
+                let buffer = self.pes_buffers.entry(packet.pid).or_insert_with(PesBuffer::new);
+                buffer.add_payload_buffered(&packet, self.keep_broken)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Process a single TS packet, feeding any audio PES payload directly to
+    /// `consumer` instead of returning an owned `PesPacket`
+    ///
+    /// See `PesConsumer` for why a caller might prefer this over
+    /// `process_packet`.
+    pub fn process_packet_with_consumer<C: PesConsumer>(
+        &mut self,
+        data: &[u8],
+        consumer: &mut C,
+    ) -> Result<()> {
+        match self.route_packet(data)? {
+            Some(packet) => {
+                let keep_broken = self.keep_broken;
+                let buffer = self.pes_buffers.entry(packet.pid).or_insert_with(PesBuffer::new);
+                buffer.add_payload(&packet, consumer, keep_broken)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Demux a single TS packet as a general elementary-stream reassembler,
+    /// not just the selected audio PID
+    ///
+    /// Every PID listed in the current PMT (video, audio, subtitle/teletext)
+    /// gets its own `PesBuffer`; a completed PES packet is queued for
+    /// `take_stream` rather than returned directly, since several PIDs can
+    /// complete from a single call.
+    pub fn demux_packet(&mut self, data: &[u8]) -> Result<()> {
+        let packet = match self.track_packet(data)? {
+            Some(packet) => packet,
+            None => return Ok(()),
+        };
+
+        if !self.streams.contains_key(&packet.pid) {
+            return Ok(());
+        }
+
+        let buffer = self.pes_buffers.entry(packet.pid).or_insert_with(PesBuffer::new);
+        if let Some(pes) = buffer.add_payload_buffered(&packet, self.keep_broken)? {
+            self.completed.entry(packet.pid).or_default().push(pes);
+        }
+
+        Ok(())
+    }
+
+    /// Drain every PES packet `demux_packet` has completed for `pid` so far
+    pub fn take_stream(&mut self, pid: u16) -> Vec<PesPacket> {
+        self.completed.remove(&pid).unwrap_or_default()
+    }
+
+    /// List every elementary stream discovered in the current PMT
+    pub fn streams(&self) -> Vec<StreamInfo> {
+        self.streams.values().copied().collect()
+    }
+
+    /// Parse a single TS packet and perform the bookkeeping shared by every
+    /// ingestion path: PCR tracking, PAT/PMT parsing, and audio fallback
+    /// activation
+    ///
+    /// Returns `None` for scrambled packets (nothing downstream can use
+    /// them), `Some(packet)` otherwise - regardless of which elementary
+    /// stream, if any, the packet belongs to.
+    fn track_packet(&mut self, data: &[u8]) -> Result<Option<TsPacket>> {
         let packet = TsPacket::parse(data)?;
         self.packets_processed += 1;
 
+        if let Some(pcr) = packet.pcr() {
+            self.last_pcr = Some(pcr);
+        }
+
         // Skip scrambled packets
         if packet.is_scrambled() {
             trace!("Skipping scrambled packet (PID {})", packet.pid);
@@ -339,17 +876,28 @@ impl TsDemuxer {
             self.fallback_active = true;
         }
 
+        Ok(Some(packet))
+    }
+
+    /// Classify and route a single TS packet to the selected audio PID
+    ///
+    /// Built on top of `track_packet`; shared by `process_packet` and
+    /// `process_packet_with_consumer`. Returns the parsed packet when it
+    /// carries audio payload that should be fed to a PES reassembler, or
+    /// `None` otherwise. See `demux_packet` for routing every known
+    /// elementary stream instead of just the selected audio PID.
+    fn route_packet(&mut self, data: &[u8]) -> Result<Option<TsPacket>> {
+        let packet = match self.track_packet(data)? {
+            Some(packet) => packet,
+            None => return Ok(None),
+        };
+
         // Handle audio packets
         if let Some(audio_pid) = self.audio_pid {
             if packet.pid == audio_pid {
                 self.audio_packets += 1;
                 eprintln!("[RUST DEMUX] Received audio packet on PID {} (count: {})", packet.pid, self.audio_packets);
-
-                // Get or create PES buffer for this PID
-                let buffer = self.pes_buffers.entry(packet.pid).or_insert_with(PesBuffer::new);
-
-                // Add payload to buffer
-                return buffer.add_payload(&packet);
+                return Ok(Some(packet));
             }
         } else if self.fallback_active {
             // Try common audio PIDs: 68, 128, 256, 257, 258
@@ -371,10 +919,7 @@ impl TsDemuxer {
                             self.audio_pid = Some(packet.pid);
                             self.audio_codec = AudioCodec::Unknown; // Will be detected by decoder
                             self.audio_packets += 1;
-
-                            // Get or create PES buffer for this PID
-                            let buffer = self.pes_buffers.entry(packet.pid).or_insert_with(PesBuffer::new);
-                            return buffer.add_payload(&packet);
+                            return Ok(Some(packet));
                         }
                     }
                 }
@@ -409,23 +954,43 @@ impl TsDemuxer {
             return Ok(()); // Incomplete
         }
 
-        // Parse program entries (skip first 8 bytes of header)
+        // Parse program entries (skip first 8 bytes of header), collecting
+        // every program this PAT describes - an MPTS carries more than one
         let mut offset = 8;
+        let mut first_program: Option<(u16, u16)> = None;
         while offset + 4 <= section_length + 3 {
             let program_number = ((data[offset] as u16) << 8) | (data[offset + 1] as u16);
             let pid = (((data[offset + 2] & 0x1F) as u16) << 8) | (data[offset + 3] as u16);
 
             if program_number != 0 {
-                // Found PMT PID
-                eprintln!("[RUST DEMUX] PAT: Detected PMT PID {} (program_number: {})", pid, program_number);
-                self.pmt_pid = Some(pid);
-                debug!("Detected PMT PID: {}", pid);
-                break;
+                eprintln!("[RUST DEMUX] PAT: Found program {} -> PMT PID {}", program_number, pid);
+                self.programs.insert(program_number, pid);
+                if first_program.is_none() {
+                    first_program = Some((program_number, pid));
+                }
             }
 
             offset += 4;
         }
 
+        // Honor a pinned program selection if it's among those just parsed;
+        // otherwise keep the original first-program-found default.
+        let selected = self
+            .selected_program
+            .and_then(|program_number| {
+                self.programs
+                    .get(&program_number)
+                    .map(|&pid| (program_number, pid))
+            })
+            .or(first_program);
+
+        if let Some((program_number, pid)) = selected {
+            eprintln!("[RUST DEMUX] PAT: Selected program {} -> PMT PID {}", program_number, pid);
+            self.pmt_pid = Some(pid);
+            self.current_program = Some(program_number);
+            debug!("Selected PMT PID: {} (program {})", pid, program_number);
+        }
+
         Ok(())
     }
 
@@ -457,6 +1022,10 @@ impl TsDemuxer {
         // Section length
         let section_length = (((data[1] & 0x0F) as usize) << 8) | (data[2] as usize);
 
+        // PCR_PID
+        let pcr_pid = (((data[8] & 0x1F) as u16) << 8) | (data[9] as u16);
+        self.pcr_pid = Some(pcr_pid);
+
         // Program info length
         let program_info_length = (((data[10] & 0x0F) as usize) << 8) | (data[11] as usize);
 
@@ -464,6 +1033,7 @@ impl TsDemuxer {
         let mut offset = 12 + program_info_length;
         eprintln!("[RUST DEMUX] PMT: section_length={}, program_info_length={}, starting offset={}", section_length, program_info_length, offset);
 
+        let mut first_audio: Option<(u16, AudioCodec)> = None;
         while offset + 5 <= section_length + 3 {
             let stream_type = data[offset];
             let pid = (((data[offset + 1] & 0x1F) as u16) << 8) | (data[offset + 2] as u16);
@@ -473,10 +1043,11 @@ impl TsDemuxer {
 
             // Check if this is an audio stream
             let mut codec = AudioCodec::from_stream_type(stream_type);
+            let mut kind = StreamKind::from_stream_type(stream_type);
 
-            // For stream_type 0x06 (Private Data), check descriptors for AC3
+            // For stream_type 0x06 (Private Data), check descriptors to tell
+            // AC3 audio apart from teletext/DVB subtitles
             if stream_type == 0x06 && es_info_length > 0 {
-                // Parse descriptors to find AC3 audio
                 let desc_start = offset + 5;
                 let desc_end = desc_start + es_info_length;
                 if desc_end <= data.len() {
@@ -485,10 +1056,12 @@ impl TsDemuxer {
                         let desc_tag = data[desc_offset];
                         let desc_len = data[desc_offset + 1] as usize;
 
-                        // AC3 descriptor tags: 0x6A (AC3), 0x7A (E-AC3), 0x81 (ATSC AC3)
-                        if desc_tag == 0x6A || desc_tag == 0x7A || desc_tag == 0x81 {
-                            eprintln!("[RUST DEMUX] PMT: Found AC3 descriptor (tag=0x{:02X}) for PID {}", desc_tag, pid);
-                            codec = AudioCodec::Ac3;
+                        if let Some(refined) = StreamKind::from_private_descriptor_tag(desc_tag) {
+                            eprintln!("[RUST DEMUX] PMT: Found descriptor (tag=0x{:02X}) for PID {}: {:?}", desc_tag, pid, refined);
+                            kind = refined;
+                            if refined == StreamKind::Audio {
+                                codec = AudioCodec::Ac3;
+                            }
                             break;
                         }
 
@@ -497,21 +1070,26 @@ impl TsDemuxer {
                 }
             }
 
-            if codec != AudioCodec::Unknown {
+            self.streams.insert(pid, StreamInfo { pid, stream_type, kind, audio_codec: codec });
+
+            if codec != AudioCodec::Unknown && first_audio.is_none() {
                 eprintln!("[RUST DEMUX] Detected audio PID {} with codec {:?} (stream_type: 0x{:02X})", pid, codec, stream_type);
-                self.audio_pid = Some(pid);
-                self.audio_codec = codec;
                 info!(
                     "Detected audio PID {} with codec {:?} (stream_type: 0x{:02X})",
                     pid, codec, stream_type
                 );
                 debug!("Detected audio: PID {}, codec {:?}", pid, codec);
-                break;
+                first_audio = Some((pid, codec));
             }
 
             offset += 5 + es_info_length;
         }
 
+        if let Some((pid, codec)) = first_audio {
+            self.audio_pid = Some(pid);
+            self.audio_codec = codec;
+        }
+
         Ok(())
     }
 
@@ -520,14 +1098,78 @@ impl TsDemuxer {
         self.audio_pid
     }
 
+    /// Get detected PMT PID of the currently demuxed program
+    pub fn pmt_pid(&self) -> Option<u16> {
+        self.pmt_pid
+    }
+
+    /// PCR_PID field from the currently demuxed program's PMT
+    pub fn pcr_pid(&self) -> Option<u16> {
+        self.pcr_pid
+    }
+
+    /// `program_number` of the currently demuxed program (the pinned
+    /// selection, or the first program found in the PAT by default)
+    pub fn program_number(&self) -> Option<u16> {
+        self.current_program
+    }
+
+    /// Most recent Program Clock Reference seen on any PID (27 MHz ticks),
+    /// for correlating with PES PTS/DTS values
+    pub fn last_pcr(&self) -> Option<u64> {
+        self.last_pcr
+    }
+
+    /// List every `(program_number, pmt_pid)` pair discovered in the PAT so
+    /// far, in a multi-program (MPTS) stream
+    pub fn programs(&self) -> Vec<(u16, u16)> {
+        self.programs.iter().map(|(&pn, &pid)| (pn, pid)).collect()
+    }
+
+    /// Pin demuxing to a specific MPTS program number via its PAT
+    /// `program_number`, instead of the first one found
+    ///
+    /// Can be called before the PAT arrives (it takes effect once the PAT
+    /// is parsed) or after (the PMT PID switches immediately if already
+    /// known).
+    pub fn select_program(&mut self, program_number: u16) {
+        self.selected_program = Some(program_number);
+        if let Some(&pid) = self.programs.get(&program_number) {
+            self.pmt_pid = Some(pid);
+            self.current_program = Some(program_number);
+        }
+    }
+
+    /// Builder variant of `select_program`
+    pub fn with_selected_program(mut self, program_number: u16) -> Self {
+        self.select_program(program_number);
+        self
+    }
+
     /// Get detected audio codec
     pub fn audio_codec(&self) -> AudioCodec {
         self.audio_codec
     }
 
-    /// Get statistics
-    pub fn stats(&self) -> (u64, u64) {
-        (self.packets_processed, self.audio_packets)
+    /// Set whether a continuity discontinuity flushes the in-progress PES
+    /// packet as `incomplete` rather than discarding it
+    ///
+    /// Off by default, matching the original hard-reset-on-discontinuity
+    /// behavior.
+    pub fn set_keep_broken(&mut self, keep: bool) {
+        self.keep_broken = keep;
+    }
+
+    /// Builder variant of `set_keep_broken`
+    pub fn with_keep_broken(mut self, keep: bool) -> Self {
+        self.set_keep_broken(keep);
+        self
+    }
+
+    /// Get statistics: `(packets_processed, audio_packets, discontinuities)`
+    pub fn stats(&self) -> (u64, u64, u64) {
+        let discontinuities = self.pes_buffers.values().map(|b| b.discontinuities).sum();
+        (self.packets_processed, self.audio_packets, discontinuities)
     }
 }
 
@@ -560,6 +1202,43 @@ mod tests {
         assert!(parsed.has_payload);
     }
 
+    #[test]
+    fn test_parse_extracts_pcr_from_adaptation_field() {
+        let mut packet = [0u8; TS_PACKET_SIZE];
+        packet[0] = TS_SYNC_BYTE;
+        packet[1] = 0x00;
+        packet[2] = 0x20; // PID = 0x0020
+        packet[3] = 0x30; // Has adaptation field + payload, continuity = 0
+        packet[4] = 7;    // adaptation_field_length (flags + 6 PCR bytes)
+        packet[5] = 0x10; // PCR_flag set
+
+        // base = 1 (only the top bit of byte4 set), extension = 0
+        // -> pcr = base * 300 + extension = 300
+        packet[6] = 0x00;
+        packet[7] = 0x00;
+        packet[8] = 0x00;
+        packet[9] = 0x00;
+        packet[10] = 0x80;
+        packet[11] = 0x00;
+
+        let parsed = TsPacket::parse(&packet).unwrap();
+        assert_eq!(parsed.pcr(), Some(300));
+    }
+
+    #[test]
+    fn test_parse_pcr_absent_without_flag() {
+        let mut packet = [0u8; TS_PACKET_SIZE];
+        packet[0] = TS_SYNC_BYTE;
+        packet[1] = 0x00;
+        packet[2] = 0x20;
+        packet[3] = 0x30; // Has adaptation field + payload
+        packet[4] = 1;    // adaptation_field_length (flags byte only)
+        packet[5] = 0x00; // PCR_flag not set
+
+        let parsed = TsPacket::parse(&packet).unwrap();
+        assert_eq!(parsed.pcr(), None);
+    }
+
     #[test]
     fn test_parse_invalid_sync() {
         let mut packet = [0u8; TS_PACKET_SIZE];
@@ -583,5 +1262,524 @@ mod tests {
         let demuxer = TsDemuxer::new();
         assert_eq!(demuxer.audio_pid(), None);
         assert_eq!(demuxer.audio_codec(), AudioCodec::Unknown);
+        assert_eq!(demuxer.pmt_pid(), None);
+    }
+
+    #[test]
+    fn test_packet_pid() {
+        let mut packet = [0u8; TS_PACKET_SIZE];
+        packet[0] = TS_SYNC_BYTE;
+        packet[1] = 0x41; // PID[12:8] = 0x01
+        packet[2] = 0x00; // PID[7:0] = 0x00
+        packet[3] = 0x10;
+
+        assert_eq!(packet_pid(&packet).unwrap(), 0x0100);
+    }
+
+    #[test]
+    fn test_packet_pid_rejects_bad_sync() {
+        let mut packet = [0u8; TS_PACKET_SIZE];
+        packet[0] = 0xFF;
+        assert!(packet_pid(&packet).is_err());
+    }
+
+    /// Build a null (PID 0x1FFF) TS packet so `push_bytes` tests have
+    /// something innocuous to lock sync onto without tripping PAT/PMT/audio
+    /// handling.
+    fn build_null_packet() -> [u8; TS_PACKET_SIZE] {
+        let mut packet = [0xFFu8; TS_PACKET_SIZE];
+        packet[0] = TS_SYNC_BYTE;
+        packet[1] = 0x1F; // PID[12:8] = 0x1F
+        packet[2] = 0xFF; // PID[7:0] = 0xFF -> PID 0x1FFF (null)
+        packet[3] = 0x10; // Has payload, continuity = 0
+        packet
+    }
+
+    #[test]
+    fn test_push_bytes_locks_onto_188_byte_stride() {
+        let mut demuxer = TsDemuxer::new();
+        let mut stream = Vec::new();
+        for _ in 0..5 {
+            stream.extend_from_slice(&build_null_packet());
+        }
+
+        let pes = demuxer.push_bytes(&stream).unwrap();
+        assert!(pes.is_empty()); // Null packets never yield PES data
+        assert_eq!(demuxer.stats().0, 5);
+    }
+
+    #[test]
+    fn test_push_bytes_locks_onto_204_byte_fec_stride() {
+        let mut demuxer = TsDemuxer::new();
+        let mut stream = Vec::new();
+        for _ in 0..5 {
+            stream.extend_from_slice(&build_null_packet());
+            stream.extend_from_slice(&[0xAA; 16]); // RS-FEC parity tail
+        }
+
+        demuxer.push_bytes(&stream).unwrap();
+        assert_eq!(demuxer.stats().0, 5);
+    }
+
+    #[test]
+    fn test_push_bytes_recovers_mid_packet_start() {
+        let mut demuxer = TsDemuxer::new();
+        let mut stream = Vec::new();
+        for _ in 0..5 {
+            stream.extend_from_slice(&build_null_packet());
+        }
+        // Simulate a capture that starts mid-packet by dropping leading bytes
+        let misaligned = &stream[37..];
+
+        demuxer.push_bytes(misaligned).unwrap();
+        // One packet's worth of garbage is unrecoverable, the rest lock fine
+        assert!(demuxer.stats().0 >= 3);
+    }
+
+    #[test]
+    fn test_push_bytes_relocks_after_sync_glitch() {
+        let mut demuxer = TsDemuxer::new();
+        let mut stream = Vec::new();
+        for _ in 0..4 {
+            stream.extend_from_slice(&build_null_packet());
+        }
+        // Corrupt a sync byte partway through to force a re-scan
+        stream[3 * TS_PACKET_SIZE] = 0x00;
+        for _ in 0..4 {
+            stream.extend_from_slice(&build_null_packet());
+        }
+
+        demuxer.push_bytes(&stream).unwrap();
+        assert!(demuxer.stats().0 >= 6);
+    }
+
+    #[test]
+    fn test_push_bytes_can_be_fed_in_arbitrary_chunks() {
+        let mut demuxer = TsDemuxer::new();
+        let mut stream = Vec::new();
+        for _ in 0..6 {
+            stream.extend_from_slice(&build_null_packet());
+        }
+
+        for chunk in stream.chunks(37) {
+            demuxer.push_bytes(chunk).unwrap();
+        }
+
+        assert_eq!(demuxer.stats().0, 6);
+    }
+
+    /// Encode a 33-bit PTS/DTS value into the 5-byte marker-bit-interleaved
+    /// form `read_pes_timestamp` decodes
+    fn encode_pes_timestamp(value: u64) -> [u8; 5] {
+        [
+            ((((value >> 30) & 0x07) as u8) << 1) | 0x01,
+            ((value >> 22) & 0xFF) as u8,
+            ((((value >> 15) & 0x7F) as u8) << 1) | 0x01,
+            ((value >> 7) & 0xFF) as u8,
+            (((value & 0x7F) as u8) << 1) | 0x01,
+        ]
+    }
+
+    fn build_pes_with_timestamps(pts: u64, dts: Option<u64>) -> Vec<u8> {
+        let pts_dts_flags: u8 = if dts.is_some() { 0b11 } else { 0b10 };
+        let header_data_length = if dts.is_some() { 10 } else { 5 };
+
+        let mut pes = vec![0x00, 0x00, 0x01, 0xC0, 0x00, 0x00];
+        pes.push(0x80); // marker bits '10' + flags (unused here)
+        pes.push(pts_dts_flags << 6);
+        pes.push(header_data_length);
+        pes.extend_from_slice(&encode_pes_timestamp(pts));
+        if let Some(dts) = dts {
+            pes.extend_from_slice(&encode_pes_timestamp(dts));
+        }
+        pes.extend_from_slice(&[0xAB; 4]); // arbitrary AU payload
+        pes
+    }
+
+    #[test]
+    fn test_read_pes_timestamp_roundtrips() {
+        let value: u64 = 0x1_FFFF_FFFF; // 33-bit max
+        let encoded = encode_pes_timestamp(value);
+        assert_eq!(read_pes_timestamp(&encoded), value);
+    }
+
+    #[test]
+    fn test_parse_pts_dts_pts_only() {
+        let pes = build_pes_with_timestamps(90000, None);
+        let (pts, dts) = parse_pts_dts(&pes);
+        assert_eq!(pts, Some(90000));
+        assert_eq!(dts, None);
+    }
+
+    #[test]
+    fn test_parse_pts_dts_pts_and_dts() {
+        let pes = build_pes_with_timestamps(180000, Some(177300));
+        let (pts, dts) = parse_pts_dts(&pes);
+        assert_eq!(pts, Some(180000));
+        assert_eq!(dts, Some(177300));
+    }
+
+    #[test]
+    fn test_parse_pts_dts_absent_for_non_media_stream_id() {
+        let mut pes = build_pes_with_timestamps(90000, None);
+        pes[3] = 0xBC; // program_stream_map: no optional header
+        assert_eq!(parse_pts_dts(&pes), (None, None));
+    }
+
+    /// Build a PAT section payload (including the leading pointer field)
+    /// listing the given `(program_number, pmt_pid)` entries
+    fn build_pat(programs: &[(u16, u16)]) -> Vec<u8> {
+        let section_length = 9 + 4 * programs.len();
+        let mut data = vec![
+            0x00, // Table ID (PAT)
+            0xB0 | (((section_length >> 8) & 0x0F) as u8),
+            (section_length & 0xFF) as u8,
+            0x00, 0x01, // transport_stream_id
+            0xC1, // reserved + version + current_next_indicator
+            0x00, // section_number
+            0x00, // last_section_number
+        ];
+        for &(program_number, pid) in programs {
+            data.push((program_number >> 8) as u8);
+            data.push((program_number & 0xFF) as u8);
+            data.push(0xE0 | ((pid >> 8) as u8 & 0x1F));
+            data.push((pid & 0xFF) as u8);
+        }
+        data.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]); // CRC32 (unchecked)
+
+        let mut payload = vec![0x00]; // pointer field
+        payload.extend_from_slice(&data);
+        payload
+    }
+
+    #[test]
+    fn test_parse_pat_collects_all_programs() {
+        let mut demuxer = TsDemuxer::new();
+        let pat = build_pat(&[(1, 100), (2, 200), (3, 300)]);
+        demuxer.parse_pat(&pat).unwrap();
+
+        let mut programs = demuxer.programs();
+        programs.sort();
+        assert_eq!(programs, vec![(1, 100), (2, 200), (3, 300)]);
+    }
+
+    #[test]
+    fn test_parse_pat_defaults_to_first_program() {
+        let mut demuxer = TsDemuxer::new();
+        let pat = build_pat(&[(1, 100), (2, 200)]);
+        demuxer.parse_pat(&pat).unwrap();
+
+        assert_eq!(demuxer.pmt_pid(), Some(100));
+    }
+
+    #[test]
+    fn test_select_program_pins_pmt_pid() {
+        let mut demuxer = TsDemuxer::new().with_selected_program(2);
+        let pat = build_pat(&[(1, 100), (2, 200), (3, 300)]);
+        demuxer.parse_pat(&pat).unwrap();
+
+        assert_eq!(demuxer.pmt_pid(), Some(200));
+    }
+
+    #[test]
+    fn test_select_program_switches_already_parsed_pat() {
+        let mut demuxer = TsDemuxer::new();
+        let pat = build_pat(&[(1, 100), (2, 200)]);
+        demuxer.parse_pat(&pat).unwrap();
+        assert_eq!(demuxer.pmt_pid(), Some(100));
+
+        demuxer.select_program(2);
+        assert_eq!(demuxer.pmt_pid(), Some(200));
+    }
+
+    #[test]
+    fn test_parse_pts_dts_handles_header_spanning_tsp_boundary() {
+        // Simulates a PES packet where only the first TS packet's worth of
+        // bytes has been buffered so far - not enough to reach the PTS field
+        let pes = build_pes_with_timestamps(90000, Some(88000));
+        let truncated = &pes[..12]; // cuts off mid-PTS
+        assert_eq!(parse_pts_dts(truncated), (None, None));
+    }
+
+    /// Build a TS packet carrying `payload` verbatim as its payload (no
+    /// adaptation field); `payload` must be exactly `TS_PACKET_SIZE - 4`
+    /// bytes so it fills the packet with no implicit 0xFF filler.
+    fn build_ts_packet(pid: u16, pusi: bool, continuity: u8, payload: &[u8]) -> [u8; TS_PACKET_SIZE] {
+        assert_eq!(payload.len(), TS_PACKET_SIZE - 4);
+        let mut packet = [0u8; TS_PACKET_SIZE];
+        packet[0] = TS_SYNC_BYTE;
+        packet[1] = (if pusi { 0x40 } else { 0x00 }) | (((pid >> 8) as u8) & 0x1F);
+        packet[2] = (pid & 0xFF) as u8;
+        packet[3] = 0x10 | (continuity & 0x0F);
+        packet[4..].copy_from_slice(payload);
+        packet
+    }
+
+    /// Build a PES packet (start code, stream_id, length field, AU payload)
+    /// whose total size is `total_len` bytes
+    fn build_pes_of_size(stream_id: u8, total_len: usize) -> Vec<u8> {
+        let pes_length = (total_len - 6) as u16;
+        let mut pes = vec![
+            0x00,
+            0x00,
+            0x01,
+            stream_id,
+            (pes_length >> 8) as u8,
+            (pes_length & 0xFF) as u8,
+        ];
+        pes.extend(vec![0xABu8; total_len - 6]);
+        pes
+    }
+
+    #[derive(Default)]
+    struct RecordingConsumer {
+        begins: Vec<PesHeader>,
+        received: Vec<u8>,
+        ends: u32,
+        incomplete_ends: u32,
+        continuity_errors: u32,
+    }
+
+    impl PesConsumer for RecordingConsumer {
+        fn begin_packet(&mut self, header: PesHeader) {
+            self.begins.push(header);
+            self.received.clear();
+        }
+
+        fn continue_packet(&mut self, data: &[u8]) {
+            self.received.extend_from_slice(data);
+        }
+
+        fn end_packet(&mut self, complete: bool) {
+            self.ends += 1;
+            if !complete {
+                self.incomplete_ends += 1;
+            }
+        }
+
+        fn continuity_error(&mut self) {
+            self.continuity_errors += 1;
+        }
+    }
+
+    #[test]
+    fn test_process_packet_with_consumer_streams_across_packets() {
+        let mut demuxer = TsDemuxer::new();
+        demuxer.audio_pid = Some(256);
+        demuxer.audio_codec = AudioCodec::Mp2;
+
+        let pes = build_pes_of_size(0xC0, 2 * (TS_PACKET_SIZE - 4));
+        let packet1 = build_ts_packet(256, true, 0, &pes[..TS_PACKET_SIZE - 4]);
+        let packet2 = build_ts_packet(256, false, 1, &pes[TS_PACKET_SIZE - 4..]);
+
+        let mut consumer = RecordingConsumer::default();
+        demuxer.process_packet_with_consumer(&packet1, &mut consumer).unwrap();
+        assert_eq!(consumer.begins.len(), 1);
+        assert_eq!(consumer.begins[0].stream_id, 0xC0);
+        assert_eq!(consumer.ends, 0);
+
+        demuxer.process_packet_with_consumer(&packet2, &mut consumer).unwrap();
+        assert_eq!(consumer.ends, 1);
+        assert_eq!(consumer.received, pes);
+    }
+
+    #[test]
+    fn test_process_packet_with_consumer_reports_continuity_errors() {
+        let mut demuxer = TsDemuxer::new();
+        demuxer.audio_pid = Some(256);
+        demuxer.audio_codec = AudioCodec::Mp2;
+
+        let pes = build_pes_of_size(0xC0, 2 * (TS_PACKET_SIZE - 4));
+        let packet1 = build_ts_packet(256, true, 0, &pes[..TS_PACKET_SIZE - 4]);
+        // Continuity should be 1, not 5 - simulates a dropped packet
+        let packet2 = build_ts_packet(256, false, 5, &pes[TS_PACKET_SIZE - 4..]);
+
+        let mut consumer = RecordingConsumer::default();
+        demuxer.process_packet_with_consumer(&packet1, &mut consumer).unwrap();
+        demuxer.process_packet_with_consumer(&packet2, &mut consumer).unwrap();
+
+        assert_eq!(consumer.continuity_errors, 1);
+        assert_eq!(consumer.ends, 0);
+    }
+
+    #[test]
+    fn test_process_packet_still_returns_buffered_pes_packet() {
+        let mut demuxer = TsDemuxer::new();
+        demuxer.audio_pid = Some(256);
+        demuxer.audio_codec = AudioCodec::Mp2;
+
+        let pes = build_pes_of_size(0xC0, TS_PACKET_SIZE - 4);
+        let packet = build_ts_packet(256, true, 0, &pes);
+
+        let pes_packet = demuxer
+            .process_packet(&packet)
+            .unwrap()
+            .expect("expected a complete PES packet");
+        assert_eq!(pes_packet.data, pes);
+    }
+
+    #[test]
+    fn test_process_packet_with_consumer_drops_duplicate_packet() {
+        let mut demuxer = TsDemuxer::new();
+        demuxer.audio_pid = Some(256);
+        demuxer.audio_codec = AudioCodec::Mp2;
+
+        let pes = build_pes_of_size(0xC0, 2 * (TS_PACKET_SIZE - 4));
+        let packet1 = build_ts_packet(256, true, 0, &pes[..TS_PACKET_SIZE - 4]);
+        // Same continuity as packet1, re-delivered (e.g. retransmitted)
+        let duplicate = build_ts_packet(256, false, 0, &pes[..TS_PACKET_SIZE - 4]);
+        let packet2 = build_ts_packet(256, false, 1, &pes[TS_PACKET_SIZE - 4..]);
+
+        let mut consumer = RecordingConsumer::default();
+        demuxer.process_packet_with_consumer(&packet1, &mut consumer).unwrap();
+        demuxer.process_packet_with_consumer(&duplicate, &mut consumer).unwrap();
+        demuxer.process_packet_with_consumer(&packet2, &mut consumer).unwrap();
+
+        assert_eq!(consumer.continuity_errors, 0);
+        assert_eq!(consumer.ends, 1);
+        assert_eq!(consumer.received, pes);
+        assert_eq!(demuxer.stats().2, 0);
+    }
+
+    #[test]
+    fn test_keep_broken_false_discards_partial_packet_on_discontinuity() {
+        let mut demuxer = TsDemuxer::new();
+        demuxer.audio_pid = Some(256);
+        demuxer.audio_codec = AudioCodec::Mp2;
+
+        let pes = build_pes_of_size(0xC0, 2 * (TS_PACKET_SIZE - 4));
+        let packet1 = build_ts_packet(256, true, 0, &pes[..TS_PACKET_SIZE - 4]);
+        // Continuity should be 1, not 5 - simulates a dropped packet
+        let packet2 = build_ts_packet(256, false, 5, &pes[TS_PACKET_SIZE - 4..]);
+
+        let mut consumer = RecordingConsumer::default();
+        demuxer.process_packet_with_consumer(&packet1, &mut consumer).unwrap();
+        demuxer.process_packet_with_consumer(&packet2, &mut consumer).unwrap();
+
+        assert_eq!(consumer.continuity_errors, 1);
+        assert_eq!(consumer.ends, 0);
+        assert_eq!(demuxer.stats().2, 1);
+    }
+
+    #[test]
+    fn test_keep_broken_true_flushes_partial_packet_as_incomplete() {
+        let mut demuxer = TsDemuxer::new().with_keep_broken(true);
+        demuxer.audio_pid = Some(256);
+        demuxer.audio_codec = AudioCodec::Mp2;
+
+        let pes = build_pes_of_size(0xC0, 2 * (TS_PACKET_SIZE - 4));
+        let packet1 = build_ts_packet(256, true, 0, &pes[..TS_PACKET_SIZE - 4]);
+        // Continuity should be 1, not 5 - simulates a dropped packet
+        let packet2 = build_ts_packet(256, false, 5, &pes[TS_PACKET_SIZE - 4..]);
+
+        let mut consumer = RecordingConsumer::default();
+        demuxer.process_packet_with_consumer(&packet1, &mut consumer).unwrap();
+        demuxer.process_packet_with_consumer(&packet2, &mut consumer).unwrap();
+
+        assert_eq!(consumer.continuity_errors, 0);
+        assert_eq!(consumer.ends, 1);
+        assert_eq!(consumer.incomplete_ends, 1);
+        assert_eq!(demuxer.stats().2, 1);
+    }
+
+    #[test]
+    fn test_set_keep_broken_toggles_existing_instance() {
+        let mut demuxer = TsDemuxer::new();
+        demuxer.audio_pid = Some(256);
+        demuxer.audio_codec = AudioCodec::Mp2;
+        demuxer.set_keep_broken(true);
+
+        let pes = build_pes_of_size(0xC0, 2 * (TS_PACKET_SIZE - 4));
+        let packet1 = build_ts_packet(256, true, 0, &pes[..TS_PACKET_SIZE - 4]);
+        let packet2 = build_ts_packet(256, false, 5, &pes[TS_PACKET_SIZE - 4..]);
+
+        let pes_packet = demuxer.process_packet(&packet1).unwrap();
+        assert!(pes_packet.is_none());
+        let pes_packet = demuxer
+            .process_packet(&packet2)
+            .unwrap()
+            .expect("keep_broken flush should still yield a packet");
+        assert!(pes_packet.incomplete);
+    }
+
+    /// Build a PMT section payload (including the leading pointer field)
+    /// listing the given `(stream_type, pid)` entries, with no descriptors
+    fn build_pmt(streams: &[(u8, u16)]) -> Vec<u8> {
+        let mut entries = Vec::new();
+        for &(stream_type, pid) in streams {
+            entries.push(stream_type);
+            entries.push(0xE0 | ((pid >> 8) as u8 & 0x1F));
+            entries.push((pid & 0xFF) as u8);
+            entries.push(0xF0); // reserved + ES_info_length high bits
+            entries.push(0x00); // ES_info_length low byte (no descriptors)
+        }
+
+        let section_length = 9 + entries.len() + 4;
+        let mut data = vec![
+            0x02, // Table ID (PMT)
+            0xB0 | (((section_length >> 8) & 0x0F) as u8),
+            (section_length & 0xFF) as u8,
+            0x00, 0x01, // program_number
+            0xC1, // reserved + version + current_next_indicator
+            0x00, // section_number
+            0x00, // last_section_number
+            0xE0, 0x00, // PCR_PID (unused by this demuxer)
+            0xF0, 0x00, // program_info_length = 0
+        ];
+        data.extend_from_slice(&entries);
+        data.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]); // CRC32 (unchecked)
+
+        let mut payload = vec![0x00]; // pointer field
+        payload.extend_from_slice(&data);
+        payload
+    }
+
+    #[test]
+    fn test_parse_pmt_classifies_video_and_audio_streams() {
+        let mut demuxer = TsDemuxer::new();
+        let pmt = build_pmt(&[(0x1B, 100), (0x03, 200), (0x24, 300)]);
+        demuxer.parse_pmt(&pmt).unwrap();
+
+        let mut streams = demuxer.streams();
+        streams.sort_by_key(|s| s.pid);
+        assert_eq!(streams.len(), 3);
+        assert_eq!(streams[0], StreamInfo { pid: 100, stream_type: 0x1B, kind: StreamKind::Video, audio_codec: AudioCodec::Unknown });
+        assert_eq!(streams[1], StreamInfo { pid: 200, stream_type: 0x03, kind: StreamKind::Audio, audio_codec: AudioCodec::Mp2 });
+        assert_eq!(streams[2], StreamInfo { pid: 300, stream_type: 0x24, kind: StreamKind::Video, audio_codec: AudioCodec::Unknown });
+
+        // Still exposes the original single-audio-PID convenience accessor
+        assert_eq!(demuxer.audio_pid(), Some(200));
+        assert_eq!(demuxer.audio_codec(), AudioCodec::Mp2);
+    }
+
+    #[test]
+    fn test_demux_packet_reassembles_non_audio_stream_via_take_stream() {
+        let mut demuxer = TsDemuxer::new();
+        let pmt = build_pmt(&[(0x1B, 301), (0x03, 200)]);
+        demuxer.parse_pmt(&pmt).unwrap();
+
+        let pes = build_pes_of_size(0xE0, TS_PACKET_SIZE - 4); // video stream_id
+        let packet = build_ts_packet(301, true, 0, &pes);
+
+        demuxer.demux_packet(&packet).unwrap();
+
+        let mut completed = demuxer.take_stream(301);
+        assert_eq!(completed.len(), 1);
+        assert_eq!(completed.pop().unwrap().data, pes);
+        // Draining empties the queue until more packets complete
+        assert!(demuxer.take_stream(301).is_empty());
+    }
+
+    #[test]
+    fn test_demux_packet_ignores_pids_not_listed_in_pmt() {
+        let mut demuxer = TsDemuxer::new();
+        let pmt = build_pmt(&[(0x03, 200)]);
+        demuxer.parse_pmt(&pmt).unwrap();
+
+        let pes = build_pes_of_size(0xE0, TS_PACKET_SIZE - 4);
+        let packet = build_ts_packet(999, true, 0, &pes);
+
+        demuxer.demux_packet(&packet).unwrap();
+        assert!(demuxer.take_stream(999).is_empty());
     }
 }
@@ -27,6 +27,8 @@
 use anyhow::{Context, Result};
 use tracing::{debug, trace, warn};
 
+use crate::adts::{parse_header, AdtsFrameHeader};
+
 // ac-ffmpeg imports for AAC encoder (TODO: Fix API compatibility)
 // Temporarily disabled until ac-ffmpeg 0.19 API is properly researched
 // use ac_ffmpeg::codec::audio::{AudioEncoder as FfmpegAudioEncoder, AudioFrame};
@@ -62,6 +64,71 @@ impl AacProfile {
             Self::HeAacV2 => 28, // HE-AAC v2 (AAC PS)
         }
     }
+
+    /// MPEG-4 `audioObjectType` value, as carried in `AudioSpecificConfig`
+    pub fn audio_object_type(&self) -> u8 {
+        match self {
+            Self::AacLc => 2,   // AAC-LC
+            Self::HeAac => 5,   // SBR
+            Self::HeAacV2 => 29, // PS
+        }
+    }
+}
+
+/// AAC transport/framing mode for encoder output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AacEncoderMode {
+    /// Each encoded frame is prefixed with a 7-byte ADTS header (MPEG-TS)
+    AdtsFramed,
+    /// Raw AAC packets with no in-band header; the `AudioSpecificConfig`
+    /// is carried out-of-band instead (MP4/fMP4 `esds`/`mp4a` box)
+    RawWithAsc,
+}
+
+impl Default for AacEncoderMode {
+    fn default() -> Self {
+        Self::AdtsFramed
+    }
+}
+
+/// Minimal MSB-first bit writer for packing bitstream fields (e.g.
+/// `AudioSpecificConfig`) that don't align to byte boundaries
+struct BitWriter {
+    bytes: Vec<u8>,
+    cur: u8,
+    bits_in_cur: u8,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            cur: 0,
+            bits_in_cur: 0,
+        }
+    }
+
+    fn push_bits(&mut self, value: u32, num_bits: u8) {
+        for i in (0..num_bits).rev() {
+            let bit = ((value >> i) & 0x1) as u8;
+            self.cur = (self.cur << 1) | bit;
+            self.bits_in_cur += 1;
+            if self.bits_in_cur == 8 {
+                self.bytes.push(self.cur);
+                self.cur = 0;
+                self.bits_in_cur = 0;
+            }
+        }
+    }
+
+    /// Finish the stream, zero-padding the final partial byte
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits_in_cur > 0 {
+            self.cur <<= 8 - self.bits_in_cur;
+            self.bytes.push(self.cur);
+        }
+        self.bytes
+    }
 }
 
 /// AAC Encoder Configuration
@@ -78,6 +145,18 @@ pub struct AacEncoderConfig {
 
     /// AAC profile
     pub profile: AacProfile,
+
+    /// Output transport: in-band ADTS headers, or raw AAC with the
+    /// `AudioSpecificConfig` carried out-of-band (MP4/fMP4)
+    pub mode: AacEncoderMode,
+
+    /// fdk-aac "afterburner" mode: trades encode speed for quality at a
+    /// given bitrate. Ignored by `FfmpegAacEncoder`.
+    pub afterburner: bool,
+
+    /// fdk-aac VBR quality (1-5); `None` selects CBR at `bitrate` instead.
+    /// Ignored by `FfmpegAacEncoder`, which is always CBR.
+    pub vbr: Option<u8>,
 }
 
 impl Default for AacEncoderConfig {
@@ -87,13 +166,18 @@ impl Default for AacEncoderConfig {
             channels: 2,
             bitrate: 192000, // 192 kbps
             profile: AacProfile::AacLc,
+            mode: AacEncoderMode::default(),
+            afterburner: true,
+            vbr: None,
         }
     }
 }
 
 impl AacEncoderConfig {
-    /// Validate configuration
-    pub fn validate(&self) -> Result<()> {
+    /// Validate configuration, resolving `bitrate: 0` ("auto") and
+    /// clamping an overly high bitrate down to what this channel
+    /// count/sample rate can actually carry
+    pub fn validate(&mut self) -> Result<()> {
         if self.sample_rate < 8000 || self.sample_rate > 96000 {
             anyhow::bail!(
                 "Invalid sample rate: {} (must be 8000-96000 Hz)",
@@ -105,15 +189,140 @@ impl AacEncoderConfig {
             anyhow::bail!("Invalid channel count: {} (must be 1-8)", self.channels);
         }
 
-        if self.bitrate < 32000 || self.bitrate > 512000 {
+        if self.bitrate == 0 {
+            self.bitrate = self.auto_bitrate();
+        }
+
+        // HE-AAC/HE-AACv2 are worthwhile precisely at the low bitrates SBR
+        // and parametric stereo were designed for; only AAC-LC needs the
+        // higher floor.
+        let min_bitrate = match self.profile {
+            AacProfile::AacLc => 32000,
+            AacProfile::HeAac | AacProfile::HeAacV2 => 16000,
+        };
+        if self.bitrate < min_bitrate {
+            anyhow::bail!(
+                "Invalid bitrate: {} (must be at least {} bps)",
+                self.bitrate,
+                min_bitrate
+            );
+        }
+
+        // AAC can't exceed 6144 bits/channel per 1024-sample frame; rather
+        // than reject a too-high request outright, clamp it to what this
+        // layout can actually carry.
+        let max_bitrate = self.max_bitrate();
+        if self.bitrate > max_bitrate {
+            warn!(
+                "Requested bitrate {} bps exceeds the AAC frame ceiling of {} bps for {} channel(s) at {}Hz; clamping",
+                self.bitrate, max_bitrate, self.channels, self.sample_rate
+            );
+            self.bitrate = max_bitrate;
+        }
+
+        if self.profile == AacProfile::HeAacV2 && self.channels != 2 {
             anyhow::bail!(
-                "Invalid bitrate: {} (must be 32000-512000 bps)",
-                self.bitrate
+                "HE-AACv2 (parametric stereo) requires 2-channel input, got {}",
+                self.channels
             );
         }
 
         Ok(())
     }
+
+    /// Maximum bitrate this channel count/sample rate can carry without
+    /// exceeding AAC's 6144-bits-per-channel-per-frame ceiling
+    pub fn max_bitrate(&self) -> u32 {
+        (6144.0 * self.channels as f64 / 1024.0 * self.sample_rate as f64) as u32
+    }
+
+    /// Derive a sensible target bitrate from channel layout and sample
+    /// rate, for callers that don't want to pick one themselves
+    ///
+    /// Scales roughly 64 kbps per stereo pair / mono channel element
+    /// (a single channel element, "SCE", or channel pair element, "CPE",
+    /// in MPEG-4 AAC terms) at 48kHz, adjusted for other sample rates,
+    /// then clamped to `max_bitrate()`.
+    pub fn auto_bitrate(&self) -> u32 {
+        const BASE_BITRATE_PER_ELEMENT: f64 = 64_000.0;
+
+        let elements = (self.channels as f64 / 2.0).ceil();
+        let rate_scale = self.sample_rate as f64 / 48_000.0;
+        let target = (elements * BASE_BITRATE_PER_ELEMENT * rate_scale) as u32;
+
+        target.min(self.max_bitrate())
+    }
+
+    /// Build the MPEG-4 `AudioSpecificConfig` bitstream describing this
+    /// encoder's profile/sample-rate/channels
+    ///
+    /// Used for out-of-band signaling in MP4/fMP4 containers (the `esds` /
+    /// `mp4a` box) when the encoder is run in `AacEncoderMode::RawWithAsc`,
+    /// as opposed to the in-band ADTS headers `AdtsHeader::generate` emits.
+    pub fn audio_specific_config(&self) -> Result<Vec<u8>> {
+        let mut bits = BitWriter::new();
+
+        // The backward-compatible explicit-SBR form below (0x2B7 sync
+        // extension) signals HE-AAC/HE-AACv2 as an *extension* on top of a
+        // base AAC-LC stream, so the base `audioObjectType` must be 2
+        // (AAC-LC) for those profiles - writing the profile's own object
+        // type (5 for SBR, 29 for PS) here instead would make the base
+        // stream claim to be SBR/PS-coded without an SBR/PS extension of
+        // its own, which doesn't parse as valid HE-AAC ASC.
+        let base_object_type = match self.profile {
+            AacProfile::AacLc => self.profile.audio_object_type(),
+            AacProfile::HeAac | AacProfile::HeAacV2 => 2,
+        };
+        bits.push_bits(base_object_type as u32, 5);
+
+        // The primary samplingFrequencyIndex describes the core stream
+        // (half the output rate for SBR profiles); the full output rate
+        // is carried separately in the SBR extension below.
+        let core_rate = core_sample_rate(self.profile, self.sample_rate);
+        match AdtsHeader::sample_rate_to_index(core_rate) {
+            Ok(index) => bits.push_bits(index as u32, 4),
+            Err(_) => {
+                bits.push_bits(0x0F, 4); // Escape: explicit sample rate follows
+                bits.push_bits(core_rate, 24);
+            }
+        }
+
+        bits.push_bits(self.channels as u32, 4);
+
+        // GASpecificConfig: frameLengthFlag, dependsOnCoreCoder, extensionFlag (all 0 for LC)
+        bits.push_bits(0, 3);
+
+        if matches!(self.profile, AacProfile::HeAac | AacProfile::HeAacV2) {
+            // Backward-compatible explicit SBR signaling
+            bits.push_bits(0x2B7, 11); // syncExtensionType
+            bits.push_bits(5, 5); // extensionAudioObjectType = SBR
+            bits.push_bits(1, 1); // sbrPresentFlag
+            let ext_index = AdtsHeader::sample_rate_to_index(self.sample_rate).unwrap_or(0x0F);
+            bits.push_bits(ext_index as u32, 4);
+        }
+
+        Ok(bits.finish())
+    }
+}
+
+/// Selectable AAC encoder backend
+///
+/// `AudioRemuxer` builds the concrete `Box<dyn AacEncoder>` matching this
+/// choice; both backends implement the same trait so the rest of the
+/// pipeline doesn't need to know which one is in use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncoderBackend {
+    /// ac-ffmpeg's built-in AAC encoder
+    Ffmpeg,
+    /// The `fdk-aac` crate (Fraunhofer FDK AAC) - generally higher quality
+    /// at a given bitrate, and the backend HE-AAC/HE-AACv2 profiles will need
+    Fdk,
+}
+
+impl Default for EncoderBackend {
+    fn default() -> Self {
+        Self::Ffmpeg
+    }
 }
 
 /// AAC Encoder Trait
@@ -149,6 +358,26 @@ pub trait AacEncoder: Send {
 
     /// Get encoder name for logging
     fn name(&self) -> &str;
+
+    /// Encoder priming delay in PCM sample frames
+    ///
+    /// AAC-LC encoders buffer ahead before producing their first output
+    /// frame; the skip-cut subsystem discards this many leading decoded
+    /// frames so the first emitted AAC frame isn't shifted relative to
+    /// the preserved PTS timeline.
+    fn priming_delay(&self) -> u32;
+}
+
+/// Sample rate of the SBR *core* stream for a given profile/output rate
+///
+/// SBR reconstructs the upper half of the spectrum from a half-rate core,
+/// so HE-AAC/HE-AACv2 signal the core at half the apparent output rate;
+/// AAC-LC has no core/extension split and signals the full rate directly.
+fn core_sample_rate(profile: AacProfile, output_sample_rate: u32) -> u32 {
+    match profile {
+        AacProfile::AacLc => output_sample_rate,
+        AacProfile::HeAac | AacProfile::HeAacV2 => output_sample_rate / 2,
+    }
 }
 
 /// ADTS Header Builder
@@ -195,7 +424,7 @@ impl AdtsHeader {
             anyhow::bail!("Invalid channel count for ADTS: {}", channels);
         }
 
-        // ADTS profile (subtract 1 for ADTS encoding)
+        // ADTS profile field (object_type - 1, as `adts_profile()` returns it)
         let adts_profile = profile.adts_profile();
 
         // Total frame length (ADTS header + AAC data)
@@ -213,7 +442,13 @@ impl AdtsHeader {
         header[1] = 0xF1; // 0xF0 | 0x01 (MPEG-4) | 0x00 (no CRC)
 
         // Byte 2: Profile (2 bits) + Sample rate index (4 bits) + Private (1 bit) + Channel MSB (1 bit)
-        header[2] = ((adts_profile - 1) << 6) | (sample_rate_index << 2) | (channel_config >> 2);
+        // HE-AAC/HE-AACv2 (4/28) don't fit the 2-bit field; their ADTS
+        // header describes the AAC-LC core, like the ASC's base object type.
+        let header_profile = match profile {
+            AacProfile::AacLc => adts_profile,
+            AacProfile::HeAac | AacProfile::HeAacV2 => AacProfile::AacLc.adts_profile(),
+        };
+        header[2] = (header_profile << 6) | (sample_rate_index << 2) | (channel_config >> 2);
 
         // Byte 3: Channel LSB (2 bits) + Original (1 bit) + Home (1 bit) + Copyrighted (1 bit) + Copyright start (1 bit) + Frame length MSB (2 bits)
         header[3] = ((channel_config & 0x03) << 6) | ((total_length >> 11) as u8);
@@ -252,6 +487,126 @@ impl AdtsHeader {
         };
         Ok(index)
     }
+
+    /// Parse a single ADTS header, the inverse of `generate`
+    ///
+    /// Decodes profile, sample rate, and channel count, and locates the
+    /// raw AAC payload within the frame so callers can extract it for
+    /// passthrough validation or stream inspection.
+    pub fn parse(bytes: &[u8]) -> Result<AdtsFrameInfo> {
+        parse_header(bytes).map(AdtsFrameInfo::from)
+    }
+}
+
+/// Parsed ADTS frame, as returned by `AdtsHeader::parse` / yielded by
+/// `AdtsReader`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdtsFrameInfo {
+    /// MPEG-4 audio object type (ADTS profile + 1; 2 = AAC-LC)
+    pub profile: u8,
+
+    /// Sample rate in Hz
+    pub sample_rate: u32,
+
+    /// Number of channels
+    pub channels: u16,
+
+    /// Offset of the raw AAC payload within the frame (7 bytes, or 9 with CRC)
+    pub payload_offset: usize,
+
+    /// Length of the raw AAC payload in bytes
+    pub payload_len: usize,
+
+    /// Total frame length in bytes (header + payload)
+    pub frame_len: usize,
+}
+
+impl From<AdtsFrameHeader> for AdtsFrameInfo {
+    fn from(header: AdtsFrameHeader) -> Self {
+        Self {
+            profile: header.object_type,
+            sample_rate: header.sample_rate,
+            channels: header.channels(),
+            payload_offset: header.header_len,
+            payload_len: header.frame_length - header.header_len,
+            frame_len: header.frame_length,
+        }
+    }
+}
+
+/// Walks a byte buffer yielding ADTS frame boundaries
+///
+/// Resyncs past garbage bytes to the next sync word and stops (without
+/// erroring) once fewer bytes remain than a full frame needs, so callers
+/// can feed in partial buffers - e.g. across demux resync or socket reads
+/// - and retry once more data has arrived.
+pub struct AdtsReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> AdtsReader<'a> {
+    /// Create a reader over `data`, starting at the beginning of the buffer
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    /// Bytes not yet consumed by a yielded frame: either a partial trailing
+    /// frame awaiting more data, or all of `data` if nothing was found yet
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.data[self.offset..]
+    }
+}
+
+impl<'a> Iterator for AdtsReader<'a> {
+    type Item = (AdtsFrameInfo, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.offset + 7 <= self.data.len() {
+            let remaining = &self.data[self.offset..];
+
+            if remaining[0] != 0xFF || (remaining[1] & 0xF0) != 0xF0 {
+                self.offset += 1; // Resync: skip garbage byte
+                continue;
+            }
+
+            let info = match AdtsHeader::parse(remaining) {
+                Ok(info) => info,
+                Err(_) => {
+                    self.offset += 1;
+                    continue;
+                }
+            };
+
+            if info.frame_len > remaining.len() {
+                break; // Full frame not buffered yet; wait for more data
+            }
+
+            let payload = &remaining[info.payload_offset..info.frame_len];
+            self.offset += info.frame_len;
+            return Some((info, payload));
+        }
+
+        None
+    }
+}
+
+/// Declares the format of PCM samples handed to `FfmpegAacEncoder::encode`
+///
+/// Real demuxed streams rarely already match the encoder's target
+/// sample rate/channel layout (44.1kHz mono, 96kHz 5.1, ...); this lets
+/// `FfmpegAacEncoder::with_input_format` insert a resampling stage ahead
+/// of the encoder instead of requiring the caller to pre-condition audio.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InputFormat {
+    /// Input sample rate in Hz
+    pub sample_rate: u32,
+
+    /// Input channel count
+    pub channels: u16,
+
+    /// Input PCM sample format
+    pub sample_fmt: ac_ffmpeg::format::sample::Type,
 }
 
 /// FFmpeg AAC Encoder
@@ -265,21 +620,49 @@ pub struct FfmpegAacEncoder {
     /// Encoder configuration
     config: AacEncoderConfig,
 
-    /// Input sample buffer (accumulate to frame_size)
+    /// Input sample buffer (accumulate to frame_size), holding samples
+    /// already in the encoder's target sample rate/channel layout
     sample_buffer: Vec<f32>,
 
     /// Frame counter for statistics
     frames_encoded: u64,
+
+    /// Declared format of samples passed to `encode`
+    input_format: InputFormat,
+
+    /// Resamples from `input_format` to the encoder's target format;
+    /// `None` when input and output formats already match
+    resampler: Option<ac_ffmpeg::codec::audio::resampler::AudioResampler>,
 }
 
 impl FfmpegAacEncoder {
-    /// Create a new FFmpeg AAC encoder
+    /// Create a new FFmpeg AAC encoder, assuming input PCM already matches
+    /// `config`'s sample rate/channels
     pub fn new(config: AacEncoderConfig) -> Result<Self> {
+        let input_format = InputFormat {
+            sample_rate: config.sample_rate,
+            channels: config.channels,
+            sample_fmt: ac_ffmpeg::format::sample::Type::F32,
+        };
+        Self::with_input_format(config, input_format)
+    }
+
+    /// Create a new FFmpeg AAC encoder that resamples from `input_format`
+    /// to `config`'s target sample rate/channels before buffering
+    ///
+    /// When `input_format` already matches the encoder's target, no
+    /// resampler is created and samples are passed through untouched.
+    pub fn with_input_format(mut config: AacEncoderConfig, input_format: InputFormat) -> Result<Self> {
         config.validate()?;
 
         debug!(
-            "Creating AAC-LC encoder: {}Hz, {} channels, {} bps, {:?}",
-            config.sample_rate, config.channels, config.bitrate, config.profile
+            "Creating AAC-LC encoder: {}Hz, {} channels, {} bps, {:?} (input: {}Hz, {} ch)",
+            config.sample_rate,
+            config.channels,
+            config.bitrate,
+            config.profile,
+            input_format.sample_rate,
+            input_format.channels
         );
 
         // Create codec parameters for AAC
@@ -293,16 +676,44 @@ impl FfmpegAacEncoder {
         let mut encoder = ac_ffmpeg::codec::Encoder::new(&params)
             .context("Failed to create AAC encoder")?;
 
-        // Set AAC-LC profile
+        // Set AAC profile (LC, or SBR-capable HE-AAC/HE-AACv2)
         encoder
             .set_option("profile", config.profile.ffmpeg_name())
             .context("Failed to set AAC profile")?;
 
+        if matches!(config.profile, AacProfile::HeAac | AacProfile::HeAacV2) {
+            // SBR reconstructs the upper spectrum from a half-rate core,
+            // doubling the apparent output sample rate
+            encoder
+                .set_option("aac_sbr_ratio", "2")
+                .context("Failed to enable SBR")?;
+        }
+
+        if config.profile == AacProfile::HeAacV2 {
+            // Parametric stereo folds the core down to mono and encodes
+            // the stereo image as side information
+            encoder
+                .set_option("aac_pns", "0") // PNS conflicts with PS signaling
+                .context("Failed to configure parametric stereo")?;
+            encoder
+                .set_option("ps", "1")
+                .context("Failed to enable parametric stereo")?;
+        }
+
         // Open encoder
         encoder
             .open(None)
             .context("Failed to open AAC encoder")?;
 
+        let resampler = if input_format.sample_rate != config.sample_rate
+            || input_format.channels != config.channels
+            || input_format.sample_fmt != ac_ffmpeg::format::sample::Type::F32
+        {
+            Some(Self::build_resampler(&config, &input_format)?)
+        } else {
+            None
+        };
+
         debug!("AAC-LC encoder initialized successfully");
 
         Ok(Self {
@@ -310,10 +721,103 @@ impl FfmpegAacEncoder {
             config,
             sample_buffer: Vec::with_capacity(2048),
             frames_encoded: 0,
+            input_format,
+            resampler,
         })
     }
 
-    /// Create audio frame from PCM samples
+    /// Build the swresample stage converting `input_format` to `config`'s
+    /// target sample rate/channels/format
+    fn build_resampler(
+        config: &AacEncoderConfig,
+        input_format: &InputFormat,
+    ) -> Result<ac_ffmpeg::codec::audio::resampler::AudioResampler> {
+        let source_layout = ac_ffmpeg::codec::audio::ChannelLayout::from_channels(
+            input_format.channels as u32,
+        )
+        .context("Unsupported input channel layout")?;
+        let target_layout =
+            ac_ffmpeg::codec::audio::ChannelLayout::from_channels(config.channels as u32)
+                .context("Unsupported output channel layout")?;
+
+        ac_ffmpeg::codec::audio::resampler::AudioResampler::builder()
+            .source_sample_format(input_format.sample_fmt)
+            .source_sample_rate(input_format.sample_rate as i32)
+            .source_channel_layout(source_layout)
+            .target_sample_format(ac_ffmpeg::format::sample::Type::F32)
+            .target_sample_rate(config.sample_rate as i32)
+            .target_channel_layout(target_layout)
+            .build()
+            .context("Failed to create input resampler")
+    }
+
+    /// Run PCM through the input resampler, if one is active
+    fn resample(&mut self, pcm: &[f32]) -> Result<Vec<f32>> {
+        let Some(resampler) = self.resampler.as_mut() else {
+            return Ok(pcm.to_vec());
+        };
+
+        let samples_per_channel = pcm.len() / self.input_format.channels as usize;
+        let mut frame = ac_ffmpeg::codec::audio::AudioFrame::new(
+            self.input_format.channels as i32,
+            self.input_format.sample_rate as i32,
+            self.input_format.sample_fmt,
+        )
+        .context("Failed to create input frame for resampling")?;
+        frame
+            .copy_from_interleaved(pcm)
+            .context("Failed to copy PCM into input frame")?;
+
+        resampler
+            .try_push(frame)
+            .context("Failed to push samples into input resampler")?;
+
+        let mut output = Vec::with_capacity(samples_per_channel * self.config.channels as usize);
+        while let Some(resampled) = resampler
+            .take()
+            .context("Failed to pull resampled frame")?
+        {
+            output.extend(Self::frame_to_interleaved(&resampled)?);
+        }
+
+        Ok(output)
+    }
+
+    /// Drain any samples still buffered inside the resampler (swr has
+    /// internal delay, so some input never surfaces until flushed)
+    fn flush_resampler(&mut self) -> Result<()> {
+        let Some(resampler) = self.resampler.as_mut() else {
+            return Ok(());
+        };
+
+        resampler.flush().context("Failed to flush input resampler")?;
+        while let Some(resampled) = resampler
+            .take()
+            .context("Failed to pull resampled frame during flush")?
+        {
+            let drained = Self::frame_to_interleaved(&resampled)?;
+            self.sample_buffer.extend(drained);
+        }
+
+        Ok(())
+    }
+
+    /// Convert a (post-resample) interleaved f32 audio frame back to a flat
+    /// PCM sample vector
+    fn frame_to_interleaved(frame: &ac_ffmpeg::codec::audio::AudioFrame) -> Result<Vec<f32>> {
+        let channels = frame.channel_layout().channels() as usize;
+        let total_samples = frame.samples() * channels;
+
+        let plane_bytes = frame.planes()[0].data();
+        let samples = unsafe {
+            std::slice::from_raw_parts(plane_bytes.as_ptr() as *const f32, total_samples)
+        };
+
+        Ok(samples.to_vec())
+    }
+
+    /// Create audio frame from PCM samples, already at the encoder's
+    /// target sample rate/channels
     fn create_audio_frame(&self, samples: &[f32]) -> Result<ac_ffmpeg::codec::audio::AudioFrame> {
         let samples_per_channel = samples.len() / self.config.channels as usize;
 
@@ -325,7 +829,8 @@ impl FfmpegAacEncoder {
         )
         .context("Failed to create audio frame")?;
 
-        // Set PTS (presentation timestamp)
+        // Set PTS (presentation timestamp), computed from output-rate
+        // sample counts so resampled input doesn't shift the timeline
         frame.set_pts(self.frames_encoded as i64 * 1024);
 
         // Copy PCM data into frame (interleaved)
@@ -352,17 +857,21 @@ impl FfmpegAacEncoder {
         loop {
             match self.encoder.receive_packet() {
                 Ok(packet) => {
-                    // Add ADTS header to packet
-                    let aac_with_adts = self.add_adts_header(&packet)?;
-                    output.extend(aac_with_adts);
+                    let framed = match self.config.mode {
+                        AacEncoderMode::AdtsFramed => self.add_adts_header(&packet)?,
+                        AacEncoderMode::RawWithAsc => packet.data().to_vec(),
+                    };
 
                     self.frames_encoded += 1;
 
                     trace!(
-                        "Encoded AAC frame: {} PCM samples â†’ {} bytes (with ADTS)",
+                        "Encoded AAC frame: {} PCM samples â†’ {} bytes ({:?})",
                         pcm.len(),
-                        aac_with_adts.len()
+                        framed.len(),
+                        self.config.mode
                     );
+
+                    output.extend(framed);
                 }
                 Err(ac_ffmpeg::Error::Again) => {
                     // No more packets available
@@ -382,10 +891,16 @@ impl FfmpegAacEncoder {
         let aac_data = packet.data();
         let aac_len = aac_data.len();
 
+        // SBR reconstructs the upper half of the spectrum from a half-rate
+        // core, so the ADTS header (which describes the core stream) must
+        // carry half the output sample rate; the full output rate is only
+        // ever signaled via the ASC's SBR extension (`audio_specific_config`).
+        let adts_sample_rate = core_sample_rate(self.config.profile, self.config.sample_rate);
+
         // Generate ADTS header
         let adts_header = AdtsHeader::generate(
             self.config.profile,
-            self.config.sample_rate,
+            adts_sample_rate,
             self.config.channels,
             aac_len, // AAC data length (header is added by AdtsHeader::generate)
         )?;
@@ -401,8 +916,10 @@ impl FfmpegAacEncoder {
 
 impl AacEncoder for FfmpegAacEncoder {
     fn encode(&mut self, pcm: &[f32]) -> Result<Vec<u8>> {
-        // Add samples to buffer
-        self.sample_buffer.extend_from_slice(pcm);
+        // Resample to the encoder's target rate/channels, if needed, then
+        // add the result to the buffer
+        let resampled = self.resample(pcm)?;
+        self.sample_buffer.extend_from_slice(&resampled);
 
         let mut output = Vec::new();
 
@@ -429,6 +946,10 @@ impl AacEncoder for FfmpegAacEncoder {
     fn flush(&mut self) -> Result<Vec<u8>> {
         let mut output = Vec::new();
 
+        // swresample holds internal delay; drain it into the sample buffer
+        // before padding/encoding the final frame
+        self.flush_resampler()?;
+
         // If there are remaining samples, pad and encode
         if !self.sample_buffer.is_empty() {
             let samples_per_frame = self.frame_size() * self.config.channels as usize;
@@ -462,6 +983,200 @@ impl AacEncoder for FfmpegAacEncoder {
     fn name(&self) -> &str {
         "AAC-LC (FFmpeg)"
     }
+
+    fn priming_delay(&self) -> u32 {
+        2112 // Typical AAC-LC encoder priming delay
+    }
+}
+
+/// FDK-AAC Encoder
+///
+/// Encodes PCM audio to AAC using the `fdk-aac` crate (Fraunhofer's
+/// reference AAC implementation), generally yielding higher quality than
+/// ac-ffmpeg's native encoder at a given bitrate - particularly for
+/// low-bitrate HE-AAC. Requests ADTS or raw+ASC framing directly from the
+/// library, sharing `AacEncoderConfig::audio_specific_config` with the
+/// FFmpeg path so callers don't need backend-specific ASC handling.
+///
+/// Gated behind the `fdk-aac` cargo feature, since it links the
+/// non-LGPL Fraunhofer FDK AAC library.
+#[cfg(feature = "fdk-aac")]
+pub struct FdkAacEncoder {
+    /// fdk-aac encoder handle
+    encoder: fdk_aac::enc::Encoder,
+
+    /// Encoder configuration
+    config: AacEncoderConfig,
+
+    /// Input sample buffer (accumulate to frame_size)
+    sample_buffer: Vec<f32>,
+
+    /// Frame counter for statistics
+    frames_encoded: u64,
+}
+
+#[cfg(feature = "fdk-aac")]
+impl FdkAacEncoder {
+    /// Create a new fdk-aac encoder
+    pub fn new(mut config: AacEncoderConfig) -> Result<Self> {
+        config.validate()?;
+
+        debug!(
+            "Creating fdk-aac encoder: {}Hz, {} channels, {} bps, {:?}",
+            config.sample_rate, config.channels, config.bitrate, config.profile
+        );
+
+        let channels = match config.channels {
+            1 => fdk_aac::enc::ChannelMode::Mono,
+            2 => fdk_aac::enc::ChannelMode::Stereo,
+            other => anyhow::bail!("fdk-aac encoder does not support {} channels", other),
+        };
+
+        let transport = match config.mode {
+            AacEncoderMode::AdtsFramed => fdk_aac::enc::Transport::Adts,
+            AacEncoderMode::RawWithAsc => fdk_aac::enc::Transport::Raw,
+        };
+
+        // AOT selection: 2 = AAC-LC, 5 = HE-AAC (SBR), 29 = HE-AACv2 (SBR + PS)
+        let audio_object_type = match config.profile {
+            AacProfile::AacLc => fdk_aac::enc::AudioObjectType::Mpeg4LowComplexity,
+            AacProfile::HeAac => fdk_aac::enc::AudioObjectType::Mpeg4HeAac,
+            AacProfile::HeAacV2 => fdk_aac::enc::AudioObjectType::Mpeg4HeAacV2,
+        };
+
+        let bit_rate = match config.vbr {
+            Some(quality) => fdk_aac::enc::BitRate::Vbr(quality),
+            None => fdk_aac::enc::BitRate::Cbr(config.bitrate),
+        };
+
+        let params = fdk_aac::enc::EncoderParams {
+            bit_rate,
+            sample_rate: config.sample_rate,
+            transport,
+            channels,
+            audio_object_type,
+            afterburner: config.afterburner,
+        };
+
+        let encoder = fdk_aac::enc::Encoder::new(params).context("Failed to create fdk-aac encoder")?;
+
+        debug!("fdk-aac encoder initialized successfully");
+
+        Ok(Self {
+            encoder,
+            config,
+            sample_buffer: Vec::with_capacity(2048),
+            frames_encoded: 0,
+        })
+    }
+
+    /// Encode one frame worth of PCM samples, converting from f32 to the
+    /// i16 PCM the `fdk-aac` crate expects
+    fn encode_frame(&mut self, pcm: &[f32]) -> Result<Vec<u8>> {
+        let pcm_i16: Vec<i16> = pcm
+            .iter()
+            .map(|sample| (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+            .collect();
+
+        // One 1024-sample ADTS frame comfortably fits; the encoder reports
+        // the actual byte count it wrote.
+        let mut output = vec![0u8; 4096];
+        let info = self
+            .encoder
+            .encode(&pcm_i16, &mut output)
+            .context("Failed to encode AAC frame via fdk-aac")?;
+        output.truncate(info.output_size);
+
+        self.frames_encoded += 1;
+
+        trace!(
+            "fdk-aac encoded frame: {} PCM samples → {} bytes ({:?})",
+            pcm.len(),
+            output.len(),
+            self.config.mode
+        );
+
+        Ok(output)
+    }
+}
+
+#[cfg(feature = "fdk-aac")]
+impl AacEncoder for FdkAacEncoder {
+    fn encode(&mut self, pcm: &[f32]) -> Result<Vec<u8>> {
+        self.sample_buffer.extend_from_slice(pcm);
+
+        let mut output = Vec::new();
+        let samples_per_frame = self.frame_size() * self.config.channels as usize;
+
+        while self.sample_buffer.len() >= samples_per_frame {
+            let frame_samples: Vec<f32> = self.sample_buffer.drain(..samples_per_frame).collect();
+            output.extend(self.encode_frame(&frame_samples)?);
+        }
+
+        Ok(output)
+    }
+
+    fn frame_size(&self) -> usize {
+        1024
+    }
+
+    fn flush(&mut self) -> Result<Vec<u8>> {
+        let mut output = Vec::new();
+
+        if !self.sample_buffer.is_empty() {
+            let samples_per_frame = self.frame_size() * self.config.channels as usize;
+            let remaining = samples_per_frame - self.sample_buffer.len();
+            self.sample_buffer.resize(samples_per_frame, 0.0);
+
+            let frame_samples: Vec<f32> = self.sample_buffer.drain(..).collect();
+            output.extend(self.encode_frame(&frame_samples)?);
+
+            warn!("Flushed fdk-aac encoder with {} padding samples", remaining);
+        }
+
+        debug!("fdk-aac encoder flushed, total frames encoded: {}", self.frames_encoded);
+
+        Ok(output)
+    }
+
+    fn config(&self) -> &AacEncoderConfig {
+        &self.config
+    }
+
+    fn reset(&mut self) {
+        self.sample_buffer.clear();
+        self.frames_encoded = 0;
+    }
+
+    fn name(&self) -> &str {
+        "AAC-LC (fdk-aac)"
+    }
+
+    fn priming_delay(&self) -> u32 {
+        2112 // fdk-aac reports the same standard AAC-LC priming delay
+    }
+}
+
+/// Build a concrete AAC encoder for the given backend
+///
+/// When the `fdk-aac` cargo feature is disabled, `EncoderBackend::Fdk`
+/// falls back to `FfmpegAacEncoder` rather than failing to build, so
+/// callers can select FDK opportunistically without feature-gating their
+/// own code.
+pub fn new_aac_encoder(
+    config: AacEncoderConfig,
+    backend: EncoderBackend,
+) -> Result<Box<dyn AacEncoder>> {
+    match backend {
+        EncoderBackend::Ffmpeg => Ok(Box::new(FfmpegAacEncoder::new(config)?)),
+        #[cfg(feature = "fdk-aac")]
+        EncoderBackend::Fdk => Ok(Box::new(FdkAacEncoder::new(config)?)),
+        #[cfg(not(feature = "fdk-aac"))]
+        EncoderBackend::Fdk => {
+            warn!("fdk-aac feature not enabled; falling back to the FFmpeg AAC encoder");
+            Ok(Box::new(FfmpegAacEncoder::new(config)?))
+        }
+    }
 }
 
 #[cfg(test)]
@@ -477,7 +1192,7 @@ mod tests {
 
     #[test]
     fn test_config_validation() {
-        let config = AacEncoderConfig::default();
+        let mut config = AacEncoderConfig::default();
         assert!(config.validate().is_ok());
 
         // Invalid sample rate
@@ -496,6 +1211,88 @@ mod tests {
         assert!(bad_config.validate().is_err());
     }
 
+    #[test]
+    fn test_he_aac_allows_lower_bitrate_floor() {
+        let mut config = AacEncoderConfig::default();
+        config.profile = AacProfile::HeAac;
+        config.bitrate = 24000;
+        assert!(config.validate().is_ok());
+
+        // Still too low even for HE-AAC
+        config.bitrate = 8000;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_he_aac_v2_rejects_non_stereo_input() {
+        let mut config = AacEncoderConfig::default();
+        config.profile = AacProfile::HeAacV2;
+        config.bitrate = 24000;
+        config.channels = 1;
+        assert!(config.validate().is_err());
+
+        config.channels = 2;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_zero_bitrate_resolves_to_auto() {
+        let mut config = AacEncoderConfig::default();
+        config.bitrate = 0;
+        assert!(config.validate().is_ok());
+        assert_eq!(config.bitrate, config.auto_bitrate());
+        assert!(config.bitrate > 0);
+    }
+
+    #[test]
+    fn test_auto_bitrate_scales_with_channels() {
+        let mut config = AacEncoderConfig::default();
+        config.channels = 1;
+        let mono = config.auto_bitrate();
+
+        config.channels = 2;
+        let stereo = config.auto_bitrate();
+        assert_eq!(stereo, mono);
+
+        config.channels = 6;
+        let surround = config.auto_bitrate();
+        assert_eq!(surround, mono * 3);
+    }
+
+    #[test]
+    fn test_excessive_bitrate_is_clamped_not_rejected() {
+        let mut config = AacEncoderConfig::default();
+        config.bitrate = 10_000_000;
+        assert!(config.validate().is_ok());
+        assert_eq!(config.bitrate, config.max_bitrate());
+    }
+
+    #[test]
+    fn test_core_sample_rate_halves_for_sbr_profiles() {
+        assert_eq!(core_sample_rate(AacProfile::AacLc, 48000), 48000);
+        assert_eq!(core_sample_rate(AacProfile::HeAac, 48000), 24000);
+        assert_eq!(core_sample_rate(AacProfile::HeAacV2, 48000), 24000);
+    }
+
+    #[test]
+    fn test_asc_he_aac_signals_half_rate_core() {
+        let config = AacEncoderConfig {
+            profile: AacProfile::HeAac,
+            sample_rate: 48000,
+            channels: 2,
+            bitrate: 32000,
+            ..Default::default()
+        };
+        let asc = config.audio_specific_config().unwrap();
+
+        // First 9 bits: 5-bit object type + 4-bit sampling frequency index
+        let core_index = ((asc[0] & 0x07) << 1) | (asc[1] >> 7);
+        assert_eq!(
+            core_index,
+            AdtsHeader::sample_rate_to_index(24000).unwrap()
+        );
+    }
+
     #[test]
     fn test_sample_rate_index() {
         assert_eq!(AdtsHeader::sample_rate_to_index(48000).unwrap(), 3);
@@ -516,6 +1313,41 @@ mod tests {
         assert_eq!(header[1] & 0xF0, 0xF0); // Sync word + MPEG-4
     }
 
+    #[test]
+    fn test_audio_specific_config_aac_lc() {
+        let config = AacEncoderConfig {
+            profile: AacProfile::AacLc,
+            sample_rate: 48000,
+            channels: 2,
+            ..Default::default()
+        };
+        let asc = config.audio_specific_config().unwrap();
+
+        // 5 bits object type (2) + 4 bits sample rate index (3) + 4 bits
+        // channels (2) + 3 bits GASpecificConfig (0) = 16 bits, 2 bytes
+        assert_eq!(asc.len(), 2);
+        assert_eq!(asc[0] >> 3, 2); // audioObjectType = AAC-LC
+    }
+
+    #[test]
+    fn test_audio_specific_config_he_aac_appends_sbr_signaling() {
+        let config = AacEncoderConfig {
+            profile: AacProfile::HeAac,
+            sample_rate: 48000,
+            channels: 2,
+            ..Default::default()
+        };
+        let asc = config.audio_specific_config().unwrap();
+        let lc_only = AacEncoderConfig {
+            profile: AacProfile::AacLc,
+            ..config.clone()
+        }
+        .audio_specific_config()
+        .unwrap();
+
+        assert!(asc.len() > lc_only.len());
+    }
+
     #[test]
     fn test_encoder_creation() {
         let config = AacEncoderConfig::default();
@@ -527,10 +1359,137 @@ mod tests {
         assert_eq!(encoder.frame_size(), 1024);
     }
 
+    #[test]
+    fn test_with_input_format_matching_target_skips_resampler() {
+        let config = AacEncoderConfig::default(); // 48000 Hz, 2 channels
+        let input_format = InputFormat {
+            sample_rate: config.sample_rate,
+            channels: config.channels,
+            sample_fmt: ac_ffmpeg::format::sample::Type::F32,
+        };
+        let encoder = FfmpegAacEncoder::with_input_format(config, input_format).unwrap();
+        assert!(encoder.resampler.is_none());
+    }
+
+    #[test]
+    fn test_with_input_format_mismatched_rate_builds_resampler() {
+        let config = AacEncoderConfig::default(); // 48000 Hz, 2 channels
+        let input_format = InputFormat {
+            sample_rate: 44100,
+            channels: 1,
+            sample_fmt: ac_ffmpeg::format::sample::Type::F32,
+        };
+        let encoder = FfmpegAacEncoder::with_input_format(config, input_format).unwrap();
+        assert!(encoder.resampler.is_some());
+    }
+
     #[test]
     fn test_encoder_frame_size() {
         let config = AacEncoderConfig::default();
         let encoder = FfmpegAacEncoder::new(config).unwrap();
         assert_eq!(encoder.frame_size(), 1024);
     }
+
+    #[test]
+    fn test_encoder_backend_default_is_ffmpeg() {
+        assert_eq!(EncoderBackend::default(), EncoderBackend::Ffmpeg);
+    }
+
+    #[test]
+    fn test_adts_parse_roundtrips_generate() {
+        let header = AdtsHeader::generate(AacProfile::AacLc, 48000, 2, 10).unwrap();
+        let mut frame = header.to_vec();
+        frame.extend(vec![0xAB; 10]);
+
+        let info = AdtsHeader::parse(&frame).unwrap();
+        assert_eq!(info.profile, 2); // AAC-LC
+        assert_eq!(info.sample_rate, 48000);
+        assert_eq!(info.channels, 2);
+        assert_eq!(info.payload_offset, 7);
+        assert_eq!(info.payload_len, 10);
+    }
+
+    #[test]
+    fn test_adts_reader_skips_garbage_and_yields_frames() {
+        let header = AdtsHeader::generate(AacProfile::AacLc, 48000, 2, 4).unwrap();
+        let mut frame = header.to_vec();
+        frame.extend(vec![0xAB; 4]);
+
+        let mut data = vec![0x00, 0x11, 0x22]; // garbage before the first frame
+        data.extend(&frame);
+        data.extend(&frame);
+
+        let mut reader = AdtsReader::new(&data);
+        let (first, payload) = reader.next().unwrap();
+        assert_eq!(first.payload_len, 4);
+        assert_eq!(payload, &frame[7..]);
+
+        let (second, _) = reader.next().unwrap();
+        assert_eq!(second.sample_rate, 48000);
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_adts_reader_stops_on_partial_trailing_frame() {
+        let header = AdtsHeader::generate(AacProfile::AacLc, 48000, 2, 4).unwrap();
+        let mut frame = header.to_vec();
+        frame.extend(vec![0xAB; 4]);
+        frame.truncate(frame.len() - 2); // trailing frame not fully buffered
+
+        let mut reader = AdtsReader::new(&frame);
+        assert!(reader.next().is_none());
+        assert_eq!(reader.remaining().len(), frame.len());
+    }
+
+    #[test]
+    #[cfg(feature = "fdk-aac")]
+    fn test_fdk_encoder_creation() {
+        let config = AacEncoderConfig::default();
+        let encoder = FdkAacEncoder::new(config);
+        assert!(encoder.is_ok());
+
+        let encoder = encoder.unwrap();
+        assert_eq!(encoder.name(), "AAC-LC (fdk-aac)");
+        assert_eq!(encoder.frame_size(), 1024);
+        assert_eq!(encoder.priming_delay(), 2112);
+    }
+
+    #[test]
+    fn test_new_aac_encoder_falls_back_to_ffmpeg_without_fdk_feature() {
+        let config = AacEncoderConfig::default();
+        let encoder = new_aac_encoder(config, EncoderBackend::Fdk).unwrap();
+        let expected = if cfg!(feature = "fdk-aac") { "AAC-LC (fdk-aac)" } else { "AAC-LC (FFmpeg)" };
+        assert_eq!(encoder.name(), expected);
+    }
+
+    #[test]
+    fn test_round_trip_sine_sweep_produces_valid_adts_frames() {
+        let config = AacEncoderConfig::default(); // 48000 Hz, 2 ch, ADTS mode
+        let mut encoder = new_aac_encoder(config.clone(), EncoderBackend::Ffmpeg).unwrap();
+
+        // One second of a 440Hz -> 880Hz sine sweep, interleaved stereo
+        let total_samples = config.sample_rate as usize;
+        let mut pcm = Vec::with_capacity(total_samples * config.channels as usize);
+        for i in 0..total_samples {
+            let t = i as f32 / config.sample_rate as f32;
+            let freq = 440.0 + 440.0 * t;
+            let sample = (2.0 * std::f32::consts::PI * freq * t).sin() * 0.5;
+            for _ in 0..config.channels {
+                pcm.push(sample);
+            }
+        }
+
+        let mut encoded = encoder.encode(&pcm).unwrap();
+        encoded.extend(encoder.flush().unwrap());
+
+        let expected_frames = total_samples / encoder.frame_size();
+        let frames: Vec<_> = AdtsReader::new(&encoded).collect();
+        assert!(frames.len() >= expected_frames);
+
+        for (info, payload) in &frames {
+            assert_eq!(info.sample_rate, 48000);
+            assert_eq!(info.channels, 2);
+            assert!(!payload.is_empty());
+        }
+    }
 }
@@ -0,0 +1,256 @@
+//! Single-flight fan-out for concurrent transcode requests
+//!
+//! When several clients request the same source at the same encode
+//! settings at once (e.g. the same live channel), this coalesces them onto
+//! one `VaapiTranscoder` instance instead of spawning one FFmpeg/VAAPI
+//! pipeline per client. The first request starts the transcode and pushes
+//! its output chunks into a `tokio::sync::broadcast` channel; concurrent and
+//! later requests for the same [`TranscodeKey`] attach as additional
+//! receivers on the same channel. Once the last subscriber drops, the
+//! `Weak<Broadcast>` registry entry can no longer be upgraded, the driver
+//! task stops pumping, and the underlying FFmpeg child is terminated the
+//! same way a direct (non-shared) stream's cancellation is.
+//!
+//! A late joiner starts receiving from whatever TS packet the producer is
+//! currently on rather than from the beginning; this is fine for live
+//! streams since MPEG-TS is resync-capable. A slow subscriber that can't
+//! keep up has its oldest unread chunks dropped (the broadcast channel's
+//! built-in overflow behavior) rather than stalling the shared producer.
+
+use anyhow::Result;
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::{Arc, Weak};
+use std::task::{Context, Poll};
+use tokio::sync::{broadcast, Mutex};
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, warn};
+
+use crate::live_sessions::LiveSessionsRegistry;
+use crate::metrics;
+use crate::transcoder::{TranscoderConfig, VaapiTranscoder};
+
+/// Per-subscriber channel capacity; bounds how far a slow subscriber may lag
+/// the producer before its oldest unread chunks are dropped.
+const BROADCAST_CAPACITY: usize = 64;
+
+/// Coalescing key: requests that share a key attach to the same encode
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TranscodeKey {
+    source_url: String,
+    video_codec: String,
+    video_bitrate: String,
+    audio_codec: String,
+    audio_bitrate: String,
+    hwaccel: crate::hwaccel::HwAccel,
+}
+
+impl TranscodeKey {
+    /// Build a key from the source URL and the effective encode settings;
+    /// two requests with the same key are served by the same shared encode.
+    ///
+    /// `hwaccel` is part of the key alongside the codec/bitrate settings -
+    /// two requests for the same source on different backends produce
+    /// different output and must not be coalesced onto one encode.
+    pub fn new(source_url: &str, config: &TranscoderConfig) -> Self {
+        Self {
+            source_url: source_url.to_string(),
+            video_codec: config.video_codec.clone(),
+            video_bitrate: config.video_bitrate.clone(),
+            audio_codec: config.audio_codec.clone(),
+            audio_bitrate: config.audio_bitrate.clone(),
+            hwaccel: config.hwaccel,
+        }
+    }
+}
+
+/// Shared live-transcode output, coalescing subscribers onto one encode
+struct Broadcast {
+    sender: broadcast::Sender<Bytes>,
+    /// Child of the server's shutdown token; cancelled either when the
+    /// server shuts down or when the driver task notices it has no
+    /// remaining subscribers, to terminate the FFmpeg child promptly.
+    shutdown: CancellationToken,
+}
+
+/// Registry of in-flight shared transcodes, keyed by [`TranscodeKey`]
+///
+/// Entries are `Weak` so a shared transcode is only kept alive by the
+/// subscribers actually streaming it; once the last one drops, the entry
+/// evicts itself.
+pub struct FanoutRegistry {
+    entries: Arc<Mutex<HashMap<TranscodeKey, Weak<Broadcast>>>>,
+}
+
+impl FanoutRegistry {
+    pub fn new() -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Attach to the shared transcode for `key`, starting one if none is
+    /// currently running
+    ///
+    /// The lock is held across FFmpeg startup for a new key so two
+    /// concurrent first-requests for the same key can't race into starting
+    /// two encodes; this is a small, bounded critical section (one spawn
+    /// call), not a hot path, so serializing it against other keys too is an
+    /// acceptable trade for simplicity.
+    pub async fn subscribe(
+        &self,
+        key: TranscodeKey,
+        transcoder: VaapiTranscoder,
+        server_shutdown: CancellationToken,
+        live_sessions: &LiveSessionsRegistry,
+    ) -> Result<impl Stream<Item = Result<Bytes, std::io::Error>> + Send + 'static> {
+        let mut entries = self.entries.lock().await;
+
+        if let Some(shared) = entries.get(&key).and_then(Weak::upgrade) {
+            debug!("fan-out: attaching to existing shared transcode for {}", key.source_url);
+            let receiver = shared.sender.subscribe();
+            return Ok(subscriber_stream(shared, receiver));
+        }
+
+        debug!("fan-out: starting new shared transcode for {}", key.source_url);
+        let source_url = key.source_url.clone();
+        let shutdown = server_shutdown.child_token();
+
+        // One live session per shared producer (not per subscriber) - every
+        // viewer attached to this key rides the same FFmpeg process, so
+        // they share one set of progress stats and one stall detector too.
+        let session_id = live_sessions
+            .register(source_url.clone(), shutdown.clone())
+            .await;
+        let live_sessions_for_progress = live_sessions.clone();
+        let on_progress = move |update: crate::transcoder::ProgressUpdate| {
+            let live_sessions = live_sessions_for_progress.clone();
+            let id_str = session_id.to_string();
+            metrics::set_session_progress(&id_str, update.speed, update.bitrate_kbps, update.frame);
+            tokio::spawn(async move {
+                live_sessions.record_progress(session_id, update).await;
+            });
+        };
+
+        let output = transcoder
+            .transcode_stream(&source_url, shutdown.clone(), on_progress)
+            .await?;
+
+        let (sender, receiver) = broadcast::channel(BROADCAST_CAPACITY);
+        let shared = Arc::new(Broadcast { sender, shutdown: shutdown.clone() });
+        entries.insert(key.clone(), Arc::downgrade(&shared));
+        drop(entries);
+
+        let weak = Arc::downgrade(&shared);
+        let entries_for_cleanup = self.entries.clone();
+        let cleanup_key = key.clone();
+        let live_sessions_for_cleanup = live_sessions.clone();
+        tokio::spawn(async move {
+            pump_shared_transcode(weak, output, &source_url, shutdown).await;
+            entries_for_cleanup.lock().await.remove(&cleanup_key);
+            live_sessions_for_cleanup.deregister(session_id).await;
+        });
+
+        Ok(subscriber_stream(shared, receiver))
+    }
+}
+
+impl Default for FanoutRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Drive one shared transcode, forwarding its output to every subscriber
+///
+/// Stops (and triggers `shutdown`, terminating the FFmpeg child) once
+/// `weak` can no longer be upgraded, meaning every subscriber has dropped.
+async fn pump_shared_transcode(
+    weak: Weak<Broadcast>,
+    mut output: Pin<Box<dyn Stream<Item = Result<Bytes, std::io::Error>> + Send>>,
+    source_url: &str,
+    shutdown: CancellationToken,
+) {
+    loop {
+        if weak.upgrade().is_none() {
+            debug!("fan-out: last subscriber for {} gone, stopping shared transcode", source_url);
+            break;
+        }
+
+        tokio::select! {
+            _ = shutdown.cancelled() => {
+                debug!("fan-out: shutdown signalled for {}", source_url);
+                break;
+            }
+            chunk = output.next() => {
+                match chunk {
+                    Some(Ok(bytes)) => {
+                        if let Some(shared) = weak.upgrade() {
+                            // Err just means no receivers are currently
+                            // attached; the next loop iteration's
+                            // weak-upgrade check notices and stops us.
+                            let _ = shared.sender.send(bytes);
+                        }
+                    }
+                    Some(Err(e)) => {
+                        error!("fan-out: shared transcode stream error for {}: {}", source_url, e);
+                        break;
+                    }
+                    None => {
+                        debug!("fan-out: shared transcode for {} ended", source_url);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    shutdown.cancel();
+}
+
+/// Per-subscriber stream: a `broadcast::Receiver` plus the shared state it
+/// must keep alive for as long as it's being read
+///
+/// Holding `_shared` here (rather than re-deriving it per poll) is what
+/// keeps the shared transcode's `Weak` upgradeable for the subscriber's
+/// whole lifetime, not just while it's actively being polled.
+struct SubscriberStream {
+    _shared: Arc<Broadcast>,
+    inner: BroadcastStream<Bytes>,
+}
+
+impl Stream for SubscriberStream {
+    type Item = Result<Bytes, std::io::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            return match Pin::new(&mut self.inner).poll_next(cx) {
+                Poll::Ready(Some(Ok(bytes))) => Poll::Ready(Some(Ok(bytes))),
+                // A lagged subscriber has its oldest unread chunks dropped
+                // (the broadcast channel's built-in overflow behavior)
+                // rather than ending its stream; it simply resumes from
+                // wherever the producer currently is.
+                Poll::Ready(Some(Err(BroadcastStreamRecvError::Lagged(skipped)))) => {
+                    warn!("fan-out subscriber lagged, dropped {} chunk(s)", skipped);
+                    continue;
+                }
+                Poll::Ready(None) => Poll::Ready(None),
+                Poll::Pending => Poll::Pending,
+            };
+        }
+    }
+}
+
+fn subscriber_stream(
+    shared: Arc<Broadcast>,
+    receiver: broadcast::Receiver<Bytes>,
+) -> SubscriberStream {
+    SubscriberStream {
+        _shared: shared,
+        inner: BroadcastStream::new(receiver),
+    }
+}
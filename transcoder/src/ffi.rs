@@ -577,20 +577,51 @@ async fn run_gpu_server(
 
     tracing::info!("GPU server initializing...");
 
-    // Check VAAPI availability
-    let vaapi_available = crate::server::check_vaapi().await;
-    if !vaapi_available {
-        tracing::warn!("VAAPI not available - GPU transcoding will not work!");
+    // Load configuration
+    let mut config = TranscoderConfig::from_env();
+
+    // Probe which backends this host's FFmpeg build can actually use; pick
+    // the best one as the default unless the operator pinned one via
+    // HWACCEL (see the equivalent startup logic in main.rs).
+    let available_hwaccels = crate::hwaccel::probe_available(&config.ffmpeg_path).await;
+    if std::env::var("HWACCEL").is_err() {
+        if let Some(&best) = available_hwaccels.first() {
+            config.hwaccel = best;
+        }
+    }
+    let vaapi_available = available_hwaccels.contains(&crate::hwaccel::HwAccel::Vaapi);
+    if config.hwaccel == crate::hwaccel::HwAccel::Software {
+        tracing::warn!("No GPU backend detected - falling back to software encoding");
+    } else {
+        tracing::info!("Using {:?} hardware acceleration", config.hwaccel);
     }
 
-    // Load configuration
-    let config = TranscoderConfig::from_env();
     let metrics_handle = crate::metrics::init_metrics();
 
+    use std::sync::atomic::AtomicUsize;
+    use tokio_util::sync::CancellationToken;
+
+    let shutdown = CancellationToken::new();
+    let active_streams = Arc::new(AtomicUsize::new(0));
+    let job_ttl_secs = config.job_ttl_secs;
+    let hls_idle_timeout_secs = config.hls_idle_timeout_secs;
+    let stream_stall_timeout_secs = config.stream_stall_timeout_secs;
+
     let app_state = Arc::new(crate::server::AppState {
         config,
         vaapi_available,
+        available_hwaccels,
         metrics_handle,
+        shutdown: shutdown.clone(),
+        active_streams,
+        fanout: crate::fanout::FanoutRegistry::new(),
+        jobs: crate::jobs::JobsRegistry::new(std::time::Duration::from_secs(job_ttl_secs)),
+        hls: crate::hls::SessionManager::new(std::time::Duration::from_secs(
+            hls_idle_timeout_secs,
+        )),
+        live_sessions: crate::live_sessions::LiveSessionsRegistry::new(
+            std::time::Duration::from_secs(stream_stall_timeout_secs),
+        ),
     });
 
     // Build router (same as main.rs)
@@ -610,6 +641,7 @@ async fn run_gpu_server(
     axum::serve(listener, app)
         .with_graceful_shutdown(async move {
             let _ = shutdown_rx.await;
+            shutdown.cancel();
             tracing::info!("GPU server shutdown signal received");
         })
         .await?;
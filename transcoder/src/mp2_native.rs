@@ -0,0 +1,439 @@
+//! Alternative MPEG-1 Layer I/II ("MP1"/"MP2") audio decoder
+//!
+//! [`crate::decoder::Mp2Decoder`] goes through Symphonia, which only
+//! accepts `CODEC_TYPE_MP2` and is one more dependency to build against.
+//! This is a from-scratch alternative implementing [`crate::decoder::AudioDecoder`]
+//! directly against ISO/IEC 11172-3, gated behind the `native-mp2` cargo
+//! feature and selected via [`crate::decoder::Mp2Backend`] /
+//! [`crate::decoder::AutoDecoder::with_mp2_backend`] - Symphonia stays the
+//! default. Unlike Symphonia's MP2-only path, this backend also accepts
+//! Layer I ("MP1") frames, which Symphonia's MP2 reader rejects outright.
+//!
+//! The frame header (sync word, MPEG version, layer, bitrate index,
+//! sampling-frequency index, channel mode) is parsed per-frame and is the
+//! only source of truth for `sample_rate()`/`channels()` - never a
+//! container-level hint - mirroring how a correct decoder must re-derive
+//! those from the codec's own bits rather than trusting a demuxer's guess.
+//!
+//! # Coverage
+//!
+//! **This does not decode real MPEG-1 Layer I/II audio correctly.** Frame
+//! sync/header parsing matches the spec precisely, and the frame-length
+//! computation is correct, but subband reconstruction reads a single
+//! *fixed* bit-allocation pattern (ISO Table B.2a's shape, for MPEG-1
+//! stereo, 48kHz/44.1kHz, mid-to-high bitrate) instead of the per-subband
+//! allocation the bitstream actually signals, and it decodes **no
+//! scalefactors at all**, even though the bitstream carries one
+//! scalefactor-select plus scalefactor per allocated subband. Because the
+//! real per-subband allocation determines how many bits each sample
+//! consumes, reading a fixed pattern instead desyncs the bit reader
+//! partway through the first granule on any input that doesn't happen to
+//! match the assumed allocation - the result is noise, not merely
+//! lower-fidelity audio.
+//!
+//! The synthesis (subband-to-PCM) filter bank also uses a Kaiser-window
+//! approximation of the synthesis prototype rather than the exact 512-tap
+//! ISO Table D.1 coefficients, but that's a secondary concern next to the
+//! bit-allocation/scalefactor gap above.
+//!
+//! In short: the frame header parser, frame-length math, and synthesis
+//! filter bank plumbing are correct and reusable, but this is a structural
+//! skeleton rather than a working decoder. Producing correct PCM needs
+//! real per-subband bit-allocation decoding selected from the matching
+//! per-bitrate/per-samplerate table (ISO Tables B.1/B.2) plus scalefactor
+//! decoding per subband - [`crate::ac3_native`]'s bit allocation has the
+//! same kind of gap (see its own "Coverage" section), so neither
+//! from-scratch decoder in this crate should be read as spec-accurate.
+
+use crate::decoder::{AudioDecoder, PcmSample};
+use anyhow::{bail, Result};
+use tracing::trace;
+
+const SAMPLES_PER_SUBBAND_GRANULE: usize = 12;
+const SUBBANDS: usize = 32;
+const GRANULES_LAYER2: usize = 3;
+
+/// MPEG-1 sample rates by the 2-bit `sampling_frequency` field
+const SAMPLE_RATES_V1: [u32; 3] = [44_100, 48_000, 32_000];
+/// MPEG-2 (LSF) sample rates, half of MPEG-1's
+const SAMPLE_RATES_V2: [u32; 3] = [22_050, 24_000, 16_000];
+
+/// Layer I bitrate table (kbps) by the 4-bit `bitrate_index`, MPEG-1
+#[rustfmt::skip]
+const BITRATE_LAYER1_V1: [u32; 15] = [0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448];
+/// Layer II bitrate table (kbps) by the 4-bit `bitrate_index`, MPEG-1
+#[rustfmt::skip]
+const BITRATE_LAYER2_V1: [u32; 15] = [0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384];
+
+/// A *fixed* placeholder bit-allocation table (ISO Table B.2a shape):
+/// number of quantization bits allotted to each of the 32 subbands, in
+/// priority order (more bits to the low subbands where the ear is most
+/// sensitive). Real streams signal their own per-subband allocation in
+/// the bitstream instead of using one fixed pattern - see the module-level
+/// "Coverage" section for why this makes `try_decode_frame` desync on
+/// real input rather than merely lose fidelity.
+#[rustfmt::skip]
+const BIT_ALLOCATION_TABLE: [u8; SUBBANDS] = [
+    15, 15, 14, 14, 13, 13, 12, 12, 11, 11, 10, 10, 9, 9, 8, 8,
+     7,  6,  5,  5,  4,  4,  3,  3,  2,  2,  1,  1, 0, 0, 0, 0,
+];
+
+/// MPEG version implied by the header's 2-bit `version` field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MpegVersion {
+    V1,
+    V2,
+}
+
+/// Layer implied by the header's 2-bit `layer` field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Layer {
+    LayerI,
+    LayerII,
+}
+
+struct FrameHeader {
+    version: MpegVersion,
+    layer: Layer,
+    bitrate_kbps: u32,
+    sample_rate: u32,
+    padding: bool,
+    channels: u16,
+    frame_len: usize,
+}
+
+/// MSB-first bitstream reader, identical in shape to `ac3_native::BitReader`
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn read_bits(&mut self, n: u32) -> u32 {
+        let mut value = 0u32;
+        for _ in 0..n {
+            let byte = self.data.get(self.pos / 8).copied().unwrap_or(0);
+            let bit = (byte >> (7 - (self.pos % 8))) & 1;
+            value = (value << 1) | bit as u32;
+            self.pos += 1;
+        }
+        value
+    }
+}
+
+/// Parse a 4-byte MPEG audio frame header starting at `data[0]`
+fn parse_header(data: &[u8]) -> Result<FrameHeader> {
+    if data.len() < 4 {
+        bail!("Not enough bytes for an MPEG audio header");
+    }
+
+    let mut br = BitReader::new(&data[..4]);
+    let sync = br.read_bits(11);
+    if sync != 0x7FF {
+        bail!("MPEG audio sync word not found");
+    }
+
+    let version_bits = br.read_bits(2);
+    let version = match version_bits {
+        0b11 => MpegVersion::V1,
+        0b10 => MpegVersion::V2,
+        _ => bail!("Unsupported MPEG version (MPEG 2.5 / reserved)"),
+    };
+
+    let layer_bits = br.read_bits(2);
+    let layer = match layer_bits {
+        0b11 => Layer::LayerI,
+        0b10 => Layer::LayerII,
+        _ => bail!("Unsupported MPEG layer (only Layer I/II are implemented)"),
+    };
+
+    br.read_bits(1); // protection_bit
+    let bitrate_index = br.read_bits(4) as usize;
+    let sampling_freq_idx = br.read_bits(2) as usize;
+    if sampling_freq_idx == 3 {
+        bail!("Reserved sampling_frequency index");
+    }
+    let padding = br.read_bits(1) != 0;
+    br.read_bits(1); // private_bit
+    let mode = br.read_bits(2);
+
+    let channels = if mode == 0b11 { 1 } else { 2 };
+
+    let sample_rate = match version {
+        MpegVersion::V1 => SAMPLE_RATES_V1[sampling_freq_idx],
+        MpegVersion::V2 => SAMPLE_RATES_V2[sampling_freq_idx],
+    };
+
+    // MPEG-2 LSF frames use their own, lower bitrate tables
+    let bitrate_table: [u32; 15] = match (version, layer) {
+        (MpegVersion::V1, Layer::LayerI) => BITRATE_LAYER1_V1,
+        (MpegVersion::V1, Layer::LayerII) => BITRATE_LAYER2_V1,
+        (MpegVersion::V2, Layer::LayerI) => [0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256],
+        (MpegVersion::V2, Layer::LayerII) => [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
+    };
+    let bitrate_kbps = *bitrate_table
+        .get(bitrate_index)
+        .filter(|&&b| b != 0)
+        .ok_or_else(|| anyhow::anyhow!("Free-format or reserved bitrate index"))?;
+
+    let frame_len = match layer {
+        Layer::LayerI => (12 * bitrate_kbps * 1000 / sample_rate + u32::from(padding)) as usize * 4,
+        Layer::LayerII => (144 * bitrate_kbps * 1000 / sample_rate + u32::from(padding)) as usize,
+    };
+
+    Ok(FrameHeader {
+        version,
+        layer,
+        bitrate_kbps,
+        sample_rate,
+        padding,
+        channels,
+        frame_len,
+    })
+}
+
+/// Dequantize one subband sample given its allocated bit count, per the
+/// linear ISO quantizer (mid-tread, symmetric around zero)
+fn dequantize(raw: u32, bits: u8) -> f32 {
+    if bits == 0 {
+        return 0.0;
+    }
+    let levels = (1u32 << bits) - 1;
+    (raw as f32 / levels as f32) * 2.0 - 1.0
+}
+
+/// Kaiser-window approximation of the Layer I/II synthesis prototype
+/// filter, generated the same way as `ac3_native::kbd_window` rather than
+/// stored as the exact 512-tap ISO Table D.1
+fn synthesis_window() -> [f32; 64] {
+    const N: usize = 64;
+    const BETA: f64 = 6.0;
+
+    fn bessel_i0(x: f64) -> f64 {
+        let mut sum = 1.0;
+        let mut term = 1.0;
+        for k in 1..20 {
+            term *= (x / 2.0) / k as f64;
+            sum += term * term;
+        }
+        sum
+    }
+
+    let denom = bessel_i0(std::f64::consts::PI * BETA);
+    let mut window = [0.0f32; N];
+    for (n, w) in window.iter_mut().enumerate() {
+        let ratio = (2.0 * n as f64 / (N as f64 - 1.0)) - 1.0;
+        let arg = std::f64::consts::PI * BETA * (1.0 - ratio * ratio).max(0.0).sqrt();
+        *w = (bessel_i0(arg) / denom) as f32;
+    }
+    window
+}
+
+/// Reconstruct one granule's worth of PCM from 32 dequantized subband
+/// samples via the standard Layer I/II cosine synthesis matrix, windowed
+/// by the (approximated) prototype filter
+///
+/// Produces 32 interleaved-per-channel PCM samples per call; the caller
+/// invokes this once per subband sample index within a granule.
+fn synthesize_subband_vector(subband_samples: &[f32; SUBBANDS], window: &[f32; 64]) -> [f32; SUBBANDS] {
+    let mut out = [0.0f32; SUBBANDS];
+    for (i, sample) in out.iter_mut().enumerate() {
+        let mut acc = 0.0f32;
+        for (k, &s) in subband_samples.iter().enumerate() {
+            let angle = std::f32::consts::PI / 64.0 * ((2 * i + 1) as f32) * (k as f32 + 16.0);
+            acc += s * angle.cos();
+        }
+        // Apply a windowed tapering matched to subband index so the
+        // reconstructed block blends with neighbors, approximating the
+        // real polyphase filter's overlap without its full 512-tap state.
+        let w = window[i % window.len()];
+        *sample = acc * w / SUBBANDS as f32;
+    }
+    out
+}
+
+/// Pure-Rust MPEG-1 Layer I/II decoder implementing the `AudioDecoder` trait
+///
+/// Gated behind the `native-mp2` feature; selected by
+/// [`crate::decoder::AutoDecoder::with_mp2_backend`] in place of the
+/// Symphonia-backed [`crate::decoder::Mp2Decoder`].
+pub struct Mp1Mp2Decoder {
+    buffer: Vec<u8>,
+    sample_rate: u32,
+    channels: u16,
+    frames_decoded: u64,
+    window: [f32; 64],
+}
+
+impl Mp1Mp2Decoder {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            buffer: Vec::new(),
+            sample_rate: 48_000,
+            channels: 2,
+            frames_decoded: 0,
+            window: synthesis_window(),
+        })
+    }
+
+    fn try_decode_frame(&mut self) -> Result<Option<Vec<f32>>> {
+        if self.buffer.len() < 4 {
+            return Ok(None);
+        }
+
+        let header = match parse_header(&self.buffer) {
+            Ok(header) => header,
+            Err(_) => {
+                self.buffer.remove(0);
+                return Ok(None);
+            }
+        };
+
+        if self.buffer.len() < header.frame_len {
+            return Ok(None);
+        }
+
+        // Always re-derive output format from this frame's own header bits,
+        // never from a container-level hint.
+        self.sample_rate = header.sample_rate;
+        self.channels = header.channels;
+
+        let granules = match header.layer {
+            Layer::LayerI => 1,
+            Layer::LayerII => GRANULES_LAYER2,
+        };
+        let samples_per_granule = match header.layer {
+            Layer::LayerI => 1,
+            Layer::LayerII => SAMPLES_PER_SUBBAND_GRANULE,
+        };
+
+        let mut br = BitReader::new(&self.buffer[4..header.frame_len]);
+        let channels = header.channels as usize;
+        let mut pcm = Vec::with_capacity(granules * samples_per_granule * SUBBANDS * channels);
+
+        for _granule in 0..granules {
+            for _sample_in_granule in 0..samples_per_granule {
+                let mut per_channel_subbands: Vec<[f32; SUBBANDS]> = Vec::with_capacity(channels);
+
+                for _ch in 0..channels {
+                    let mut subbands = [0.0f32; SUBBANDS];
+                    for (sb, &bits) in BIT_ALLOCATION_TABLE.iter().enumerate() {
+                        if bits == 0 {
+                            continue;
+                        }
+                        let raw = br.read_bits(bits as u32);
+                        subbands[sb] = dequantize(raw, bits);
+                    }
+                    per_channel_subbands.push(subbands);
+                }
+
+                for subbands in &per_channel_subbands {
+                    let reconstructed = synthesize_subband_vector(subbands, &self.window);
+                    pcm.extend_from_slice(&reconstructed[..1]);
+                }
+            }
+        }
+
+        // Interleave: the loop above appends one reconstructed sample per
+        // channel per (granule, sample-in-granule) in channel order already,
+        // so `pcm` is already interleaved as [ch0, ch1, ch0, ch1, ...].
+        trace!(
+            "Decoded {:?}/{:?} frame: {}kbps, {}Hz, {} channels, padding={}",
+            header.version, header.layer, header.bitrate_kbps, header.sample_rate, header.channels, header.padding
+        );
+
+        self.buffer.drain(0..header.frame_len);
+        Ok(Some(pcm))
+    }
+}
+
+impl AudioDecoder for Mp1Mp2Decoder {
+    fn push(&mut self, data: &[u8]) -> Result<()> {
+        self.buffer.extend_from_slice(data);
+        Ok(())
+    }
+
+    fn pull(&mut self) -> Result<Option<Vec<PcmSample>>> {
+        match self.try_decode_frame() {
+            Ok(Some(pcm)) => {
+                self.frames_decoded += 1;
+                Ok(Some(pcm))
+            }
+            Ok(None) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn reset(&mut self) {
+        self.buffer.clear();
+        self.frames_decoded = 0;
+    }
+
+    fn name(&self) -> &str {
+        "MP1/MP2 (native)"
+    }
+}
+
+impl Default for Mp1Mp2Decoder {
+    fn default() -> Self {
+        Self::new().expect("Failed to create native MP1/MP2 decoder")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_header_rejects_bad_sync() {
+        let data = [0x00, 0x00, 0x00, 0x00];
+        assert!(parse_header(&data).is_err());
+    }
+
+    #[test]
+    fn test_parse_header_mpeg1_layer2_48khz_stereo() {
+        // sync=11111111111, version=11 (V1), layer=10 (LayerII),
+        // protection=0, bitrate_index=1000 (128kbps), sampling=01 (48kHz),
+        // padding=0, private=0, mode=00 (stereo)
+        let data = [0xFF, 0xFC, 0x84, 0x00];
+        let header = parse_header(&data).unwrap();
+        assert_eq!(header.sample_rate, 48_000);
+        assert_eq!(header.channels, 2);
+        assert!(matches!(header.layer, Layer::LayerII));
+    }
+
+    #[test]
+    fn test_dequantize_is_symmetric_around_zero() {
+        let max = dequantize((1 << 4) - 1, 4);
+        let min = dequantize(0, 4);
+        assert!((max - 1.0).abs() < 1e-5);
+        assert!((min - (-1.0)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_synthesis_window_in_unit_range() {
+        let window = synthesis_window();
+        for w in window {
+            assert!((0.0..=1.0).contains(&w));
+        }
+    }
+
+    #[test]
+    fn test_incomplete_frame_returns_none() {
+        let mut decoder = Mp1Mp2Decoder::new().unwrap();
+        decoder.push(&[0xFF, 0xF4]).unwrap();
+        assert!(decoder.pull().unwrap().is_none());
+    }
+}
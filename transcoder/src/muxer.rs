@@ -34,11 +34,43 @@
 //! }
 //! ```
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use tracing::{debug, trace};
 
 use crate::demux::TS_PACKET_SIZE;
 
+/// Audio elementary stream framing emitted by the muxer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamFormat {
+    /// ADTS-framed AAC (stream_type 0x0F)
+    Adts,
+    /// LOAS/LATM-framed AAC (stream_type 0x11, MP4A-LATM)
+    Latm,
+}
+
+impl Default for StreamFormat {
+    fn default() -> Self {
+        Self::Adts
+    }
+}
+
+/// A single elementary stream entry carried in the PMT
+///
+/// `descriptors` holds the raw ES-info descriptor bytes for this stream
+/// (e.g. a registration descriptor for AC-3, or a LATM config descriptor);
+/// an empty vec means no descriptors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElementaryStream {
+    /// PID carrying this stream
+    pub pid: u16,
+
+    /// PMT stream_type (e.g. 0x1B for H.264, 0x0F for ADTS-AAC, 0x81 for AC-3)
+    pub stream_type: u8,
+
+    /// Raw ES-info descriptor bytes
+    pub descriptors: Vec<u8>,
+}
+
 /// MPEG-TS Muxer Configuration
 #[derive(Debug, Clone)]
 pub struct TsMuxerConfig {
@@ -59,6 +91,16 @@ pub struct TsMuxerConfig {
 
     /// Program Number
     pub program_number: u16,
+
+    /// Audio elementary stream framing (ADTS or LOAS/LATM)
+    pub stream_format: StreamFormat,
+
+    /// Explicit elementary stream list to advertise in the PMT. Leave empty
+    /// to fall back to the implicit video (H.264) + audio (`stream_format`)
+    /// pair derived from `video_pid`/`audio_pid`; set it to mux AC-3,
+    /// MPEG-1/2 audio, multiple audio tracks, or an audio-only program
+    /// (video entry omitted, `pcr_pid` pointed at `audio_pid`).
+    pub elementary_streams: Vec<ElementaryStream>,
 }
 
 impl Default for TsMuxerConfig {
@@ -70,8 +112,304 @@ impl Default for TsMuxerConfig {
             pmt_pid: 0x1000,        // 4096
             transport_stream_id: 1,
             program_number: 1,
+            stream_format: StreamFormat::Adts,
+            elementary_streams: Vec::new(),
+        }
+    }
+}
+
+/// Derive a `TsMuxerConfig` by probing an input transport stream's PAT/PMT
+///
+/// Scans up to `probe_limit` bytes of `data` (which need not be aligned to
+/// packet boundaries; sync loss and `transport_error_indicator` are
+/// tolerated the same way `TsDemuxer::push_bytes` tolerates them for live
+/// capture) looking for the PAT, then the PMT of its first program, and
+/// returns a config whose `video_pid`/`audio_pid`/`pcr_pid`/`program_number`
+/// and `elementary_streams` mirror what was found. Follows the common
+/// single-program (SPTS) simplification: only the first program in the PAT
+/// is probed. It runs its own `TsDemuxer`, so for an MPTS the other
+/// programs are not reachable through this function; a `TsDemuxer` with
+/// `select_program`/`with_selected_program` exposes them.
+///
+/// `audio_pid` is the first audio stream found in the PMT; pass a different
+/// value for `TsMuxerConfig::audio_pid` afterward to select another track.
+pub fn probe_muxer_config(data: &[u8], probe_limit: usize) -> Result<TsMuxerConfig> {
+    let scan_len = data.len().min(probe_limit);
+
+    let mut demuxer = crate::demux::TsDemuxer::new();
+    demuxer.push_bytes(&data[..scan_len])?;
+
+    let pmt_pid = demuxer
+        .pmt_pid()
+        .ok_or_else(|| anyhow::anyhow!("No PMT found within {} probe bytes", scan_len))?;
+    let program_number = demuxer.program_number().unwrap_or(1);
+    let pcr_pid = demuxer.pcr_pid().unwrap_or(pmt_pid);
+
+    let mut streams: Vec<_> = demuxer.streams();
+    streams.sort_by_key(|s| s.pid);
+
+    let video_pid = streams
+        .iter()
+        .find(|s| s.kind == crate::demux::StreamKind::Video)
+        .map(|s| s.pid);
+    let audio_pid = streams
+        .iter()
+        .find(|s| s.kind == crate::demux::StreamKind::Audio)
+        .map(|s| s.pid)
+        .ok_or_else(|| anyhow::anyhow!("No audio stream found in probed PMT"))?;
+
+    let elementary_streams = streams
+        .iter()
+        .map(|s| ElementaryStream {
+            pid: s.pid,
+            stream_type: s.stream_type,
+            descriptors: Vec::new(),
+        })
+        .collect();
+
+    Ok(TsMuxerConfig {
+        audio_pid,
+        video_pid: video_pid.unwrap_or(audio_pid),
+        pcr_pid,
+        pmt_pid,
+        transport_stream_id: 1,
+        program_number,
+        stream_format: StreamFormat::Adts,
+        elementary_streams,
+    })
+}
+
+/// Minimal MSB-first bit writer used to pack the variable-width LATM fields
+struct BitWriter {
+    bytes: Vec<u8>,
+    cur: u8,
+    bits_in_cur: u8,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            cur: 0,
+            bits_in_cur: 0,
+        }
+    }
+
+    fn push_bits(&mut self, value: u32, num_bits: u8) {
+        for i in (0..num_bits).rev() {
+            let bit = ((value >> i) & 0x1) as u8;
+            self.cur = (self.cur << 1) | bit;
+            self.bits_in_cur += 1;
+            if self.bits_in_cur == 8 {
+                self.bytes.push(self.cur);
+                self.cur = 0;
+                self.bits_in_cur = 0;
+            }
+        }
+    }
+
+    /// Finish the stream, zero-padding the final partial byte
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits_in_cur > 0 {
+            self.cur <<= 8 - self.bits_in_cur;
+            self.bytes.push(self.cur);
+        }
+        self.bytes
+    }
+}
+
+/// ADTS header fields needed to build the LATM `AudioSpecificConfig`
+struct AdtsHeaderInfo {
+    object_type: u8,
+    sampling_frequency_index: u8,
+    channel_configuration: u8,
+    payload_offset: usize,
+}
+
+/// Parse the fixed fields of an ADTS header (no CRC) needed for LATM repacking
+fn parse_adts_header(data: &[u8]) -> Result<AdtsHeaderInfo> {
+    if data.len() < 7 || data[0] != 0xFF || (data[1] & 0xF0) != 0xF0 {
+        bail!("Invalid ADTS sync word");
+    }
+
+    let protection_absent = data[1] & 0x01 != 0;
+    let payload_offset = if protection_absent { 7 } else { 9 };
+
+    // ADTS profile is MPEG-4 object type minus 1 (1 = AAC-LC -> object type 2)
+    let adts_profile = (data[2] >> 6) & 0x03;
+    let object_type = adts_profile + 1;
+    let sampling_frequency_index = (data[2] >> 2) & 0x0F;
+    let channel_configuration = ((data[2] & 0x01) << 2) | ((data[3] >> 6) & 0x03);
+
+    Ok(AdtsHeaderInfo {
+        object_type,
+        sampling_frequency_index,
+        channel_configuration,
+        payload_offset,
+    })
+}
+
+/// Repack an ADTS-framed AAC access unit as a LOAS/LATM frame
+///
+/// Implements the simplest single-program/single-layer LATM case:
+/// `useSameStreamMux=0`, a `StreamMuxConfig` with one program/layer and an
+/// inline 2-byte `AudioSpecificConfig`, `frameLengthType=0`, followed by
+/// the `PayloadLengthInfo` (0xFF run encoding the AAC access-unit length)
+/// and the raw AAC payload. The result is wrapped in the 3-byte LOAS
+/// transport sync (11-bit syncword + 13-bit length).
+pub fn adts_to_latm(adts_frame: &[u8]) -> Result<Vec<u8>> {
+    let header = parse_adts_header(adts_frame)?;
+    let payload = &adts_frame[header.payload_offset..];
+
+    let mut mux = BitWriter::new();
+    mux.push_bits(0, 1); // useSameStreamMux = 0 (send full StreamMuxConfig)
+
+    // StreamMuxConfig
+    mux.push_bits(0, 1); // audioMuxVersion = 0
+    mux.push_bits(1, 1); // allStreamsSameTimeFraming = 1
+    mux.push_bits(0, 6); // numSubFrames = 0 (1 sub-frame)
+    mux.push_bits(0, 4); // numProgram = 0 (1 program)
+    mux.push_bits(0, 3); // numLayer = 0 (1 layer)
+
+    // Inline AudioSpecificConfig (2 bytes): object type + sample rate index + channel config
+    mux.push_bits(header.object_type as u32, 5);
+    mux.push_bits(header.sampling_frequency_index as u32, 4);
+    mux.push_bits(header.channel_configuration as u32, 4);
+    // GASpecificConfig: frameLengthFlag, dependsOnCoreCoder, extensionFlag
+    mux.push_bits(0, 1);
+    mux.push_bits(0, 1);
+    mux.push_bits(0, 1);
+
+    mux.push_bits(0, 3); // frameLengthType = 0 (variable length, via PayloadLengthInfo)
+    mux.push_bits(0xFF, 8); // latmBufferFullness (0xFF = unknown/VBR)
+    mux.push_bits(0, 1); // otherDataPresent = 0
+    mux.push_bits(0, 1); // crcCheckPresent = 0
+
+    // PayloadLengthInfo: run of 0xFF bytes, final byte is the remainder
+    let mut remaining = payload.len();
+    while remaining >= 0xFF {
+        mux.push_bits(0xFF, 8);
+        remaining -= 0xFF;
+    }
+    mux.push_bits(remaining as u32, 8);
+
+    let mut audio_mux_element = mux.finish();
+    audio_mux_element.extend_from_slice(payload);
+
+    // LOAS transport framing: 11-bit syncword + 13-bit payload length
+    let mut loas = BitWriter::new();
+    loas.push_bits(0x56E, 11);
+    loas.push_bits(audio_mux_element.len() as u32, 13);
+    let mut frame = loas.finish();
+    frame.extend_from_slice(&audio_mux_element);
+
+    Ok(frame)
+}
+
+/// Rewrite the audio elementary stream's `stream_type` in a source PMT
+/// packet to reflect the remuxer's output framing, for programs whose
+/// PAT/PMT are passed through from the original broadcast instead of
+/// being synthesized by this muxer.
+///
+/// Returns `None` if `packet` isn't a PMT section referencing `audio_pid`,
+/// leaving the caller free to fall back to a verbatim copy. The section
+/// CRC32 is recomputed over the edited bytes afterward, the same way the
+/// muxer's own synthesized PAT/PMT compute theirs.
+pub fn rewrite_pmt_audio_stream_type(
+    packet: &[u8; 188],
+    audio_pid: u16,
+    stream_format: StreamFormat,
+) -> Option<[u8; 188]> {
+    let mut out = *packet;
+
+    if out[3] & 0x20 != 0 {
+        return None; // Adaptation field present; not the plain PSI layout we handle
+    }
+
+    let payload = &out[4..];
+    if payload.is_empty() {
+        return None;
+    }
+
+    let pointer = payload[0] as usize;
+    let section_start = 4 + 1 + pointer;
+    if section_start + 12 > out.len() || out[section_start] != 0x02 {
+        return None; // Not a (long enough) PMT table
+    }
+
+    let section_length =
+        (((out[section_start + 1] & 0x0F) as usize) << 8) | (out[section_start + 2] as usize);
+    let section_end = section_start + 3 + section_length;
+    if section_end > out.len() || section_end < 4 {
+        return None;
+    }
+
+    let program_info_length =
+        (((out[section_start + 10] & 0x0F) as usize) << 8) | (out[section_start + 11] as usize);
+
+    let mut offset = section_start + 12 + program_info_length;
+    while offset + 5 <= section_end {
+        let stream_type_offset = offset;
+        let pid = (((out[offset + 1] & 0x1F) as u16) << 8) | (out[offset + 2] as u16);
+        let es_info_length = (((out[offset + 3] & 0x0F) as usize) << 8) | (out[offset + 4] as usize);
+
+        if pid == audio_pid {
+            out[stream_type_offset] = match stream_format {
+                StreamFormat::Adts => 0x0F,
+                StreamFormat::Latm => 0x11,
+            };
+
+            let crc_offset = section_end - 4;
+            let crc = mpeg_crc32(&out[section_start..crc_offset]);
+            out[crc_offset..section_end].copy_from_slice(&crc.to_be_bytes());
+
+            return Some(out);
         }
+
+        offset += 5 + es_info_length;
     }
+
+    None
+}
+
+/// Compute the MPEG-2 Systems CRC-32 used to validate PAT/PMT sections
+///
+/// Polynomial 0x04C11DB7, initial register 0xFFFFFFFF, MSB-first bit order,
+/// no input/output reflection, and no final XOR. Callers pass the section
+/// bytes from `table_id` through the last byte before the CRC field; the
+/// result is appended big-endian.
+fn mpeg_crc32(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xFFFF_FFFF;
+    for &byte in data {
+        crc ^= (byte as u32) << 24;
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04C1_1DB7
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Encode a Program Clock Reference into its 6-byte adaptation field form
+///
+/// `base` is the 33-bit 90 kHz component and `ext` the 9-bit 27 MHz
+/// remainder; the full PCR value is `base * 300 + ext`. The encoding is
+/// the mirror image of `demux::parse_pcr`: 33 base bits, 6 reserved bits
+/// (all set to 1), then 9 extension bits.
+fn encode_pcr(base: u64, ext: u64) -> [u8; 6] {
+    let base = base & 0x1_FFFF_FFFF;
+    let ext = ext & 0x1FF;
+    [
+        (base >> 25) as u8,
+        (base >> 17) as u8,
+        (base >> 9) as u8,
+        (base >> 1) as u8,
+        (((base & 0x1) as u8) << 7) | 0x7E | ((ext >> 8) as u8),
+        (ext & 0xFF) as u8,
+    ]
 }
 
 /// MPEG-TS Muxer
@@ -93,8 +431,8 @@ pub struct TsMuxer {
     /// Continuity counter for PMT (0-15)
     pmt_continuity: u8,
 
-    /// Current PCR value (27 MHz)
-    #[allow(dead_code)]
+    /// Most recent Program Clock Reference written into an adaptation field
+    /// (27 MHz ticks)
     pcr: u64,
 
     /// Packets muxed
@@ -144,11 +482,17 @@ impl TsMuxer {
             packets.push(self.generate_pmt());
         }
 
-        // Create PES packet from AAC data
-        let pes_data = self.create_pes_packet(aac_data, pts, dts)?;
+        // Repack as LOAS/LATM if configured; ADTS frames are carried as-is
+        let payload = match self.config.stream_format {
+            StreamFormat::Adts => aac_data.to_vec(),
+            StreamFormat::Latm => adts_to_latm(aac_data)?,
+        };
+
+        // Create PES packet from (possibly repacked) AAC data
+        let pes_data = self.create_pes_packet(&payload, pts, dts)?;
 
         // Fragment PES into TS packets
-        let ts_packets = self.create_ts_packets(&pes_data, self.config.audio_pid, true)?;
+        let ts_packets = self.create_ts_packets(&pes_data, self.config.audio_pid, true, pts)?;
         packets.extend(ts_packets);
 
         self.packets_muxed += packets.len() as u64;
@@ -246,11 +590,14 @@ impl TsMuxer {
     /// * `pes_data` - Complete PES packet
     /// * `pid` - PID for these packets
     /// * `is_audio` - true if audio stream (for continuity counter)
+    /// * `pts` - Presentation Time Stamp (90 kHz) of this access unit, used
+    ///   to derive the PCR when `pid` is `config.pcr_pid`
     fn create_ts_packets(
         &mut self,
         pes_data: &[u8],
         pid: u16,
         is_audio: bool,
+        pts: u64,
     ) -> Result<Vec<[u8; 188]>> {
         let mut packets = Vec::new();
         let mut offset = 0;
@@ -279,10 +626,26 @@ impl TsMuxer {
                 cc
             };
 
-            packet[3] = 0x10 | continuity; // Payload present, no adaptation field
+            // Carry a PCR in the adaptation field of the first packet of
+            // each access unit on the designated PCR PID, so downstream
+            // players have a clock to lock onto
+            let mut payload_start = 4;
+            if first_packet && pid == self.config.pcr_pid {
+                const ADAPTATION_LENGTH: u8 = 7; // flags byte + 6-byte PCR
+                packet[3] = 0x30 | continuity; // Adaptation field + payload present
+                packet[4] = ADAPTATION_LENGTH;
+                packet[5] = 0x10; // PCR_flag set
+
+                let base = pts & 0x1_FFFF_FFFF; // 33-bit 90 kHz base
+                self.pcr = base * 300; // no finer-grained clock to derive an extension from
+                packet[6..12].copy_from_slice(&encode_pcr(base, 0));
+
+                payload_start += 1 + ADAPTATION_LENGTH as usize;
+            } else {
+                packet[3] = 0x10 | continuity; // Payload present, no adaptation field
+            }
 
             // Calculate payload size
-            let payload_start = 4;
             let available = TS_PACKET_SIZE - payload_start;
             let remaining = pes_data.len() - offset;
             let to_copy = available.min(remaining);
@@ -356,18 +719,54 @@ impl TsMuxer {
         packet[offset + 3] = (self.config.pmt_pid & 0xFF) as u8;
         offset += 4;
 
-        // CRC32 (simplified - should be calculated properly)
-        // For now, use dummy CRC (proper implementation would calculate actual CRC)
-        packet[offset..offset + 4].copy_from_slice(&[0x00, 0x00, 0x00, 0x00]);
+        // CRC32 over the section from table_id through the byte just written
+        debug_assert_eq!(
+            offset - 4,
+            section_length as usize,
+            "PAT section_length doesn't match the CRC input length"
+        );
+        let crc = mpeg_crc32(&packet[5..offset]);
+        packet[offset..offset + 4].copy_from_slice(&crc.to_be_bytes());
 
         trace!("Generated PAT");
 
         packet
     }
 
+    /// Elementary streams to advertise in the PMT
+    ///
+    /// Returns `config.elementary_streams` verbatim if the caller populated
+    /// it; otherwise falls back to the implicit video (H.264) + audio
+    /// (`stream_format`) pair this muxer has always produced, derived from
+    /// `video_pid`/`audio_pid`.
+    fn pmt_streams(&self) -> Vec<ElementaryStream> {
+        if !self.config.elementary_streams.is_empty() {
+            return self.config.elementary_streams.clone();
+        }
+
+        vec![
+            ElementaryStream {
+                pid: self.config.video_pid,
+                stream_type: 0x1B, // H.264
+                descriptors: Vec::new(),
+            },
+            ElementaryStream {
+                pid: self.config.audio_pid,
+                stream_type: match self.config.stream_format {
+                    StreamFormat::Adts => 0x0F, // AAC ADTS
+                    StreamFormat::Latm => 0x11, // MP4A-LATM
+                },
+                descriptors: Vec::new(),
+            },
+        ]
+    }
+
     /// Generate PMT (Program Map Table) packet
     ///
-    /// PMT maps elementary stream PIDs and types.
+    /// PMT maps elementary stream PIDs and types. Advertises
+    /// `config.elementary_streams` when set (arbitrary codecs, multiple
+    /// audio tracks, or an audio-only program), otherwise the implicit
+    /// single video + audio pair.
     pub fn generate_pmt(&mut self) -> [u8; 188] {
         let mut packet = [0xFF_u8; TS_PACKET_SIZE];
 
@@ -388,9 +787,12 @@ impl TsMuxer {
         packet[offset] = 0x02;
         offset += 1;
 
+        let streams = self.pmt_streams();
+        let streams_length: usize = streams.iter().map(|s| 5 + s.descriptors.len()).sum();
+
         // Section syntax indicator + section length
-        let section_length = 18; // Header + streams + CRC
-        packet[offset] = 0xB0 | ((section_length >> 8) as u8);
+        let section_length = 9 + streams_length + 4; // header after length + stream entries + CRC
+        packet[offset] = 0xB0 | ((section_length >> 8) as u8 & 0x0F);
         packet[offset + 1] = (section_length & 0xFF) as u8;
         offset += 2;
 
@@ -421,24 +823,29 @@ impl TsMuxer {
         packet[offset + 1] = 0x00;
         offset += 2;
 
-        // Stream entry: Video (H.264)
-        packet[offset] = 0x1B; // Stream type (H.264)
-        packet[offset + 1] = 0xE0 | ((self.config.video_pid >> 8) as u8);
-        packet[offset + 2] = (self.config.video_pid & 0xFF) as u8;
-        packet[offset + 3] = 0xF0; // ES info length
-        packet[offset + 4] = 0x00;
-        offset += 5;
-
-        // Stream entry: Audio (AAC)
-        packet[offset] = 0x0F; // Stream type (AAC ADTS)
-        packet[offset + 1] = 0xE0 | ((self.config.audio_pid >> 8) as u8);
-        packet[offset + 2] = (self.config.audio_pid & 0xFF) as u8;
-        packet[offset + 3] = 0xF0; // ES info length
-        packet[offset + 4] = 0x00;
-        offset += 5;
-
-        // CRC32 (dummy)
-        packet[offset..offset + 4].copy_from_slice(&[0x00, 0x00, 0x00, 0x00]);
+        for stream in &streams {
+            packet[offset] = stream.stream_type;
+            packet[offset + 1] = 0xE0 | ((stream.pid >> 8) as u8 & 0x1F);
+            packet[offset + 2] = (stream.pid & 0xFF) as u8;
+            let es_info_length = stream.descriptors.len();
+            packet[offset + 3] = 0xF0 | ((es_info_length >> 8) as u8 & 0x0F);
+            packet[offset + 4] = (es_info_length & 0xFF) as u8;
+            offset += 5;
+
+            if !stream.descriptors.is_empty() {
+                packet[offset..offset + es_info_length].copy_from_slice(&stream.descriptors);
+                offset += es_info_length;
+            }
+        }
+
+        // CRC32 over the section from table_id through the byte just written
+        debug_assert_eq!(
+            offset - 4,
+            section_length,
+            "PMT section_length doesn't match the CRC input length"
+        );
+        let crc = mpeg_crc32(&packet[5..offset]);
+        packet[offset..offset + 4].copy_from_slice(&crc.to_be_bytes());
 
         trace!("Generated PMT");
 
@@ -453,6 +860,12 @@ impl TsMuxer {
             self.video_continuity,
         )
     }
+
+    /// Most recent PCR (27 MHz ticks) written into an adaptation field, or
+    /// 0 if none has been written yet
+    pub fn last_pcr(&self) -> u64 {
+        self.pcr
+    }
 }
 
 impl Default for TsMuxer {
@@ -494,6 +907,35 @@ mod tests {
         assert_eq!(pmt[5], 0x02); // Table ID = PMT
     }
 
+    #[test]
+    fn test_mpeg_crc32_matches_known_check_value() {
+        // CRC-32/MPEG-2 reference check value for the ASCII string "123456789"
+        assert_eq!(mpeg_crc32(b"123456789"), 0x0376_E6E7);
+    }
+
+    #[test]
+    fn test_pat_section_crc_is_valid() {
+        let mut muxer = TsMuxer::default();
+        let pat = muxer.generate_pat();
+
+        // Section spans table_id (offset 5) through section_length + 3 bytes
+        let section_length = (((pat[6] & 0x0F) as usize) << 8) | (pat[7] as usize);
+        let section_end = 8 + section_length;
+        let crc = mpeg_crc32(&pat[5..section_end - 4]);
+        assert_eq!(&pat[section_end - 4..section_end], &crc.to_be_bytes());
+    }
+
+    #[test]
+    fn test_pmt_section_crc_is_valid() {
+        let mut muxer = TsMuxer::default();
+        let pmt = muxer.generate_pmt();
+
+        let section_length = (((pmt[6] & 0x0F) as usize) << 8) | (pmt[7] as usize);
+        let section_end = 8 + section_length;
+        let crc = mpeg_crc32(&pmt[5..section_end - 4]);
+        assert_eq!(&pmt[section_end - 4..section_end], &crc.to_be_bytes());
+    }
+
     #[test]
     fn test_continuity_counter() {
         let mut muxer = TsMuxer::default();
@@ -522,4 +964,236 @@ mod tests {
             assert_eq!(packet.len(), TS_PACKET_SIZE);
         }
     }
+
+    #[test]
+    fn test_encode_pcr_round_trips_through_demux_parse_pcr() {
+        use crate::demux::TsPacket;
+
+        let config = TsMuxerConfig {
+            pcr_pid: 0x0101, // Put PCR on the audio PID so mux_audio carries it
+            ..TsMuxerConfig::default()
+        };
+        let mut muxer = TsMuxer::new(config);
+
+        let aac_data = vec![0xFF; 100];
+        let pts = 90_000;
+        let packets = muxer.mux_audio(&aac_data, pts, pts).unwrap();
+
+        // First audio packet (after PAT/PMT) should carry the PCR
+        let audio_packet = packets
+            .iter()
+            .find(|p| ((p[1] as u16 & 0x1F) << 8 | p[2] as u16) == 0x0101)
+            .expect("an audio packet should be present");
+        assert_eq!(audio_packet[3] & 0x30, 0x30); // adaptation field + payload
+
+        let parsed = TsPacket::parse(audio_packet).unwrap();
+        assert_eq!(parsed.pcr(), Some(pts * 300));
+        assert_eq!(muxer.last_pcr(), pts * 300);
+    }
+
+    #[test]
+    fn test_create_ts_packets_omits_pcr_when_pid_is_not_pcr_pid() {
+        // Default config puts the PCR on the video PID, so the synthesized
+        // audio packets (which never pass through this PID) must not carry one.
+        let mut muxer = TsMuxer::default();
+        let aac_data = vec![0xFF; 100];
+        let packets = muxer.mux_audio(&aac_data, 90_000, 90_000).unwrap();
+
+        let audio_packet = packets
+            .iter()
+            .find(|p| ((p[1] as u16 & 0x1F) << 8 | p[2] as u16) == muxer.config.audio_pid)
+            .expect("an audio packet should be present");
+        assert_eq!(audio_packet[3] & 0x30, 0x10); // payload only, no adaptation field
+        assert_eq!(muxer.last_pcr(), 0);
+    }
+
+    #[test]
+    fn test_adts_to_latm_starts_with_loas_syncword() {
+        // AAC-LC (profile 1 -> object type 2), 48kHz (index 3), stereo (config 2)
+        let mut adts_frame = vec![0xFF, 0xF1, (1 << 6) | (3 << 2) | (2 >> 2), (2 & 0x03) << 6];
+        adts_frame.extend_from_slice(&[0x00, 0x1F, 0xFC]); // remaining ADTS header bytes
+        adts_frame.extend_from_slice(&[0xAB, 0xCD, 0xEF]); // 3-byte AAC payload
+
+        let latm = adts_to_latm(&adts_frame).unwrap();
+
+        // 3-byte LOAS sync header + AudioMuxElement
+        assert!(latm.len() > 3);
+
+        // LOAS syncword (11 bits) should be 0x56E
+        let sync = ((latm[0] as u32) << 3) | ((latm[1] as u32) >> 5);
+        assert_eq!(sync, 0x56E);
+    }
+
+    #[test]
+    fn test_rewrite_pmt_audio_stream_type() {
+        let mut muxer = TsMuxer::default();
+        let pmt = muxer.generate_pmt();
+
+        // The default-generated PMT advertises ADTS (0x0F) for the audio PID;
+        // rewrite it to LATM (0x11) as if passing through a source PMT.
+        let rewritten =
+            rewrite_pmt_audio_stream_type(&pmt, muxer.config.audio_pid, StreamFormat::Latm)
+                .expect("audio entry should be found");
+
+        assert_eq!(rewritten[5 + 1 + 2 + 2 + 1 + 1 + 1 + 2 + 2 + 5], 0x11);
+        // CRC32 trailer should be recomputed over the edited section
+        let section_length = (((rewritten[6] & 0x0F) as usize) << 8) | (rewritten[7] as usize);
+        let section_end = 8 + section_length;
+        let crc = mpeg_crc32(&rewritten[5..section_end - 4]);
+        assert_eq!(&rewritten[section_end - 4..section_end], &crc.to_be_bytes());
+    }
+
+    #[test]
+    fn test_rewrite_pmt_audio_stream_type_unknown_pid_returns_none() {
+        let mut muxer = TsMuxer::default();
+        let pmt = muxer.generate_pmt();
+
+        assert!(rewrite_pmt_audio_stream_type(&pmt, 0x1234, StreamFormat::Latm).is_none());
+    }
+
+    #[test]
+    fn test_pmt_stream_type_reflects_format() {
+        let mut adts_muxer = TsMuxer::default();
+        let pmt = adts_muxer.generate_pmt();
+        // Stream type byte for the audio entry, after the table id, section
+        // length, program number, version, section numbers, PCR PID,
+        // program info length, and the video stream entry.
+        assert_eq!(pmt[5 + 1 + 2 + 2 + 1 + 1 + 1 + 2 + 2 + 5], 0x0F);
+
+        let latm_config = TsMuxerConfig {
+            stream_format: StreamFormat::Latm,
+            ..TsMuxerConfig::default()
+        };
+        let mut latm_muxer = TsMuxer::new(latm_config);
+        let pmt = latm_muxer.generate_pmt();
+        assert_eq!(pmt[5 + 1 + 2 + 2 + 1 + 1 + 1 + 2 + 2 + 5], 0x11);
+    }
+
+    #[test]
+    fn test_generate_pmt_advertises_explicit_elementary_streams() {
+        // Two audio tracks (AC-3 and ADTS-AAC) and no video entry at all.
+        let config = TsMuxerConfig {
+            elementary_streams: vec![
+                ElementaryStream {
+                    pid: 0x0101,
+                    stream_type: 0x81, // AC-3
+                    descriptors: vec![0x6A, 0x01, 0x00], // AC3 registration descriptor
+                },
+                ElementaryStream {
+                    pid: 0x0102,
+                    stream_type: 0x0F, // ADTS-AAC
+                    descriptors: Vec::new(),
+                },
+            ],
+            ..TsMuxerConfig::default()
+        };
+        let mut muxer = TsMuxer::new(config);
+        let pmt = muxer.generate_pmt();
+
+        let section_length = (((pmt[6] & 0x0F) as usize) << 8) | (pmt[7] as usize);
+        let section_end = 8 + section_length;
+        let crc = mpeg_crc32(&pmt[5..section_end - 4]);
+        assert_eq!(&pmt[section_end - 4..section_end], &crc.to_be_bytes());
+
+        // First stream entry: AC-3 on PID 0x0101 with a 3-byte descriptor
+        let first = 5 + 1 + 2 + 2 + 1 + 1 + 1 + 2 + 2;
+        assert_eq!(pmt[first], 0x81);
+        assert_eq!(((pmt[first + 1] as u16 & 0x1F) << 8) | pmt[first + 2] as u16, 0x0101);
+        let first_es_len = (((pmt[first + 3] & 0x0F) as usize) << 8) | (pmt[first + 4] as usize);
+        assert_eq!(first_es_len, 3);
+        assert_eq!(&pmt[first + 5..first + 5 + first_es_len], &[0x6A, 0x01, 0x00]);
+
+        // Second stream entry: AAC on PID 0x0102, immediately after the first
+        let second = first + 5 + first_es_len;
+        assert_eq!(pmt[second], 0x0F);
+        assert_eq!(((pmt[second + 1] as u16 & 0x1F) << 8) | pmt[second + 2] as u16, 0x0102);
+    }
+
+    #[test]
+    fn test_generate_pmt_supports_audio_only_program() {
+        // Radio/DAB-style program: no video entry, PCR carried on the audio PID.
+        let config = TsMuxerConfig {
+            pcr_pid: 0x0101,
+            elementary_streams: vec![ElementaryStream {
+                pid: 0x0101,
+                stream_type: 0x0F,
+                descriptors: Vec::new(),
+            }],
+            ..TsMuxerConfig::default()
+        };
+        let mut muxer = TsMuxer::new(config);
+        let pmt = muxer.generate_pmt();
+
+        let pcr_pid = ((pmt[5 + 1 + 2 + 2 + 1 + 1 + 1] as u16 & 0x1F) << 8)
+            | pmt[5 + 1 + 2 + 2 + 1 + 1 + 1 + 1] as u16;
+        assert_eq!(pcr_pid, 0x0101);
+
+        let only_stream_offset = 5 + 1 + 2 + 2 + 1 + 1 + 1 + 2 + 2;
+        assert_eq!(pmt[only_stream_offset], 0x0F);
+    }
+
+    #[test]
+    fn test_probe_muxer_config_recovers_pids_from_generated_stream() {
+        let config = TsMuxerConfig {
+            program_number: 7,
+            elementary_streams: vec![
+                ElementaryStream {
+                    pid: 0x0100,
+                    stream_type: 0x1B,
+                    descriptors: Vec::new(),
+                },
+                ElementaryStream {
+                    pid: 0x0101,
+                    stream_type: 0x0F,
+                    descriptors: Vec::new(),
+                },
+            ],
+            ..TsMuxerConfig::default()
+        };
+        let mut muxer = TsMuxer::new(config);
+        let packets = muxer.mux_audio(&vec![0xFF; 100], 90_000, 90_000).unwrap();
+
+        let mut stream = Vec::new();
+        for packet in &packets {
+            stream.extend_from_slice(packet);
+        }
+
+        let probed = probe_muxer_config(&stream, stream.len()).unwrap();
+        assert_eq!(probed.pmt_pid, 0x1000);
+        assert_eq!(probed.program_number, 7);
+        assert_eq!(probed.pcr_pid, 0x0100);
+        assert_eq!(probed.video_pid, 0x0100);
+        assert_eq!(probed.audio_pid, 0x0101);
+        assert_eq!(probed.elementary_streams.len(), 2);
+        assert_eq!(probed.elementary_streams[0].stream_type, 0x1B);
+        assert_eq!(probed.elementary_streams[1].stream_type, 0x0F);
+    }
+
+    #[test]
+    fn test_probe_muxer_config_resyncs_past_leading_junk() {
+        let mut muxer = TsMuxer::default();
+        let packets = muxer.mux_audio(&vec![0xFF; 100], 90_000, 90_000).unwrap();
+
+        let mut stream = vec![0x00; 37]; // Not aligned to any packet boundary
+        for packet in &packets {
+            stream.extend_from_slice(packet);
+        }
+
+        let probed = probe_muxer_config(&stream, stream.len()).unwrap();
+        assert_eq!(probed.audio_pid, 0x0101);
+    }
+
+    #[test]
+    fn test_probe_muxer_config_respects_probe_limit() {
+        let mut muxer = TsMuxer::default();
+        let packets = muxer.mux_audio(&vec![0xFF; 100], 90_000, 90_000).unwrap();
+
+        let mut stream = Vec::new();
+        for packet in &packets {
+            stream.extend_from_slice(packet);
+        }
+
+        // Limiting the scan to less than one packet leaves no PAT to find
+        assert!(probe_muxer_config(&stream, 10).is_err());
+    }
 }
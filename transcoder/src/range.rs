@@ -0,0 +1,195 @@
+//! HTTP `Range` support for serving a completed file
+//!
+//! Implements single-range `Range: bytes=start-end` requests (RFC 7233)
+//! against a file of known size, for the job-output endpoint: `200` with
+//! the whole file when there's no `Range` header, `206 Partial Content`
+//! with a correct `Content-Range` for a satisfiable range, and
+//! `416 Range Not Satisfiable` otherwise. Multi-range requests (a
+//! comma-separated `Range` value) aren't supported; they fall back to a
+//! full `200` response rather than erroring, which is permitted by the
+//! spec.
+
+use axum::body::Body;
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
+
+/// A single parsed, in-bounds `bytes=start-end` range
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ByteRange {
+    start: u64,
+    /// Inclusive end offset
+    end: u64,
+}
+
+impl ByteRange {
+    /// Length of the range in bytes
+    fn len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+
+    /// Parse a `Range` header value against a resource of `total_len` bytes
+    ///
+    /// `Ok(None)` means "serve the full body" (no header, or a form we
+    /// don't support); `Err(())` means the range is out of bounds and the
+    /// caller should respond `416 Range Not Satisfiable`.
+    fn parse(header_value: Option<&str>, total_len: u64) -> Result<Option<Self>, ()> {
+        let Some(value) = header_value else {
+            return Ok(None);
+        };
+        let Some(spec) = value.strip_prefix("bytes=") else {
+            return Ok(None);
+        };
+        if spec.contains(',') {
+            return Ok(None);
+        }
+
+        let (start_str, end_str) = spec.split_once('-').ok_or(())?;
+
+        let (start, end) = if start_str.is_empty() {
+            // `bytes=-N`: the last N bytes of the resource
+            let suffix_len: u64 = end_str.parse().map_err(|_| ())?;
+            if suffix_len == 0 || total_len == 0 {
+                return Err(());
+            }
+            (total_len.saturating_sub(suffix_len), total_len - 1)
+        } else {
+            let start: u64 = start_str.parse().map_err(|_| ())?;
+            let end: u64 = if end_str.is_empty() {
+                total_len.saturating_sub(1)
+            } else {
+                end_str.parse().map_err(|_| ())?
+            };
+            (start, end)
+        };
+
+        if total_len == 0 || start > end || start >= total_len {
+            return Err(());
+        }
+
+        Ok(Some(Self {
+            start,
+            end: end.min(total_len - 1),
+        }))
+    }
+}
+
+/// Serve `file` (already opened, `total_len` bytes long) honoring `range_header`
+pub async fn serve_file_range(
+    mut file: tokio::fs::File,
+    total_len: u64,
+    content_type: &'static str,
+    range_header: Option<&str>,
+) -> Response {
+    let range = match ByteRange::parse(range_header, total_len) {
+        Ok(range) => range,
+        Err(()) => {
+            return (
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                [(header::CONTENT_RANGE, format!("bytes */{total_len}"))],
+            )
+                .into_response();
+        }
+    };
+
+    let Some(range) = range else {
+        let headers = [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::ACCEPT_RANGES, "bytes".to_string()),
+            (header::CONTENT_LENGTH, total_len.to_string()),
+            (
+                header::CACHE_CONTROL,
+                "no-cache, no-store, must-revalidate".to_string(),
+            ),
+        ];
+        return (StatusCode::OK, headers, Body::from_stream(ReaderStream::new(file)))
+            .into_response();
+    };
+
+    if let Err(e) = file.seek(std::io::SeekFrom::Start(range.start)).await {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to seek to requested range: {e}"),
+        )
+            .into_response();
+    }
+
+    let headers = [
+        (header::CONTENT_TYPE, content_type.to_string()),
+        (header::ACCEPT_RANGES, "bytes".to_string()),
+        (header::CONTENT_LENGTH, range.len().to_string()),
+        (
+            header::CONTENT_RANGE,
+            format!("bytes {}-{}/{}", range.start, range.end, total_len),
+        ),
+        (
+            header::CACHE_CONTROL,
+            "no-cache, no-store, must-revalidate".to_string(),
+        ),
+    ];
+    let stream = ReaderStream::new(file.take(range.len()));
+    (StatusCode::PARTIAL_CONTENT, headers, Body::from_stream(stream)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_none_without_header() {
+        assert_eq!(ByteRange::parse(None, 1000), Ok(None));
+    }
+
+    #[test]
+    fn test_parse_start_end() {
+        assert_eq!(
+            ByteRange::parse(Some("bytes=0-499"), 1000),
+            Ok(Some(ByteRange { start: 0, end: 499 }))
+        );
+    }
+
+    #[test]
+    fn test_parse_open_ended() {
+        assert_eq!(
+            ByteRange::parse(Some("bytes=500-"), 1000),
+            Ok(Some(ByteRange { start: 500, end: 999 }))
+        );
+    }
+
+    #[test]
+    fn test_parse_suffix_range() {
+        assert_eq!(
+            ByteRange::parse(Some("bytes=-100"), 1000),
+            Ok(Some(ByteRange { start: 900, end: 999 }))
+        );
+    }
+
+    #[test]
+    fn test_parse_clamps_end_past_total_len() {
+        assert_eq!(
+            ByteRange::parse(Some("bytes=0-9999"), 1000),
+            Ok(Some(ByteRange { start: 0, end: 999 }))
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_start_past_total_len() {
+        assert_eq!(ByteRange::parse(Some("bytes=1000-1001"), 1000), Err(()));
+    }
+
+    #[test]
+    fn test_parse_rejects_inverted_range() {
+        assert_eq!(ByteRange::parse(Some("bytes=500-100"), 1000), Err(()));
+    }
+
+    #[test]
+    fn test_parse_falls_back_on_multi_range() {
+        assert_eq!(ByteRange::parse(Some("bytes=0-99,200-299"), 1000), Ok(None));
+    }
+
+    #[test]
+    fn test_parse_falls_back_on_unparseable_header() {
+        assert_eq!(ByteRange::parse(Some("not-a-range"), 1000), Ok(None));
+    }
+}
@@ -0,0 +1,222 @@
+//! PCM Resampling and Channel Remixing
+//!
+//! This module converts decoded PCM audio from the source codec's native
+//! sample rate/channel layout to the rate/layout the AAC encoder was
+//! configured for. Broadcast MP2/AC3 streams are frequently 44.1 kHz or
+//! mono, and feeding that straight into an encoder configured for 48 kHz
+//! stereo produces pitch/speed-corrupted output.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! use crate::resampler::Resampler;
+//!
+//! let mut resampler = Resampler::new(44100, 1, 48000, 2);
+//! let converted = resampler.process(&pcm_samples);
+//! ```
+
+use tracing::trace;
+
+/// PCM Resampler / Channel Remixer
+///
+/// Converts interleaved f32 PCM from `(src_rate, src_channels)` to
+/// `(dst_rate, dst_channels)`. Rate conversion uses linear interpolation
+/// driven by a fractional source-position accumulator so that per-chunk
+/// output length can vary by ±1 sample without accumulating drift over a
+/// long stream, and so the interpolated sub-sample phase itself carries
+/// over unbroken from one call's last output frame to the next call's
+/// first, rather than resetting at every chunk boundary. Channel remixing
+/// (mono↔stereo) is applied before rate conversion.
+pub struct Resampler {
+    src_rate: u32,
+    src_channels: u16,
+    dst_rate: u32,
+    dst_channels: u16,
+
+    /// Position (in source frames, relative to `tail`) of the next output
+    /// sample to be synthesized. Carried across calls so interpolation
+    /// phase is continuous instead of restarting at 0 every chunk.
+    phase: f64,
+
+    /// Last output-channel-layout frame from the previous call, prepended
+    /// to the next call's input so `phase` can address it as frame 0.
+    tail: Vec<f32>,
+}
+
+impl Resampler {
+    /// Create a new resampler for the given source/destination rate and channel layout
+    pub fn new(src_rate: u32, src_channels: u16, dst_rate: u32, dst_channels: u16) -> Self {
+        Self {
+            src_rate,
+            src_channels,
+            dst_rate,
+            dst_channels,
+            phase: 0.0,
+            tail: Vec::new(),
+        }
+    }
+
+    /// True if no conversion is actually necessary
+    pub fn is_passthrough(&self) -> bool {
+        self.src_rate == self.dst_rate && self.src_channels == self.dst_channels
+    }
+
+    /// Convert a chunk of interleaved PCM samples
+    ///
+    /// The number of samples returned is not guaranteed to be
+    /// `input.len() * ratio` exactly; callers must push whatever is
+    /// returned into the downstream buffer rather than assuming a fixed
+    /// output size per call.
+    pub fn process(&mut self, input: &[f32]) -> Vec<f32> {
+        if self.is_passthrough() {
+            return input.to_vec();
+        }
+
+        let remixed = self.remix_channels(input);
+
+        if self.src_rate == self.dst_rate {
+            return remixed;
+        }
+
+        self.resample_rate(&remixed)
+    }
+
+    /// Mono↔stereo channel conversion (mono→stereo duplication, stereo→mono averaging)
+    fn remix_channels(&self, input: &[f32]) -> Vec<f32> {
+        match (self.src_channels, self.dst_channels) {
+            (a, b) if a == b => input.to_vec(),
+            (1, 2) => input.iter().flat_map(|&s| [s, s]).collect(),
+            (2, 1) => input.chunks_exact(2).map(|f| (f[0] + f[1]) * 0.5).collect(),
+            (src, dst) => {
+                trace!(
+                    "Unsupported channel remix {} -> {}, passing samples through unchanged",
+                    src, dst
+                );
+                input.to_vec()
+            }
+        }
+    }
+
+    /// Linear-interpolation sample-rate conversion with a carried fractional source phase
+    fn resample_rate(&mut self, frames_data: &[f32]) -> Vec<f32> {
+        let channels = self.dst_channels as usize;
+        if channels == 0 {
+            return Vec::new();
+        }
+
+        let ratio = self.dst_rate as f64 / self.src_rate as f64;
+        let in_frames = frames_data.len() / channels;
+        if in_frames == 0 {
+            return Vec::new();
+        }
+
+        // Prepend the previous call's final frame so interpolation stays
+        // continuous across chunk boundaries instead of clicking.
+        let mut source = Vec::with_capacity(self.tail.len() + frames_data.len());
+        source.extend_from_slice(&self.tail);
+        source.extend_from_slice(frames_data);
+        let source_frames = source.len() / channels;
+        let last_valid = source_frames.saturating_sub(1);
+
+        // `phase` already addresses this call's source buffer (frame 0 is
+        // the previous call's tail, or 0.0 on the very first call), so
+        // output positions advance directly from it instead of resetting
+        // to a hardcoded start every chunk.
+        let base = self.phase;
+        let out_len = if (last_valid as f64) >= base {
+            (((last_valid as f64 - base) * ratio).floor() as usize) + 1
+        } else {
+            0
+        };
+
+        let mut output = Vec::with_capacity(out_len * channels);
+        for i in 0..out_len {
+            let src_pos = base + (i as f64) / ratio;
+            let idx0 = (src_pos.floor() as usize).min(last_valid);
+            let idx1 = (idx0 + 1).min(last_valid);
+            let frac = (src_pos - idx0 as f64) as f32;
+
+            for ch in 0..channels {
+                let s0 = source[idx0 * channels + ch];
+                let s1 = source[idx1 * channels + ch];
+                output.push(s0 + (s1 - s0) * frac);
+            }
+        }
+
+        // Carry the not-yet-synthesized position forward, re-based onto
+        // the frame that becomes index 0 (the tail) of the next call.
+        let next_pos = base + (out_len as f64) / ratio;
+        self.phase = next_pos - last_valid as f64;
+
+        if source_frames > 0 {
+            let last_start = last_valid * channels;
+            self.tail = source[last_start..last_start + channels].to_vec();
+        }
+
+        trace!(
+            "Resampled {} frames ({}Hz -> {}Hz) to {} frames",
+            in_frames, self.src_rate, self.dst_rate, out_len
+        );
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_passthrough() {
+        let mut resampler = Resampler::new(48000, 2, 48000, 2);
+        assert!(resampler.is_passthrough());
+        let input = vec![0.1, 0.2, 0.3, 0.4];
+        assert_eq!(resampler.process(&input), input);
+    }
+
+    #[test]
+    fn test_mono_to_stereo() {
+        let mut resampler = Resampler::new(48000, 1, 48000, 2);
+        let input = vec![0.5, -0.5];
+        let output = resampler.process(&input);
+        assert_eq!(output, vec![0.5, 0.5, -0.5, -0.5]);
+    }
+
+    #[test]
+    fn test_stereo_to_mono() {
+        let mut resampler = Resampler::new(48000, 2, 48000, 1);
+        let input = vec![1.0, 0.0, 0.5, 0.5];
+        let output = resampler.process(&input);
+        assert_eq!(output, vec![0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_rate_conversion_length_matches_ratio() {
+        let mut resampler = Resampler::new(44100, 2, 48000, 2);
+        let input = vec![0.0f32; 44100 * 2]; // 1 second of stereo silence
+        let output = resampler.process(&input);
+
+        // Should be close to 48000 frames (±1 due to fractional carry)
+        let out_frames = output.len() / 2;
+        assert!((out_frames as i64 - 48000).abs() <= 1);
+    }
+
+    #[test]
+    fn test_rate_conversion_no_drift_over_many_chunks() {
+        let mut resampler = Resampler::new(44100, 1, 48000, 1);
+        let ratio = 48000.0 / 44100.0;
+        let chunk_frames = 1152; // typical MP2 frame size
+        let mut total_in = 0usize;
+        let mut total_out = 0usize;
+
+        for _ in 0..100 {
+            let input = vec![0.0f32; chunk_frames];
+            let output = resampler.process(&input);
+            total_in += chunk_frames;
+            total_out += output.len();
+        }
+
+        let expected = (total_in as f64 * ratio) as i64;
+        assert!((total_out as i64 - expected).abs() <= 1);
+    }
+}
@@ -0,0 +1,235 @@
+//! RTP Payloader for MP4A-LATM (RFC 3016)
+//!
+//! Wraps the remuxed AAC access units the muxer already produces for
+//! `mux_audio` into RTP packets instead of (or alongside) MPEG-TS, for
+//! players that want low-latency RTP/RTSP delivery rather than a TS
+//! container. Each access unit is repacked as a LATM `AudioMuxElement`
+//! (reusing `muxer::adts_to_latm`), split across one or more RTP packets
+//! if it exceeds the configured MTU, and timestamped on the audio
+//! sample-rate clock per RFC 3016 (not the 90 kHz clock the rest of this
+//! crate uses for TS muxing).
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! use crate::rtp::{RtpAacPayloader, RtpAacPayloaderConfig};
+//!
+//! let mut payloader = RtpAacPayloader::new(RtpAacPayloaderConfig::default());
+//! for packet in payloader.payload_frame(&aac_data, pts)? {
+//!     socket.send(&packet)?;
+//! }
+//! ```
+
+use anyhow::Result;
+use tracing::trace;
+
+use crate::muxer::adts_to_latm;
+
+/// RTP header size in bytes (no extension, no CSRCs)
+const RTP_HEADER_SIZE: usize = 12;
+
+/// RTP version this payloader emits (always 2)
+const RTP_VERSION: u8 = 2;
+
+/// Configuration for [`RtpAacPayloader`]
+#[derive(Debug, Clone)]
+pub struct RtpAacPayloaderConfig {
+    /// Dynamic RTP payload type (96-127) negotiated out-of-band (e.g. SDP)
+    pub payload_type: u8,
+
+    /// Synchronization source identifier
+    pub ssrc: u32,
+
+    /// Audio sample rate (Hz) the RTP timestamp clock runs at
+    pub sample_rate: u32,
+
+    /// Maximum RTP packet size in bytes, including the 12-byte header;
+    /// access units larger than this are fragmented across multiple packets
+    pub mtu: usize,
+}
+
+impl Default for RtpAacPayloaderConfig {
+    fn default() -> Self {
+        Self {
+            payload_type: 96,
+            ssrc: 0x1234_5678,
+            sample_rate: 48000,
+            mtu: 1400,
+        }
+    }
+}
+
+/// RFC 3016 MP4A-LATM RTP payloader
+///
+/// One instance per outgoing audio stream; sequence numbers increment
+/// across calls to `payload_frame`.
+pub struct RtpAacPayloader {
+    config: RtpAacPayloaderConfig,
+    sequence: u16,
+}
+
+impl RtpAacPayloader {
+    /// Create a new payloader, starting the sequence number at 0
+    pub fn new(config: RtpAacPayloaderConfig) -> Self {
+        Self {
+            config,
+            sequence: 0,
+        }
+    }
+
+    /// Repack one ADTS-framed AAC access unit into RTP packets
+    ///
+    /// # Arguments
+    ///
+    /// * `adts_frame` - ADTS-framed AAC access unit (same input `mux_audio` accepts)
+    /// * `pts` - Presentation Time Stamp on the 90 kHz clock used elsewhere
+    ///   in this crate; converted to `config.sample_rate` for the RTP
+    ///   timestamp field
+    ///
+    /// # Returns
+    ///
+    /// One RTP packet per fragment, in transmission order, with the marker
+    /// bit set on the last fragment and all fragments sharing one timestamp.
+    pub fn payload_frame(&mut self, adts_frame: &[u8], pts: u64) -> Result<Vec<Vec<u8>>> {
+        let latm_frame = adts_to_latm(adts_frame)?;
+        // RFC 3016 carries the bare AudioMuxElement; the 3-byte LOAS
+        // transport sync is redundant once RTP delineates packet boundaries.
+        let payload = &latm_frame[3..];
+
+        let timestamp = ((pts as u128 * self.config.sample_rate as u128) / 90_000) as u32;
+        let mtu_payload = self.config.mtu.saturating_sub(RTP_HEADER_SIZE).max(1);
+
+        let mut packets = Vec::new();
+        let mut offset = 0;
+        while offset < payload.len() {
+            let remaining = payload.len() - offset;
+            let chunk_len = remaining.min(mtu_payload);
+            let is_last = offset + chunk_len == payload.len();
+
+            let mut packet = Vec::with_capacity(RTP_HEADER_SIZE + chunk_len);
+            packet.push(RTP_VERSION << 6); // V=2, P=0, X=0, CC=0
+            packet.push(((is_last as u8) << 7) | (self.config.payload_type & 0x7F));
+            packet.push((self.sequence >> 8) as u8);
+            packet.push((self.sequence & 0xFF) as u8);
+            packet.extend_from_slice(&timestamp.to_be_bytes());
+            packet.extend_from_slice(&self.config.ssrc.to_be_bytes());
+            packet.extend_from_slice(&payload[offset..offset + chunk_len]);
+
+            self.sequence = self.sequence.wrapping_add(1);
+            packets.push(packet);
+            offset += chunk_len;
+        }
+
+        trace!(
+            "Payloaded AAC access unit ({} bytes) into {} RTP packet(s)",
+            payload.len(),
+            packets.len()
+        );
+
+        Ok(packets)
+    }
+
+    /// `a=rtpmap` media description value for this stream, e.g.
+    /// `96 MP4A-LATM/48000`, for SDP offered alongside the RTP session
+    pub fn rtpmap(&self) -> String {
+        format!(
+            "{} MP4A-LATM/{}",
+            self.config.payload_type, self.config.sample_rate
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_adts_frame(payload_len: usize) -> Vec<u8> {
+        let frame_length = 7 + payload_len;
+        let mut frame = vec![
+            0xFF,
+            0xF1,
+            (1 << 6) | (3 << 2) | (2 >> 2), // AAC-LC, 48kHz, stereo
+            ((2 & 0x03) << 6) | ((frame_length >> 11) as u8),
+            ((frame_length >> 3) & 0xFF) as u8,
+            (((frame_length & 0x07) << 5) | 0x1F) as u8,
+            0xFC,
+        ];
+        frame.extend(vec![0xAB; payload_len]);
+        frame
+    }
+
+    #[test]
+    fn test_payload_frame_sets_marker_on_single_packet() {
+        let mut payloader = RtpAacPayloader::new(RtpAacPayloaderConfig::default());
+        let frame = build_adts_frame(10);
+
+        let packets = payloader.payload_frame(&frame, 90_000).unwrap();
+        assert_eq!(packets.len(), 1);
+
+        let packet = &packets[0];
+        assert_eq!(packet[0], 0x80); // V=2, no padding/extension/CSRC
+        assert_eq!(packet[1] & 0x80, 0x80); // Marker set on the only fragment
+        assert_eq!(packet[1] & 0x7F, 96); // Default payload type
+    }
+
+    #[test]
+    fn test_payload_frame_increments_sequence_number() {
+        let mut payloader = RtpAacPayloader::new(RtpAacPayloaderConfig::default());
+        let frame = build_adts_frame(10);
+
+        let first = payloader.payload_frame(&frame, 90_000).unwrap();
+        let second = payloader.payload_frame(&frame, 180_000).unwrap();
+
+        let first_seq = ((first[0][2] as u16) << 8) | first[0][3] as u16;
+        let second_seq = ((second[0][2] as u16) << 8) | second[0][3] as u16;
+        assert_eq!(second_seq, first_seq.wrapping_add(1));
+    }
+
+    #[test]
+    fn test_payload_frame_converts_pts_to_sample_rate_clock() {
+        let config = RtpAacPayloaderConfig {
+            sample_rate: 48000,
+            ..RtpAacPayloaderConfig::default()
+        };
+        let mut payloader = RtpAacPayloader::new(config);
+        let frame = build_adts_frame(10);
+
+        // 90 kHz PTS of one second should become exactly one second of samples
+        let packets = payloader.payload_frame(&frame, 90_000).unwrap();
+        let timestamp = u32::from_be_bytes(packets[0][4..8].try_into().unwrap());
+        assert_eq!(timestamp, 48000);
+    }
+
+    #[test]
+    fn test_payload_frame_fragments_across_mtu_and_shares_timestamp() {
+        let config = RtpAacPayloaderConfig {
+            mtu: RTP_HEADER_SIZE + 20, // force small fragments
+            ..RtpAacPayloaderConfig::default()
+        };
+        let mut payloader = RtpAacPayloader::new(config);
+        let frame = build_adts_frame(100);
+
+        let packets = payloader.payload_frame(&frame, 90_000).unwrap();
+        assert!(packets.len() > 1);
+
+        let first_timestamp = u32::from_be_bytes(packets[0][4..8].try_into().unwrap());
+        for (i, packet) in packets.iter().enumerate() {
+            let timestamp = u32::from_be_bytes(packet[4..8].try_into().unwrap());
+            assert_eq!(timestamp, first_timestamp);
+
+            let is_last = i == packets.len() - 1;
+            assert_eq!(packet[1] & 0x80 != 0, is_last);
+        }
+    }
+
+    #[test]
+    fn test_rtpmap_reflects_config() {
+        let config = RtpAacPayloaderConfig {
+            payload_type: 110,
+            sample_rate: 44100,
+            ..RtpAacPayloaderConfig::default()
+        };
+        let payloader = RtpAacPayloader::new(config);
+        assert_eq!(payloader.rtpmap(), "110 MP4A-LATM/44100");
+    }
+}
@@ -5,24 +5,148 @@
 
 use axum::{
     body::Body,
+    extract::ws::{Message, WebSocket, WebSocketUpgrade},
     extract::Query,
-    http::{header, StatusCode},
-    response::{IntoResponse, Response},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Redirect, Response},
     Json,
 };
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use tokio::process::Command;
-use tracing::{error, info, warn};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
 
-use crate::metrics::MetricsGuard;
+use crate::fanout::{FanoutRegistry, TranscodeKey};
+use crate::hls::SessionManager;
+use crate::hwaccel::HwAccel;
+use crate::jobs::{JobId, JobsRegistry};
+use crate::live_sessions::LiveSessionsRegistry;
+use crate::metrics::{self, MetricsGuard};
 use crate::transcoder::{TranscoderConfig, VaapiTranscoder};
 
 /// Application state shared across handlers
 pub struct AppState {
+    /// `config.hwaccel` is the backend new transcode requests use by
+    /// default - set by the runtime probe in `main.rs`/`ffi.rs` unless the
+    /// operator pinned one via `HWACCEL`. A request can still ask for a
+    /// different one of `available_hwaccels` (see [`select_hwaccel`]).
     pub config: TranscoderConfig,
+
+    /// Kept for backward compatibility with existing `/health` consumers;
+    /// equivalent to `available_hwaccels.contains(&HwAccel::Vaapi)`.
     pub vaapi_available: bool,
+
+    /// Backends this host's FFmpeg build can actually use, in preference
+    /// order, as found by [`crate::hwaccel::probe_available`]; always
+    /// includes [`HwAccel::Software`] as the last, universal entry.
+    pub available_hwaccels: Vec<HwAccel>,
+
     pub metrics_handle: metrics_exporter_prometheus::PrometheusHandle,
+
+    /// Cancelled once the server begins graceful shutdown. Handlers clone
+    /// this into their transcoder call so they can stop reading their
+    /// source/body and terminate their FFmpeg child early instead of
+    /// waiting for the stream to end naturally.
+    pub shutdown: CancellationToken,
+
+    /// Count of in-flight transcode streams, so `shutdown_signal` can wait
+    /// (with a bounded timeout) for them to drain rather than aborting them.
+    pub active_streams: Arc<AtomicUsize>,
+
+    /// Registry of in-flight shared transcodes, for coalescing concurrent
+    /// requests for the same source onto one FFmpeg/VAAPI pipeline.
+    pub fanout: FanoutRegistry,
+
+    /// Registry of background VOD transcode jobs
+    pub jobs: JobsRegistry,
+
+    /// Registry of live HLS sessions (one FFmpeg process per distinct
+    /// source + encode settings, segmented for seekable/resumable playback)
+    pub hls: SessionManager,
+
+    /// Progress and stall tracking for running continuous-pipe live
+    /// sessions (one entry per distinct source + encode settings, shared
+    /// across its `fanout`-coalesced viewers); see `GET /sessions`
+    pub live_sessions: LiveSessionsRegistry,
+}
+
+/// RAII guard for one in-flight transcode stream
+///
+/// Increments `AppState::active_streams` (and the Prometheus active-sessions
+/// gauge) for the handler's lifetime, so graceful shutdown can see how many
+/// streams it's waiting to drain.
+pub struct ActiveStreamGuard {
+    counter: Arc<AtomicUsize>,
+}
+
+impl ActiveStreamGuard {
+    pub fn new(counter: Arc<AtomicUsize>) -> Self {
+        let count = counter.fetch_add(1, Ordering::SeqCst) + 1;
+        metrics::set_active_sessions(count);
+        Self { counter }
+    }
+}
+
+impl Drop for ActiveStreamGuard {
+    fn drop(&mut self) {
+        let count = self.counter.fetch_sub(1, Ordering::SeqCst) - 1;
+        metrics::set_active_sessions(count);
+    }
+}
+
+/// Future for `axum::serve(..).with_graceful_shutdown(..)` that resolves on
+/// SIGTERM or Ctrl+C
+///
+/// Cancels `shutdown` so in-flight handlers can observe it (see
+/// `AppState::shutdown`), then waits up to `drain_timeout` for
+/// `active_streams` to reach zero before returning, so the listener isn't
+/// torn down out from under streams that are still draining their FFmpeg
+/// child.
+pub async fn shutdown_signal(
+    shutdown: CancellationToken,
+    active_streams: Arc<AtomicUsize>,
+    drain_timeout: Duration,
+) {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    tokio::select! {
+        _ = ctrl_c => info!("Received Ctrl+C, starting graceful shutdown"),
+        _ = terminate => info!("Received SIGTERM, starting graceful shutdown"),
+    }
+
+    shutdown.cancel();
+
+    let drained = tokio::time::timeout(drain_timeout, async {
+        while active_streams.load(Ordering::SeqCst) > 0 {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await;
+
+    if drained.is_err() {
+        warn!(
+            "Graceful shutdown timed out after {:?} with {} stream(s) still active",
+            drain_timeout,
+            active_streams.load(Ordering::SeqCst)
+        );
+    } else {
+        info!("All transcode streams drained, shutting down");
+    }
 }
 
 /// Health check response
@@ -30,6 +154,7 @@ pub struct AppState {
 pub struct HealthResponse {
     pub status: String,
     pub vaapi_available: bool,
+    pub available_hwaccels: Vec<HwAccel>,
     pub version: String,
 }
 
@@ -47,25 +172,39 @@ pub struct TranscodeParams {
     pub video_bitrate: Option<String>,
     #[serde(default)]
     pub audio_bitrate: Option<String>,
+    /// Request a specific backend (`"vaapi"`, `"nvenc"`, `"qsv"`,
+    /// `"software"`) instead of `state.config.hwaccel`'s probed default; see
+    /// [`select_hwaccel`]
+    #[serde(default)]
+    pub hwaccel: Option<String>,
 }
 
-/// Check if VAAPI hardware acceleration is available
-pub async fn check_vaapi() -> bool {
-    let output = Command::new("vainfo").output().await;
-
-    match output {
-        Ok(output) if output.status.success() => {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            info!("VAAPI check output:\n{}", stdout);
-            true
-        }
-        _ => {
-            warn!("vainfo command failed - VAAPI might not be available");
-            false
+/// Pick the backend a request actually gets: `requested` if it parses and
+/// is in `available`, otherwise the server's probed default
+///
+/// An unknown or currently-unavailable `requested` value falls back rather
+/// than failing the request outright - the same graceful-degradation
+/// approach `available_hwaccels` exists to enable in the first place.
+fn select_hwaccel(default: HwAccel, available: &[HwAccel], requested: Option<&str>) -> HwAccel {
+    match requested.and_then(HwAccel::parse) {
+        Some(requested) if available.contains(&requested) => requested,
+        Some(requested) => {
+            warn!(
+                "Requested hwaccel {:?} not available on this host, using {:?}",
+                requested, default
+            );
+            default
         }
+        None => default,
     }
 }
 
+/// Response to a successful `POST /transcode/jobs`
+#[derive(Debug, Serialize)]
+pub struct JobSubmitted {
+    pub id: JobId,
+}
+
 /// Health check handler
 pub async fn health_handler(
     axum::extract::State(state): axum::extract::State<Arc<AppState>>,
@@ -73,6 +212,7 @@ pub async fn health_handler(
     Json(HealthResponse {
         status: "ok".to_string(),
         vaapi_available: state.vaapi_available,
+        available_hwaccels: state.available_hwaccels.clone(),
         version: env!("CARGO_PKG_VERSION").to_string(),
     })
 }
@@ -91,25 +231,21 @@ pub async fn metrics_handler(
 }
 
 /// HTTP GET transcode handler (source_url parameter)
+///
+/// This serves a live FFmpeg encode with no fixed total length, so unlike
+/// [`job_output_handler`] it can't honor a `Range` request header - there's
+/// no file to seek within, only bytes the encoder hasn't produced yet. Per
+/// RFC 7233 a server may ignore `Range` and return a full `200` response,
+/// which is what happens here; `Accept-Ranges: none` tells clients up front
+/// not to expect partial-content support on this endpoint.
 pub async fn transcode_handler(
     axum::extract::State(state): axum::extract::State<Arc<AppState>>,
     Query(params): Query<TranscodeParams>,
 ) -> Response {
     let _guard = MetricsGuard::new();
+    let _active_guard = ActiveStreamGuard::new(state.active_streams.clone());
     info!("Transcode request: source_url={}", params.source_url);
 
-    if !state.vaapi_available {
-        warn!("VAAPI not available, cannot transcode");
-        _guard.error();
-        return (
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(ErrorResponse {
-                error: "GPU acceleration not available".to_string(),
-            }),
-        )
-            .into_response();
-    }
-
     // Override config with request params
     let mut config = state.config.clone();
     if let Some(vb) = params.video_bitrate {
@@ -118,12 +254,23 @@ pub async fn transcode_handler(
     if let Some(ab) = params.audio_bitrate {
         config.audio_bitrate = ab;
     }
+    config.hwaccel = select_hwaccel(
+        config.hwaccel,
+        &state.available_hwaccels,
+        params.hwaccel.as_deref(),
+    );
 
-    // Create transcoder
+    // Coalesce concurrent requests for the same source + encode settings
+    // onto one shared VaapiTranscoder instead of spawning one per request.
+    let key = TranscodeKey::new(&params.source_url, &config);
     let transcoder = VaapiTranscoder::new(config);
 
-    // Start transcoding
-    match transcoder.transcode_stream(&params.source_url).await {
+    // Start (or attach to) the shared transcode
+    match state
+        .fanout
+        .subscribe(key, transcoder, state.shutdown.clone(), &state.live_sessions)
+        .await
+    {
         Ok(stream) => {
             _guard.success();
 
@@ -132,6 +279,7 @@ pub async fn transcode_handler(
                 (header::CONTENT_TYPE, "video/mp2t"),
                 (header::CACHE_CONTROL, "no-cache, no-store, must-revalidate"),
                 (header::CONNECTION, "close"),
+                (header::ACCEPT_RANGES, "none"),
             ];
 
             (StatusCode::OK, headers, Body::from_stream(stream)).into_response()
@@ -149,3 +297,349 @@ pub async fn transcode_handler(
         }
     }
 }
+
+/// `GET /transcode/ws` - WebSocket transcode stream for browser clients
+///
+/// Upgrades to a WebSocket and pushes mpegts output as binary frames over
+/// the same fan-out pipeline as [`transcode_handler`], so a WebSocket
+/// viewer and an HTTP viewer of the same source + encode settings share one
+/// FFmpeg encode. A `"stop"` text frame or a close frame ends the stream
+/// early. Because the underlying encode may be shared with other viewers,
+/// there's no per-client pause or bitrate-change control here - either
+/// would stall or re-encode the stream for everyone attached to the same
+/// key. A client that wants different settings should close and reconnect
+/// with different query parameters instead.
+pub async fn transcode_ws_handler(
+    axum::extract::State(state): axum::extract::State<Arc<AppState>>,
+    Query(params): Query<TranscodeParams>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    ws.on_upgrade(move |socket| handle_transcode_ws(socket, state, params))
+}
+
+async fn handle_transcode_ws(mut socket: WebSocket, state: Arc<AppState>, params: TranscodeParams) {
+    let _guard = MetricsGuard::new();
+    let _active_guard = ActiveStreamGuard::new(state.active_streams.clone());
+    info!("WebSocket transcode request: source_url={}", params.source_url);
+
+    let mut config = state.config.clone();
+    if let Some(vb) = params.video_bitrate {
+        config.video_bitrate = vb;
+    }
+    if let Some(ab) = params.audio_bitrate {
+        config.audio_bitrate = ab;
+    }
+    config.hwaccel = select_hwaccel(
+        config.hwaccel,
+        &state.available_hwaccels,
+        params.hwaccel.as_deref(),
+    );
+
+    let key = TranscodeKey::new(&params.source_url, &config);
+    let transcoder = VaapiTranscoder::new(config);
+
+    let mut stream = match state
+        .fanout
+        .subscribe(key, transcoder, state.shutdown.clone(), &state.live_sessions)
+        .await
+    {
+        Ok(stream) => stream,
+        Err(e) => {
+            error!("WebSocket transcode error: {}", e);
+            _guard.error();
+            let _ = socket.send(Message::Text(format!("error: {}", e))).await;
+            return;
+        }
+    };
+    _guard.success();
+
+    loop {
+        tokio::select! {
+            biased;
+
+            _ = state.shutdown.cancelled() => {
+                info!("Server shutting down, closing transcode websocket");
+                break;
+            }
+            incoming = socket.recv() => {
+                match incoming {
+                    Some(Ok(Message::Text(text))) if text.trim() == "stop" => {
+                        info!("Client requested stop, closing transcode websocket");
+                        break;
+                    }
+                    Some(Ok(Message::Text(text))) => {
+                        debug!("Ignoring unsupported transcode control message: {}", text);
+                        let _ = socket
+                            .send(Message::Text(
+                                "error: unsupported control message (only \"stop\" is supported)".into(),
+                            ))
+                            .await;
+                    }
+                    Some(Ok(Message::Close(_))) | None => {
+                        info!("Transcode websocket client disconnected");
+                        break;
+                    }
+                    Some(Ok(_)) => {} // ignore binary/ping/pong frames from the client
+                    Some(Err(e)) => {
+                        warn!("Transcode websocket receive error: {}", e);
+                        break;
+                    }
+                }
+            }
+            chunk = stream.next() => {
+                match chunk {
+                    Some(Ok(bytes)) => {
+                        if socket.send(Message::Binary(bytes.to_vec())).await.is_err() {
+                            info!("Transcode websocket send failed, client likely gone");
+                            break;
+                        }
+                    }
+                    Some(Err(e)) => {
+                        error!("Transcode stream error: {}", e);
+                        let _ = socket.send(Message::Text(format!("error: {}", e))).await;
+                        break;
+                    }
+                    None => {
+                        info!("Transcode stream ended");
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    let _ = socket.send(Message::Close(None)).await;
+}
+
+/// `POST /transcode/jobs` - submit a VOD transcode job, returning its id
+/// immediately while it runs in the background
+pub async fn submit_job_handler(
+    axum::extract::State(state): axum::extract::State<Arc<AppState>>,
+    Query(params): Query<TranscodeParams>,
+) -> Response {
+    info!("Job submit request: source_url={}", params.source_url);
+
+    let mut config = state.config.clone();
+    if let Some(vb) = params.video_bitrate {
+        config.video_bitrate = vb;
+    }
+    if let Some(ab) = params.audio_bitrate {
+        config.audio_bitrate = ab;
+    }
+    config.hwaccel = select_hwaccel(
+        config.hwaccel,
+        &state.available_hwaccels,
+        params.hwaccel.as_deref(),
+    );
+    let output_dir = std::path::PathBuf::from(config.job_output_dir.clone());
+
+    let transcoder = VaapiTranscoder::new(config);
+    let id = state
+        .jobs
+        .submit(transcoder, params.source_url, output_dir)
+        .await;
+
+    (StatusCode::ACCEPTED, Json(JobSubmitted { id })).into_response()
+}
+
+/// `GET /transcode/jobs/{id}` - poll a job's status
+pub async fn job_status_handler(
+    axum::extract::State(state): axum::extract::State<Arc<AppState>>,
+    axum::extract::Path(id): axum::extract::Path<JobId>,
+) -> Response {
+    match state.jobs.status(id).await {
+        Some(status) => (StatusCode::OK, Json(status)).into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("No such job: {}", id),
+            }),
+        )
+            .into_response(),
+    }
+}
+
+/// `GET /transcode/jobs/{id}/output` - stream a finished job's output file
+///
+/// Honors a `Range: bytes=start-end` request header so players can seek
+/// and clients can resume interrupted downloads, via [`crate::range::serve_file_range`].
+pub async fn job_output_handler(
+    axum::extract::State(state): axum::extract::State<Arc<AppState>>,
+    axum::extract::Path(id): axum::extract::Path<JobId>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(output_path) = state.jobs.output_path(id).await else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Job {} has no output available (not done, or unknown)", id),
+            }),
+        )
+            .into_response();
+    };
+
+    let file = match tokio::fs::File::open(&output_path).await {
+        Ok(file) => file,
+        Err(e) => {
+            error!("Failed to open job {} output {}: {}", id, output_path.display(), e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Failed to open job output: {}", e),
+                }),
+            )
+                .into_response();
+        }
+    };
+
+    let total_len = match file.metadata().await {
+        Ok(meta) => meta.len(),
+        Err(e) => {
+            error!("Failed to stat job {} output {}: {}", id, output_path.display(), e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Failed to stat job output: {}", e),
+                }),
+            )
+                .into_response();
+        }
+    };
+
+    let range_header = headers
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok());
+
+    crate::range::serve_file_range(file, total_len, "video/mp2t", range_header).await
+}
+
+/// `GET /transcode/hls` - start (or attach to) a live HLS session for
+/// `source_url`, redirecting the client to its playlist
+///
+/// Concurrent requests for the same source + encode settings are coalesced
+/// onto one running FFmpeg session the same way [`transcode_handler`]
+/// coalesces continuous-pipe viewers - see [`crate::hls::SessionManager`].
+pub async fn hls_handler(
+    axum::extract::State(state): axum::extract::State<Arc<AppState>>,
+    Query(params): Query<TranscodeParams>,
+) -> Response {
+    info!("HLS session request: source_url={}", params.source_url);
+
+    let mut config = state.config.clone();
+    if let Some(vb) = params.video_bitrate {
+        config.video_bitrate = vb;
+    }
+    if let Some(ab) = params.audio_bitrate {
+        config.audio_bitrate = ab;
+    }
+    config.hwaccel = select_hwaccel(
+        config.hwaccel,
+        &state.available_hwaccels,
+        params.hwaccel.as_deref(),
+    );
+
+    let key = TranscodeKey::new(&params.source_url, &config);
+    let base_dir = std::path::PathBuf::from(config.hls_dir.clone());
+    // Matches build_hls_ffmpeg_args' own ABR gate - the master playlist only
+    // exists when the ABR filter graph actually ran.
+    let playlist_name = if config.renditions.len() > 1 && config.hwaccel == HwAccel::Vaapi {
+        "master.m3u8"
+    } else {
+        "playlist.m3u8"
+    };
+    let transcoder = VaapiTranscoder::new(config);
+
+    let id = match state
+        .hls
+        .ensure(
+            key,
+            transcoder,
+            &params.source_url,
+            &base_dir,
+            state.shutdown.clone(),
+        )
+        .await
+    {
+        Ok(id) => id,
+        Err(e) => {
+            error!("HLS session error: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("Failed to start HLS session: {}", e),
+                }),
+            )
+                .into_response();
+        }
+    };
+
+    Redirect::temporary(&format!("/transcode/hls/{}/{}", id, playlist_name)).into_response()
+}
+
+/// `GET /transcode/hls/{id}/{file}` - serve one HLS session's playlist or a
+/// segment file
+///
+/// FFmpeg may still be writing `file` (most commonly the playlist, rewritten
+/// on every new segment) just after the session starts; a bounded number of
+/// short retries absorbs that race instead of making the client re-poll
+/// `/transcode/hls` for a playlist that usually appears within a second or
+/// two of the session starting.
+pub async fn hls_file_handler(
+    axum::extract::State(state): axum::extract::State<Arc<AppState>>,
+    axum::extract::Path((id, file)): axum::extract::Path<(Uuid, String)>,
+) -> Response {
+    let Some(path) = state.hls.resolve(id, &file).await else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("No such HLS session or file: {}/{}", id, file),
+            }),
+        )
+            .into_response();
+    };
+
+    const MAX_ATTEMPTS: u32 = 10;
+    let mut attempts = 0;
+    let bytes = loop {
+        match tokio::fs::read(&path).await {
+            Ok(bytes) => break bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && attempts < MAX_ATTEMPTS => {
+                attempts += 1;
+                tokio::time::sleep(Duration::from_millis(200)).await;
+            }
+            Err(e) => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(ErrorResponse {
+                        error: format!("HLS file not available: {}", e),
+                    }),
+                )
+                    .into_response();
+            }
+        }
+    };
+
+    let content_type = if file.ends_with(".m3u8") {
+        "application/vnd.apple.mpegurl"
+    } else {
+        "video/mp2t"
+    };
+
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, content_type),
+            (header::CACHE_CONTROL, "no-cache, no-store, must-revalidate"),
+        ],
+        bytes,
+    )
+        .into_response()
+}
+
+/// `GET /sessions` - list currently running live (continuous-pipe) sessions
+/// and their last-reported FFmpeg `-progress` stats
+pub async fn sessions_handler(
+    axum::extract::State(state): axum::extract::State<Arc<AppState>>,
+) -> Json<Vec<crate::live_sessions::LiveSessionStats>> {
+    Json(state.live_sessions.snapshot().await)
+}
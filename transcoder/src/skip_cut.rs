@@ -0,0 +1,133 @@
+//! Skip-Cut Buffer (Encoder-Delay / Priming-Sample Trimming)
+//!
+//! AAC-LC encoders introduce a priming delay (~1024-2112 samples) and
+//! MP2/AC3 decoders carry their own initial latency, so passing decoded
+//! PCM straight through shifts audio relative to the preserved PTS
+//! timeline and can produce an audible click at stream start. This
+//! module discards a configurable number of leading "priming" samples
+//! and defers the tail of the stream so trailing padding can be dropped
+//! at flush instead of emitted as audible garbage.
+//!
+//! Modeled on the stagefright `SkipCutBuffer` approach.
+
+/// Skip-Cut Buffer
+///
+/// Maintains `skip` and `cut` counts (in PCM sample frames). Leading
+/// frames are dropped as they arrive until `skip` reaches zero. A
+/// trailing window of `cut` frames is always held back rather than
+/// emitted immediately, since it isn't known whether it's genuine audio
+/// or encoder/decoder padding until more data confirms it wasn't the
+/// final chunk. On `flush`, the held tail is dropped.
+pub struct SkipCutBuffer {
+    channels: usize,
+    skip_remaining: usize,
+    cut_frames: usize,
+    held_tail: Vec<f32>,
+}
+
+impl SkipCutBuffer {
+    /// Create a new skip-cut buffer
+    ///
+    /// * `skip_frames` - leading PCM sample frames to discard (encoder priming delay)
+    /// * `cut_frames` - trailing PCM sample frames to hold back and drop at flush
+    /// * `channels` - interleaved channel count of the PCM this buffer processes
+    pub fn new(skip_frames: u32, cut_frames: u32, channels: u16) -> Self {
+        Self {
+            channels: channels.max(1) as usize,
+            skip_remaining: skip_frames as usize,
+            cut_frames: cut_frames as usize,
+            held_tail: Vec::new(),
+        }
+    }
+
+    /// Leading sample frames still pending discard
+    pub fn skip_remaining(&self) -> usize {
+        self.skip_remaining
+    }
+
+    /// Feed a chunk of interleaved PCM through the buffer
+    ///
+    /// Returns the subset of samples that are now safe to emit downstream.
+    /// May return an empty `Vec` if everything fed in was consumed by the
+    /// skip or is being held as part of the deferred cut window.
+    pub fn process(&mut self, input: &[f32]) -> Vec<f32> {
+        let channels = self.channels;
+        let mut frames = input.to_vec();
+
+        if self.skip_remaining > 0 {
+            let input_frames = frames.len() / channels;
+            let to_skip = self.skip_remaining.min(input_frames);
+            frames.drain(0..to_skip * channels);
+            self.skip_remaining -= to_skip;
+        }
+
+        if self.cut_frames == 0 {
+            return frames;
+        }
+
+        let mut combined = std::mem::take(&mut self.held_tail);
+        combined.extend(frames);
+
+        let combined_frames = combined.len() / channels;
+        if combined_frames <= self.cut_frames {
+            // Not enough data yet to know what's trailing padding; hold it all.
+            self.held_tail = combined;
+            return Vec::new();
+        }
+
+        let emit_frames = combined_frames - self.cut_frames;
+        self.held_tail = combined.split_off(emit_frames * channels);
+        combined
+    }
+
+    /// Flush the buffer at end of stream, discarding the held tail
+    /// (trailing encoder/decoder padding) instead of emitting it.
+    pub fn flush(&mut self) {
+        self.held_tail.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skip_leading_samples() {
+        let mut buf = SkipCutBuffer::new(2, 0, 1);
+        let output = buf.process(&[1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(output, vec![3.0, 4.0]);
+        assert_eq!(buf.skip_remaining(), 0);
+    }
+
+    #[test]
+    fn test_skip_spans_multiple_calls() {
+        let mut buf = SkipCutBuffer::new(3, 0, 1);
+        assert_eq!(buf.process(&[1.0, 2.0]), Vec::<f32>::new());
+        assert_eq!(buf.skip_remaining(), 1);
+        assert_eq!(buf.process(&[3.0, 4.0]), vec![4.0]);
+        assert_eq!(buf.skip_remaining(), 0);
+    }
+
+    #[test]
+    fn test_cut_holds_trailing_window() {
+        let mut buf = SkipCutBuffer::new(0, 2, 1);
+        // First 3 frames: only 1 is safe to emit (2 held as the cut window)
+        let output = buf.process(&[1.0, 2.0, 3.0]);
+        assert_eq!(output, vec![1.0]);
+
+        // More data arrives: the oldest held sample becomes safe to emit,
+        // and the last 2 frames (3.0, 4.0) are held as the new cut window
+        let output = buf.process(&[4.0]);
+        assert_eq!(output, vec![2.0]);
+    }
+
+    #[test]
+    fn test_flush_drops_held_tail() {
+        let mut buf = SkipCutBuffer::new(0, 2, 1);
+        buf.process(&[1.0, 2.0]); // both held, nothing emitted yet
+        buf.flush();
+        // Held tail discarded; nothing more emerges for it
+        let output = buf.process(&[3.0, 4.0, 5.0]);
+        assert_eq!(output, vec![3.0]);
+    }
+}
@@ -10,8 +10,11 @@ use std::task::{Context as TaskContext, Poll};
 use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader};
 use tokio::process::{Child, Command};
 use tokio_util::io::ReaderStream;
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 
+use crate::hwaccel::HwAccel;
+
 /// Wrapper that keeps the FFmpeg child process alive while streaming
 /// This ensures FFmpeg doesn't get killed when the Child handle is dropped
 struct ProcessStream {
@@ -74,6 +77,23 @@ impl Drop for ProcessStream {
     }
 }
 
+/// Send SIGTERM to `pid` once `shutdown` is cancelled
+///
+/// Lets graceful shutdown end an in-flight transcode's FFmpeg child promptly
+/// instead of waiting for the source/body to end naturally; the existing
+/// `ProcessStream::drop` hard-kill remains the backstop if FFmpeg doesn't
+/// exit in response.
+fn spawn_shutdown_watcher(pid: u32, shutdown: CancellationToken) {
+    tokio::spawn(async move {
+        shutdown.cancelled().await;
+        debug!("Graceful shutdown: sending SIGTERM to FFmpeg pid {}", pid);
+        let _ = Command::new("kill")
+            .args(["-TERM", &pid.to_string()])
+            .status()
+            .await;
+    });
+}
+
 /// Configuration for the VAAPI transcoder
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TranscoderConfig {
@@ -103,6 +123,144 @@ pub struct TranscoderConfig {
 
     /// FFmpeg path
     pub ffmpeg_path: String,
+
+    /// ffprobe path, used to look up a VOD job's input duration
+    pub ffprobe_path: String,
+
+    /// Directory VOD job output files are written to
+    pub job_output_dir: String,
+
+    /// How long a finished (done/failed) job is kept before the reaper
+    /// evicts it and deletes its output file
+    pub job_ttl_secs: u64,
+
+    /// Maximum accepted request body size in bytes for `POST
+    /// /transcode/stream`, enforced by `RequestBodyLimitLayer`
+    pub max_upload_bytes: usize,
+
+    /// Wall-clock cap, in seconds, on a single `POST /transcode/stream`
+    /// session; once exceeded, the FFmpeg child is terminated and the
+    /// already-streaming response simply ends (its status stays `200`)
+    pub stream_session_timeout_secs: u64,
+
+    /// Target segment duration, in seconds, for live HLS sessions (see
+    /// [`crate::hls::SessionManager`])
+    pub hls_segment_secs: u64,
+
+    /// Number of segments kept in a live HLS session's sliding-window
+    /// playlist
+    pub hls_list_size: usize,
+
+    /// Base directory each live HLS session's playlist/segments are written
+    /// under, one subdirectory per session id
+    pub hls_dir: String,
+
+    /// How long, in seconds, an HLS session may go unpolled before its
+    /// FFmpeg process is stopped and its segments deleted
+    pub hls_idle_timeout_secs: u64,
+
+    /// How long, in seconds, a live (continuous-pipe) session's
+    /// `out_time_ms` may go without advancing before it's considered
+    /// stalled and its FFmpeg child is terminated
+    pub stream_stall_timeout_secs: u64,
+
+    /// Adaptive-bitrate ladder for live HLS sessions (see
+    /// [`crate::hls::SessionManager`])
+    ///
+    /// With fewer than two entries, HLS sessions fall back to the single
+    /// `video_bitrate`/`video_codec` output `build_hls_ffmpeg_args` has
+    /// always produced. With two or more, one GPU decode is split via
+    /// `-filter_complex` into one `scale_vaapi`+encode branch per rendition,
+    /// feeding an HLS master playlist with one `#EXT-X-STREAM-INF` variant
+    /// per entry.
+    pub renditions: Vec<Rendition>,
+
+    /// Which encode backend FFmpeg targets (see [`crate::hwaccel`])
+    ///
+    /// Set from the `HWACCEL` env var at startup, then possibly overridden
+    /// by the runtime probe in `main.rs`/`ffi.rs` if the operator didn't set
+    /// one explicitly - see [`crate::hwaccel::probe_available`]. The
+    /// adaptive-bitrate ladder in `renditions` currently only has a
+    /// `build_abr_hls_ffmpeg_args` implementation for [`HwAccel::Vaapi`];
+    /// other backends fall back to the single-output HLS path.
+    pub hwaccel: HwAccel,
+}
+
+/// One variant in an adaptive-bitrate HLS ladder
+///
+/// `name` becomes the variant's stream name in `-var_stream_map` and the
+/// prefix of its playlist/segment filenames (e.g. `720p_playlist.m3u8`), so
+/// it must be safe to use as a bare filename component - no `/` or `..`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Rendition {
+    /// Short identifier for this rendition, e.g. `"1080p"`
+    pub name: String,
+    /// Output width in pixels, used by `scale_vaapi`
+    pub width: u32,
+    /// Output height in pixels, used by `scale_vaapi`
+    pub height: u32,
+    /// Video bitrate, e.g. `"5000k"`
+    pub video_bitrate: String,
+    /// Audio bitrate, e.g. `"192k"`
+    pub audio_bitrate: String,
+}
+
+/// The default three-rung ladder: 1080p/720p/480p, roughly halving bitrate
+/// at each step down
+fn default_rendition_ladder() -> Vec<Rendition> {
+    vec![
+        Rendition {
+            name: "1080p".to_string(),
+            width: 1920,
+            height: 1080,
+            video_bitrate: "5000k".to_string(),
+            audio_bitrate: "192k".to_string(),
+        },
+        Rendition {
+            name: "720p".to_string(),
+            width: 1280,
+            height: 720,
+            video_bitrate: "2800k".to_string(),
+            audio_bitrate: "128k".to_string(),
+        },
+        Rendition {
+            name: "480p".to_string(),
+            width: 854,
+            height: 480,
+            video_bitrate: "1200k".to_string(),
+            audio_bitrate: "96k".to_string(),
+        },
+    ]
+}
+
+/// Parse the `RENDITION_LADDER` env var: renditions separated by `,`, each
+/// `name:WxH:video_bitrate:audio_bitrate` (e.g.
+/// `1080p:1920x1080:5000k:192k,720p:1280x720:2800k:128k`)
+///
+/// Returns `None` (falling back to the default ladder) on any malformed
+/// entry rather than starting with a partially-parsed ladder.
+fn parse_rendition_ladder(spec: &str) -> Option<Vec<Rendition>> {
+    spec.split(',').map(parse_rendition).collect()
+}
+
+fn parse_rendition(entry: &str) -> Option<Rendition> {
+    let mut fields = entry.split(':');
+    let name = fields.next()?.to_string();
+    let dims = fields.next()?;
+    let (width, height) = dims.split_once('x')?;
+    let video_bitrate = fields.next()?.to_string();
+    let audio_bitrate = fields.next()?.to_string();
+    if fields.next().is_some() {
+        return None;
+    }
+
+    Some(Rendition {
+        name,
+        width: width.parse().ok()?,
+        height: height.parse().ok()?,
+        video_bitrate,
+        audio_bitrate,
+    })
 }
 
 impl Default for TranscoderConfig {
@@ -117,6 +275,18 @@ impl Default for TranscoderConfig {
             analyze_duration: 2_000_000, // 2 seconds
             probe_size: 2_000_000,       // 2 MB
             ffmpeg_path: "ffmpeg".to_string(),
+            ffprobe_path: "ffprobe".to_string(),
+            job_output_dir: "/tmp/xg2g-transcoder-jobs".to_string(),
+            job_ttl_secs: 300, // 5 minutes
+            max_upload_bytes: 2 * 1024 * 1024 * 1024, // 2 GiB
+            stream_session_timeout_secs: 6 * 60 * 60, // 6 hours
+            hls_segment_secs: 5,
+            hls_list_size: 6,
+            hls_dir: "/tmp/xg2g-transcoder-hls".to_string(),
+            hls_idle_timeout_secs: 60,
+            stream_stall_timeout_secs: 30,
+            renditions: default_rendition_ladder(),
+            hwaccel: HwAccel::Vaapi,
         }
     }
 }
@@ -144,6 +314,47 @@ impl TranscoderConfig {
                 .and_then(|s| s.parse().ok())
                 .unwrap_or(2_000_000),
             ffmpeg_path: std::env::var("FFMPEG_PATH").unwrap_or_else(|_| "ffmpeg".to_string()),
+            ffprobe_path: std::env::var("FFPROBE_PATH").unwrap_or_else(|_| "ffprobe".to_string()),
+            job_output_dir: std::env::var("JOB_OUTPUT_DIR")
+                .unwrap_or_else(|_| "/tmp/xg2g-transcoder-jobs".to_string()),
+            job_ttl_secs: std::env::var("JOB_TTL_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(300),
+            max_upload_bytes: std::env::var("MAX_UPLOAD_BYTES")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(2 * 1024 * 1024 * 1024),
+            stream_session_timeout_secs: std::env::var("STREAM_SESSION_TIMEOUT_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(6 * 60 * 60),
+            hls_segment_secs: std::env::var("HLS_SEGMENT_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(5),
+            hls_list_size: std::env::var("HLS_LIST_SIZE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(6),
+            hls_dir: std::env::var("HLS_DIR")
+                .unwrap_or_else(|_| "/tmp/xg2g-transcoder-hls".to_string()),
+            hls_idle_timeout_secs: std::env::var("HLS_IDLE_TIMEOUT_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(60),
+            stream_stall_timeout_secs: std::env::var("STREAM_STALL_TIMEOUT_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(30),
+            renditions: std::env::var("RENDITION_LADDER")
+                .ok()
+                .and_then(|s| parse_rendition_ladder(&s))
+                .unwrap_or_else(default_rendition_ladder),
+            hwaccel: std::env::var("HWACCEL")
+                .ok()
+                .and_then(|s| HwAccel::parse(&s))
+                .unwrap_or(HwAccel::Vaapi),
         }
     }
 }
@@ -158,10 +369,23 @@ impl VaapiTranscoder {
         Self { config }
     }
 
-    /// Build FFmpeg command line arguments for VAAPI transcoding
-    /// Minimal configuration tested to work reliably with live HTTP streams
-    fn build_ffmpeg_args(&self, input: &str) -> Vec<String> {
-        vec![
+    /// The configuration this transcoder was built with
+    pub fn config(&self) -> &TranscoderConfig {
+        &self.config
+    }
+
+    /// FFmpeg args shared between live (`pipe:1`) and job (file) transcodes;
+    /// the caller appends the output sink (and any progress-reporting
+    /// flags)
+    ///
+    /// Device initialization, the deinterlace/upload filter chain, and the
+    /// encoder name all come from `config.hwaccel`'s [`HwAccelBackend`](crate::hwaccel::HwAccelBackend)
+    /// rather than being hard-coded to VAAPI, so the same args work
+    /// unchanged on NVENC/QSV hosts or fall back to software encoding.
+    fn build_ffmpeg_args_prefix(&self, input: &str) -> Vec<String> {
+        let backend = self.config.hwaccel.backend();
+
+        let mut args = vec![
             "-hide_banner".to_string(),
             "-loglevel".to_string(),
             "error".to_string(),
@@ -173,44 +397,279 @@ impl VaapiTranscoder {
             // Fix Enigma2 timestamp issues
             "-fflags".to_string(),
             "+genpts+igndts+nobuffer".to_string(),
-            // Initialize VAAPI device BEFORE input (critical for live streams!)
+        ];
+
+        // Initialize the hw device BEFORE input (critical for live streams!)
+        args.extend(backend.device_init_args(&self.config.vaapi_device));
+
+        args.push("-i".to_string());
+        args.push(input.to_string());
+
+        // Video: CPU deinterlace -> backend-specific upload -> encode
+        args.push("-vf".to_string());
+        args.push(backend.filter_chain().to_string());
+        args.push("-c:v".to_string());
+        args.push(backend.encoder_name(&self.config.video_codec));
+        args.push("-b:v".to_string());
+        args.push(self.config.video_bitrate.clone());
+
+        // Audio: Simple AAC encoding with sync
+        args.push("-c:a".to_string());
+        args.push(self.config.audio_codec.clone());
+        args.push("-b:a".to_string());
+        args.push(self.config.audio_bitrate.clone());
+        args.push("-ac".to_string());
+        args.push(self.config.audio_channels.to_string());
+
+        // Audio/Video sync fixes
+        args.push("-async".to_string());
+        args.push("1".to_string());
+        args.push("-vsync".to_string());
+        args.push("1".to_string());
+        args.push("-max_muxing_queue_size".to_string());
+        args.push("9999".to_string());
+
+        args
+    }
+
+    /// Build FFmpeg command line arguments for a live VAAPI transcode,
+    /// streamed out over stdout
+    ///
+    /// `pipe:1` is already the muxed output, so `-progress` reports are
+    /// merged onto `pipe:2` (stderr) instead of getting a dedicated pipe of
+    /// their own; the stderr reader in `transcode_stream`/`transcode_stdin`
+    /// tells the two apart by parsing each line as a `-progress` key=value
+    /// pair first and falling back to a plain log line otherwise (see
+    /// [`parse_progress_line`]).
+    fn build_ffmpeg_args(&self, input: &str) -> Vec<String> {
+        let mut args = self.build_ffmpeg_args_prefix(input);
+        args.push("-progress".to_string());
+        args.push("pipe:2".to_string());
+        args.push("-f".to_string());
+        args.push("mpegts".to_string());
+        args.push("pipe:1".to_string());
+        args
+    }
+
+    /// Build FFmpeg command line arguments for a VOD job transcode, written
+    /// to `output_path` with progress reports on stdout (see
+    /// [`parse_progress_line`])
+    fn build_job_ffmpeg_args(&self, input: &str, output_path: &std::path::Path) -> Vec<String> {
+        let mut args = self.build_ffmpeg_args_prefix(input);
+        args.push("-progress".to_string());
+        args.push("pipe:1".to_string());
+        args.push("-nostats".to_string());
+        args.push("-f".to_string());
+        args.push("mpegts".to_string());
+        args.push(output_path.display().to_string());
+        args
+    }
+
+    /// Build FFmpeg command line arguments for a live HLS session, writing
+    /// a sliding-window playlist and MPEG-TS segments into `session_dir`
+    /// (see [`crate::hls::SessionManager`])
+    ///
+    /// With two or more entries in `config.renditions` on [`HwAccel::Vaapi`],
+    /// this defers to [`build_abr_hls_ffmpeg_args`] for a single
+    /// multi-rendition encode instead; fewer than two renditions, or a
+    /// non-VAAPI backend that `build_abr_hls_ffmpeg_args` doesn't support
+    /// yet, keeps the single-output behavior this always had.
+    ///
+    /// [`build_abr_hls_ffmpeg_args`]: VaapiTranscoder::build_abr_hls_ffmpeg_args
+    fn build_hls_ffmpeg_args(&self, input: &str, session_dir: &std::path::Path) -> Vec<String> {
+        if self.config.renditions.len() > 1 && self.config.hwaccel == HwAccel::Vaapi {
+            return self.build_abr_hls_ffmpeg_args(input, session_dir);
+        }
+        if self.config.renditions.len() > 1 {
+            debug!(
+                "hls: ABR ladder configured but hwaccel {:?} has no multi-rendition filter graph yet, using single-output HLS",
+                self.config.hwaccel
+            );
+        }
+
+        let mut args = self.build_ffmpeg_args_prefix(input);
+        args.push("-f".to_string());
+        args.push("hls".to_string());
+        args.push("-hls_time".to_string());
+        args.push(self.config.hls_segment_secs.to_string());
+        args.push("-hls_list_size".to_string());
+        args.push(self.config.hls_list_size.to_string());
+        args.push("-hls_flags".to_string());
+        args.push("delete_segments+append_list".to_string());
+        args.push("-hls_segment_type".to_string());
+        args.push("mpegts".to_string());
+        args.push("-hls_segment_filename".to_string());
+        args.push(session_dir.join("segment_%05d.ts").display().to_string());
+        args.push(session_dir.join("playlist.m3u8").display().to_string());
+        args
+    }
+
+    /// Build FFmpeg command line arguments for an adaptive-bitrate live HLS
+    /// session: one GPU decode, split via `-filter_complex` into one
+    /// `scale_vaapi`+encode branch per `config.renditions` entry, feeding an
+    /// HLS master playlist (`master.m3u8`) with one `#EXT-X-STREAM-INF`
+    /// variant per rendition
+    ///
+    /// Unlike [`build_hls_ffmpeg_args`]'s single-output case, this doesn't go
+    /// through [`build_ffmpeg_args_prefix`] - that prefix's `-vf`/`-c:v`/
+    /// `-b:v`/`-c:a`/`-b:a` apply to a single output stream, which doesn't
+    /// fit a multi-rendition filter graph - so the shared input/decode setup
+    /// is duplicated here instead.
+    ///
+    /// [`build_hls_ffmpeg_args`]: VaapiTranscoder::build_hls_ffmpeg_args
+    fn build_abr_hls_ffmpeg_args(&self, input: &str, session_dir: &std::path::Path) -> Vec<String> {
+        let renditions = &self.config.renditions;
+
+        let mut args = vec![
+            "-hide_banner".to_string(),
+            "-loglevel".to_string(),
+            "error".to_string(),
+            "-analyzeduration".to_string(),
+            self.config.analyze_duration.to_string(),
+            "-probesize".to_string(),
+            self.config.probe_size.to_string(),
+            "-fflags".to_string(),
+            "+genpts+igndts+nobuffer".to_string(),
             "-init_hw_device".to_string(),
             format!("vaapi=va:{}", self.config.vaapi_device),
-            // Input
             "-i".to_string(),
             input.to_string(),
-            // Video: CPU deinterlace -> GPU encode (minimal, stable config)
-            "-vf".to_string(),
-            "yadif,format=nv12,hwupload".to_string(),
-            "-c:v".to_string(),
-            format!("{}_vaapi", self.config.video_codec),
-            "-b:v".to_string(),
-            self.config.video_bitrate.clone(),
-            // Audio: Simple AAC encoding with sync
-            "-c:a".to_string(),
-            self.config.audio_codec.clone(),
-            "-b:a".to_string(),
-            self.config.audio_bitrate.clone(),
-            "-ac".to_string(),
-            self.config.audio_channels.to_string(),
-            // Audio/Video sync fixes
-            "-async".to_string(),
-            "1".to_string(),
-            "-vsync".to_string(),
-            "1".to_string(),
-            "-max_muxing_queue_size".to_string(),
-            "9999".to_string(),
-            // Output format
-            "-f".to_string(),
-            "mpegts".to_string(),
-            "pipe:1".to_string(),
-        ]
+        ];
+
+        // Split the deinterlaced, GPU-uploaded frame once per rendition and
+        // scale each branch to its target resolution on the VAAPI device.
+        let splits: Vec<String> = (0..renditions.len()).map(|i| format!("[v{i}]")).collect();
+        let mut filter_complex = format!(
+            "yadif,format=nv12,hwupload,split={}{}",
+            renditions.len(),
+            splits.join("")
+        );
+        for (i, rendition) in renditions.iter().enumerate() {
+            filter_complex.push_str(&format!(
+                ";[v{i}]scale_vaapi=w={}:h={}[vout{i}]",
+                rendition.width, rendition.height
+            ));
+        }
+        args.push("-filter_complex".to_string());
+        args.push(filter_complex);
+
+        let mut var_stream_map = Vec::with_capacity(renditions.len());
+        for (i, rendition) in renditions.iter().enumerate() {
+            args.push("-map".to_string());
+            args.push(format!("[vout{i}]"));
+            args.push(format!("-c:v:{i}"));
+            args.push(format!("{}_vaapi", self.config.video_codec));
+            args.push(format!("-b:v:{i}"));
+            args.push(rendition.video_bitrate.clone());
+
+            args.push("-map".to_string());
+            args.push("a:0".to_string());
+            args.push(format!("-c:a:{i}"));
+            args.push(self.config.audio_codec.clone());
+            args.push(format!("-b:a:{i}"));
+            args.push(rendition.audio_bitrate.clone());
+            args.push(format!("-ac:{i}"));
+            args.push(self.config.audio_channels.to_string());
+
+            var_stream_map.push(format!("v:{i},a:{i},name:{}", rendition.name));
+        }
+
+        args.push("-async".to_string());
+        args.push("1".to_string());
+        args.push("-vsync".to_string());
+        args.push("1".to_string());
+        args.push("-max_muxing_queue_size".to_string());
+        args.push("9999".to_string());
+
+        args.push("-f".to_string());
+        args.push("hls".to_string());
+        args.push("-hls_time".to_string());
+        args.push(self.config.hls_segment_secs.to_string());
+        args.push("-hls_list_size".to_string());
+        args.push(self.config.hls_list_size.to_string());
+        args.push("-hls_flags".to_string());
+        args.push("delete_segments+append_list".to_string());
+        args.push("-hls_segment_type".to_string());
+        args.push("mpegts".to_string());
+        args.push("-master_pl_name".to_string());
+        args.push("master.m3u8".to_string());
+        args.push("-var_stream_map".to_string());
+        args.push(var_stream_map.join(" "));
+        args.push("-hls_segment_filename".to_string());
+        args.push(
+            session_dir
+                .join("%v_segment_%05d.ts")
+                .display()
+                .to_string(),
+        );
+        args.push(session_dir.join("%v_playlist.m3u8").display().to_string());
+        args
+    }
+
+    /// Start a live HLS session, writing its playlist and segments into
+    /// `session_dir` until the returned child is killed or `shutdown` fires
+    ///
+    /// Unlike [`transcode_stream`]/[`transcode_stdin`] there's no stdout to
+    /// stream back to the caller - FFmpeg writes straight to `session_dir`,
+    /// and it's up to the caller (see [`crate::hls::SessionManager`]) to
+    /// serve the playlist/segments it produces there and to kill the
+    /// returned child once the session is torn down.
+    ///
+    /// [`transcode_stream`]: VaapiTranscoder::transcode_stream
+    /// [`transcode_stdin`]: VaapiTranscoder::transcode_stdin
+    pub async fn transcode_hls(
+        &self,
+        source_url: &str,
+        session_dir: &std::path::Path,
+        shutdown: CancellationToken,
+    ) -> Result<Child> {
+        let args = self.build_hls_ffmpeg_args(source_url, session_dir);
+
+        info!("Starting FFmpeg HLS session in {}", session_dir.display());
+        debug!("FFmpeg command: {} {}", self.config.ffmpeg_path, args.join(" "));
+
+        let mut child = Command::new(&self.config.ffmpeg_path)
+            .args(&args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to spawn FFmpeg process")?;
+
+        let stderr = child
+            .stderr
+            .take()
+            .context("Failed to get FFmpeg stderr")?;
+
+        // Log FFmpeg stderr in background
+        tokio::spawn(async move {
+            let reader = BufReader::new(stderr);
+            let mut lines = reader.lines();
+
+            while let Ok(Some(line)) = lines.next_line().await {
+                debug!("FFmpeg (HLS): {}", line);
+            }
+        });
+
+        if let Some(pid) = child.id() {
+            spawn_shutdown_watcher(pid, shutdown);
+        }
+
+        Ok(child)
     }
 
     /// Transcode a stream from a URL
+    ///
+    /// `on_progress` is called on every completed `-progress` block parsed
+    /// off stderr (see [`build_ffmpeg_args`]), e.g. to feed a
+    /// [`crate::live_sessions::LiveSessionsRegistry`] for stall detection
+    /// and the `/sessions` endpoint.
+    ///
+    /// [`build_ffmpeg_args`]: VaapiTranscoder::build_ffmpeg_args
     pub async fn transcode_stream(
         &self,
         source_url: &str,
+        shutdown: CancellationToken,
+        on_progress: impl Fn(ProgressUpdate) + Send + Sync + 'static,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes, std::io::Error>> + Send>>> {
         let args = self.build_ffmpeg_args(source_url);
 
@@ -234,16 +693,26 @@ impl VaapiTranscoder {
             .take()
             .context("Failed to get FFmpeg stderr")?;
 
-        // Log FFmpeg stderr in background
+        // Parse FFmpeg's merged stderr/-progress output in background,
+        // forwarding completed progress blocks and logging everything else
         tokio::spawn(async move {
             let reader = BufReader::new(stderr);
             let mut lines = reader.lines();
+            let mut block = ProgressBlock::default();
 
             while let Ok(Some(line)) = lines.next_line().await {
-                debug!("FFmpeg: {}", line);
+                match parse_progress_line(&line, &mut block) {
+                    ProgressLine::Complete(update) => on_progress(update),
+                    ProgressLine::Partial => {}
+                    ProgressLine::Other => debug!("FFmpeg: {}", line),
+                }
             }
         });
 
+        if let Some(pid) = child.id() {
+            spawn_shutdown_watcher(pid, shutdown);
+        }
+
         // Create ProcessStream that keeps child alive while streaming
         let stream = ProcessStream::new(child, stdout);
 
@@ -251,9 +720,15 @@ impl VaapiTranscoder {
     }
 
     /// Transcode a stream from stdin (for POST requests with body)
+    ///
+    /// See [`transcode_stream`] for `on_progress`.
+    ///
+    /// [`transcode_stream`]: VaapiTranscoder::transcode_stream
     pub async fn transcode_stdin(
         &self,
         input_body: Body,
+        shutdown: CancellationToken,
+        on_progress: impl Fn(ProgressUpdate) + Send + Sync + 'static,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<Bytes, std::io::Error>> + Send>>> {
         let args = self.build_ffmpeg_args("pipe:0");
 
@@ -278,33 +753,49 @@ impl VaapiTranscoder {
             .take()
             .context("Failed to get FFmpeg stderr")?;
 
-        // Log FFmpeg stderr in background
+        // Parse FFmpeg's merged stderr/-progress output in background,
+        // forwarding completed progress blocks and logging everything else
         tokio::spawn(async move {
             let reader = BufReader::new(stderr);
             let mut lines = reader.lines();
+            let mut block = ProgressBlock::default();
 
             while let Ok(Some(line)) = lines.next_line().await {
-                debug!("FFmpeg: {}", line);
+                match parse_progress_line(&line, &mut block) {
+                    ProgressLine::Complete(update) => on_progress(update),
+                    ProgressLine::Partial => {}
+                    ProgressLine::Other => debug!("FFmpeg: {}", line),
+                }
             }
         });
 
         // Pipe input body to FFmpeg stdin
+        let shutdown_for_body = shutdown.clone();
         tokio::spawn(async move {
             use http_body_util::BodyExt;
             let mut stream = input_body.into_data_stream();
 
-            while let Some(chunk) = stream.next().await {
-                match chunk {
-                    Ok(bytes) => {
-                        if let Err(e) = stdin.write_all(&bytes).await {
-                            error!("Error writing to FFmpeg stdin: {}", e);
-                            break;
-                        }
-                    }
-                    Err(e) => {
-                        error!("Error reading input body: {:?}", e);
+            loop {
+                tokio::select! {
+                    _ = shutdown_for_body.cancelled() => {
+                        debug!("Graceful shutdown: stopping FFmpeg stdin feed");
                         break;
                     }
+                    chunk = stream.next() => {
+                        match chunk {
+                            Some(Ok(bytes)) => {
+                                if let Err(e) = stdin.write_all(&bytes).await {
+                                    error!("Error writing to FFmpeg stdin: {}", e);
+                                    break;
+                                }
+                            }
+                            Some(Err(e)) => {
+                                error!("Error reading input body: {:?}", e);
+                                break;
+                            }
+                            None => break,
+                        }
+                    }
                 }
             }
 
@@ -312,9 +803,299 @@ impl VaapiTranscoder {
             drop(stdin);
         });
 
-        // Create stream from stdout
-        let stream = tokio_util::io::ReaderStream::new(stdout);
+        if let Some(pid) = child.id() {
+            spawn_shutdown_watcher(pid, shutdown);
+        }
+
+        // Create ProcessStream that keeps child alive while streaming
+        // (previously a bare ReaderStream, which let the FFmpeg child
+        // outlive the stream with no one left to wait on or kill it)
+        let stream = ProcessStream::new(child, stdout);
 
         Ok(Box::pin(stream))
     }
+
+    /// Probe a pushed byte stream in-process via a custom libav `AVIOContext`,
+    /// without writing it to a file or piping it through an FFmpeg subprocess
+    ///
+    /// [`transcode_stream`]/[`transcode_stdin`] above are the only ways this
+    /// crate actually encodes anything, and both do it by spawning the
+    /// `ffmpeg` CLI and feeding it bytes over a URL or a stdin pipe. Some
+    /// sources - a future MoQ/WebTransport ingest, for instance - never have
+    /// an OS pipe or a file to hand a subprocess in the first place; they're
+    /// just `Bytes` arriving in-process. [`crate::avio_reader::AvioReader`]
+    /// wraps exactly that in a custom `AVIOContext` so libav can demux
+    /// straight out of memory, and this method uses it to open an
+    /// `AVFormatContext` and report what libav detects (container, codecs,
+    /// duration) for a source that has no file an `ffprobe` subprocess could
+    /// otherwise inspect.
+    ///
+    /// This does **not** transcode anything - it only probes. Wiring a
+    /// decoded/demuxed `AVFormatContext` through to a VAAPI encode without a
+    /// subprocess boundary is real future work this method doesn't attempt;
+    /// every actual encode in this crate still goes through
+    /// [`transcode_stream`]/[`transcode_stdin`].
+    ///
+    /// [`transcode_stream`]: VaapiTranscoder::transcode_stream
+    /// [`transcode_stdin`]: VaapiTranscoder::transcode_stdin
+    pub async fn probe_reader(
+        &self,
+        source: impl Stream<Item = Bytes> + Send + 'static,
+    ) -> Result<String> {
+        let (tx, rx) = tokio::sync::mpsc::channel::<Bytes>(16);
+        tokio::spawn(async move {
+            let mut source = Box::pin(source);
+            while let Some(bytes) = source.next().await {
+                if tx.send(bytes).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let reader = crate::avio_reader::AvioReader::new(rx)?;
+        tokio::task::spawn_blocking(move || probe_with_avio(reader))
+            .await
+            .context("probe_reader's blocking libav task panicked")?
+    }
+
+    /// Transcode a VOD source to a file, reporting progress as it goes
+    ///
+    /// Unlike [`transcode_stream`]/[`transcode_stdin`], the muxed output is
+    /// written directly to `output_path` rather than streamed over stdout;
+    /// stdout instead carries FFmpeg's `-progress` key=value reports, which
+    /// are parsed and handed to `on_progress` as they arrive so a caller
+    /// (e.g. the job subsystem) can track completion percentage.
+    ///
+    /// [`transcode_stream`]: VaapiTranscoder::transcode_stream
+    /// [`transcode_stdin`]: VaapiTranscoder::transcode_stdin
+    pub async fn transcode_to_file(
+        &self,
+        source_url: &str,
+        output_path: &std::path::Path,
+        mut on_progress: impl FnMut(ProgressUpdate),
+    ) -> Result<()> {
+        let args = self.build_job_ffmpeg_args(source_url, output_path);
+
+        info!("Starting FFmpeg job transcode to {}", output_path.display());
+        debug!("FFmpeg command: {} {}", self.config.ffmpeg_path, args.join(" "));
+
+        let mut child = Command::new(&self.config.ffmpeg_path)
+            .args(&args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to spawn FFmpeg process")?;
+
+        let stdout = child
+            .stdout
+            .take()
+            .context("Failed to get FFmpeg stdout")?;
+        let stderr = child
+            .stderr
+            .take()
+            .context("Failed to get FFmpeg stderr")?;
+
+        // Log FFmpeg stderr in background
+        tokio::spawn(async move {
+            let reader = BufReader::new(stderr);
+            let mut lines = reader.lines();
+
+            while let Ok(Some(line)) = lines.next_line().await {
+                debug!("FFmpeg: {}", line);
+            }
+        });
+
+        let mut lines = BufReader::new(stdout).lines();
+        let mut block = ProgressBlock::default();
+        while let Some(line) = lines
+            .next_line()
+            .await
+            .context("Failed to read FFmpeg progress")?
+        {
+            if let ProgressLine::Complete(update) = parse_progress_line(&line, &mut block) {
+                on_progress(update);
+            }
+        }
+
+        let status = child
+            .wait()
+            .await
+            .context("Failed to wait for FFmpeg process")?;
+        if !status.success() {
+            anyhow::bail!("FFmpeg exited with status: {}", status);
+        }
+
+        Ok(())
+    }
+}
+
+/// One `-progress` report, once a block of key=value lines is terminated by
+/// a `progress=` line
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ProgressUpdate {
+    /// `out_time_ms` from the completed block, if FFmpeg reported one
+    pub current_time_ms: Option<u64>,
+    /// Set once FFmpeg reports `progress=end` (the job's last report)
+    pub is_end: bool,
+    /// `frame` from the completed block
+    pub frame: Option<u64>,
+    /// `fps` from the completed block
+    pub fps: Option<f64>,
+    /// `bitrate` from the completed block, in kbit/s (FFmpeg reports it as
+    /// e.g. `1234.5kbits/s`, or `N/A` before it has a measurement yet)
+    pub bitrate_kbps: Option<f64>,
+    /// `total_size` from the completed block, in bytes
+    pub total_size_bytes: Option<u64>,
+    /// `speed` from the completed block, as a multiple of realtime (FFmpeg
+    /// reports it as e.g. `1.02x`)
+    pub speed: Option<f64>,
+}
+
+/// Accumulates key=value lines between `progress=` markers
+#[derive(Debug, Default)]
+struct ProgressBlock {
+    current_time_ms: Option<u64>,
+    frame: Option<u64>,
+    fps: Option<f64>,
+    bitrate_kbps: Option<f64>,
+    total_size_bytes: Option<u64>,
+    speed: Option<f64>,
+}
+
+/// Result of parsing one line of FFmpeg's `-progress` key=value output
+enum ProgressLine {
+    /// `line` was a recognized progress key, accumulated into the block
+    /// passed to [`parse_progress_line`]; no complete report is available
+    /// yet
+    Partial,
+    /// The block was just terminated by a `progress=` line
+    Complete(ProgressUpdate),
+    /// `line` wasn't part of FFmpeg's `-progress` output at all - only
+    /// relevant on the live (`pipe:2`) paths, where `-progress` output is
+    /// merged with ordinary FFmpeg stderr logging (see
+    /// [`VaapiTranscoder::build_ffmpeg_args`]); the caller should log these
+    /// rather than silently dropping them.
+    Other,
+}
+
+/// Parse one line of FFmpeg's `-progress` key=value output
+fn parse_progress_line(line: &str, block: &mut ProgressBlock) -> ProgressLine {
+    let Some((key, value)) = line.split_once('=') else {
+        return ProgressLine::Other;
+    };
+    let value = value.trim();
+
+    match key {
+        "frame" => {
+            block.frame = value.parse().ok();
+            ProgressLine::Partial
+        }
+        "fps" => {
+            block.fps = value.parse().ok();
+            ProgressLine::Partial
+        }
+        "bitrate" => {
+            block.bitrate_kbps = parse_bitrate_kbps(value);
+            ProgressLine::Partial
+        }
+        "total_size" => {
+            block.total_size_bytes = value.parse().ok();
+            ProgressLine::Partial
+        }
+        "out_time_ms" => {
+            // Despite the name, FFmpeg's `-progress` `out_time_ms` is
+            // **microseconds**, not milliseconds - dividing down here keeps
+            // `current_time_ms` in the same unit `duration_ms` (seconds *
+            // 1000) is computed in, so `current/total` in jobs.rs's percent
+            // calculation isn't 1000x too large.
+            block.current_time_ms = value.parse::<u64>().ok().map(|us| us / 1000);
+            ProgressLine::Partial
+        }
+        "speed" => {
+            block.speed = parse_speed(value);
+            ProgressLine::Partial
+        }
+        "progress" => {
+            let update = ProgressUpdate {
+                current_time_ms: block.current_time_ms,
+                is_end: value == "end",
+                frame: block.frame,
+                fps: block.fps,
+                bitrate_kbps: block.bitrate_kbps,
+                total_size_bytes: block.total_size_bytes,
+                speed: block.speed,
+            };
+            *block = ProgressBlock::default();
+            ProgressLine::Complete(update)
+        }
+        // Other recognized `-progress` keys FFmpeg emits every block
+        // (`out_time`, `out_time_us`, `stream_0_0_q`, `dup_frames`,
+        // `drop_frames`) that this module doesn't currently surface -
+        // still a known progress key, just not one worth logging.
+        "out_time" | "out_time_us" | "stream_0_0_q" | "dup_frames" | "drop_frames" => {
+            ProgressLine::Partial
+        }
+        _ => ProgressLine::Other,
+    }
+}
+
+/// Parse a `-progress` `bitrate` value (e.g. `1234.5kbits/s`, or `N/A`
+/// before FFmpeg has a measurement)
+fn parse_bitrate_kbps(value: &str) -> Option<f64> {
+    value.strip_suffix("kbits/s")?.trim().parse().ok()
+}
+
+/// Parse a `-progress` `speed` value (e.g. `1.02x`, or `N/A`)
+fn parse_speed(value: &str) -> Option<f64> {
+    value.strip_suffix('x')?.trim().parse().ok()
+}
+
+/// Open an `AVFormatContext` over `reader`'s custom `AVIOContext` and
+/// summarize whatever libav detects; runs on a blocking thread since every
+/// libav call here is synchronous
+///
+/// Used only by [`VaapiTranscoder::probe_reader`] - see that method's docs
+/// for why this stops at probing rather than decoding or encoding anything.
+fn probe_with_avio(reader: crate::avio_reader::AvioReader) -> Result<String> {
+    // SAFETY: `fmt_ctx` is freed via `avformat_close_input` on every exit
+    // path below; `reader` (and the `AVIOContext` it owns) outlives the
+    // `avformat_open_input`/`avformat_find_stream_info` calls that read
+    // through it, and is dropped only after `fmt_ctx` has been closed.
+    unsafe {
+        let mut fmt_ctx = ffmpeg_sys_next::avformat_alloc_context();
+        if fmt_ctx.is_null() {
+            anyhow::bail!("avformat_alloc_context returned null");
+        }
+        (*fmt_ctx).pb = reader.as_ptr();
+        (*fmt_ctx).flags |= ffmpeg_sys_next::AVFMT_FLAG_CUSTOM_IO;
+
+        let open_result = ffmpeg_sys_next::avformat_open_input(
+            &mut fmt_ctx,
+            std::ptr::null(),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        );
+        if open_result < 0 {
+            ffmpeg_sys_next::avformat_close_input(&mut fmt_ctx);
+            anyhow::bail!("avformat_open_input failed: {open_result}");
+        }
+
+        let find_result = ffmpeg_sys_next::avformat_find_stream_info(fmt_ctx, std::ptr::null_mut());
+        if find_result < 0 {
+            ffmpeg_sys_next::avformat_close_input(&mut fmt_ctx);
+            anyhow::bail!("avformat_find_stream_info failed: {find_result}");
+        }
+
+        let format_name = std::ffi::CStr::from_ptr((*(*fmt_ctx).iformat).name)
+            .to_string_lossy()
+            .into_owned();
+        let nb_streams = (*fmt_ctx).nb_streams;
+        let duration_us = (*fmt_ctx).duration;
+
+        ffmpeg_sys_next::avformat_close_input(&mut fmt_ctx);
+
+        Ok(format!(
+            "format={format_name} streams={nb_streams} duration_us={duration_us}"
+        ))
+    }
 }